@@ -230,3 +230,82 @@ async fn client_mark_compressed_without_header_server_enabled(encoding: Compress
         "protocol error: received message with compressed-flag but no grpc-encoding was specified"
     );
 }
+
+util::parametrized_tests! {
+    codec_preferred_compression,
+    zstd: CompressionEncoding::Zstd,
+    gzip: CompressionEncoding::Gzip,
+}
+
+#[allow(dead_code)]
+async fn codec_preferred_compression(encoding: CompressionEncoding) {
+    use tonic::codec::{Codec, ProstCodec};
+
+    #[derive(Default)]
+    struct PreferringCodec {
+        inner: ProstCodec<SomeData, ()>,
+        encoding: Option<CompressionEncoding>,
+    }
+
+    impl Codec for PreferringCodec {
+        type Encode = SomeData;
+        type Decode = ();
+        type Encoder = <ProstCodec<SomeData, ()> as Codec>::Encoder;
+        type Decoder = <ProstCodec<SomeData, ()> as Codec>::Decoder;
+
+        fn encoder(&mut self) -> Self::Encoder {
+            self.inner.encoder()
+        }
+
+        fn decoder(&mut self) -> Self::Decoder {
+            self.inner.decoder()
+        }
+
+        fn preferred_compression(&self) -> Option<CompressionEncoding> {
+            self.encoding
+        }
+    }
+
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default()).accept_compressed(encoding);
+
+    let request_bytes_counter = Arc::new(AtomicUsize::new(0));
+
+    tokio::spawn({
+        let request_bytes_counter = request_bytes_counter.clone();
+        async move {
+            Server::builder()
+                .layer(
+                    ServiceBuilder::new()
+                        .map_request(move |req| AssertRightEncoding::new(encoding).call(req))
+                        .layer(measure_request_body_size_layer(request_bytes_counter))
+                        .into_inner(),
+                )
+                .add_service(svc)
+                .serve_with_incoming(tokio_stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+                .await
+                .unwrap();
+        }
+    });
+
+    // The client doesn't pick an encoding, so the one preferred by the codec is used.
+    let mut grpc = tonic::client::Grpc::new(mock_io_channel(client).await);
+    grpc.ready().await.unwrap();
+
+    grpc.unary(
+        Request::new(SomeData {
+            data: [0_u8; UNCOMPRESSED_MIN_BODY_SIZE].to_vec(),
+        }),
+        "/test.Test/CompressInputUnary".parse().unwrap(),
+        PreferringCodec {
+            encoding: Some(encoding),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let bytes_sent = request_bytes_counter.load(SeqCst);
+    assert!(bytes_sent < UNCOMPRESSED_MIN_BODY_SIZE);
+}
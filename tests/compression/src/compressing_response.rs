@@ -291,10 +291,14 @@ async fn server_replying_with_unsupported_encoding(encoding: CompressionEncoding
         test_client::TestClient::new(mock_io_channel(client).await).accept_compressed(encoding);
     let status: Status = client.compress_output_unary(()).await.unwrap_err();
 
-    assert_eq!(status.code(), tonic::Code::Unimplemented);
+    assert_eq!(status.code(), tonic::Code::Internal);
     assert_eq!(
         status.message(),
-        "Content is compressed with `br` which isn't supported"
+        format!(
+            "Server violated compression negotiation: response is compressed with `br` \
+             but the client only accepts `{},identity`",
+            encoding
+        )
     );
 }
 
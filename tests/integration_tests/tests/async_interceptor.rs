@@ -0,0 +1,94 @@
+use integration_tests::pb::{test_server, Input, Output};
+use std::{net::SocketAddr, time::Duration};
+use tokio::net::TcpListener;
+use tonic::{
+    client::{AsyncInterceptor, Grpc, InterceptFuture},
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        match req.metadata().get("x-intercepted") {
+            Some(value) if value == "yes" => Ok(Response::new(Output {})),
+            _ => Err(Status::invalid_argument("missing x-intercepted")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn interceptor_sets_metadata() {
+    struct Slow;
+
+    impl AsyncInterceptor for Slow {
+        fn intercept<'a>(&'a self, request: &'a mut Request<()>) -> InterceptFuture<'a> {
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                request
+                    .metadata_mut()
+                    .insert("x-intercepted", "yes".parse().unwrap());
+                Ok(())
+            })
+        }
+    }
+
+    let channel = connect(run_service_in_background().await).await;
+    let mut grpc = Grpc::new(channel).with_async_interceptor(Slow);
+
+    call(&mut grpc).await.unwrap();
+}
+
+#[tokio::test]
+async fn interceptor_error_cancels_call() {
+    struct Reject;
+
+    impl AsyncInterceptor for Reject {
+        fn intercept<'a>(&'a self, _: &'a mut Request<()>) -> InterceptFuture<'a> {
+            Box::pin(async { Err(Status::permission_denied("rejected")) })
+        }
+    }
+
+    let channel = connect(run_service_in_background().await).await;
+    let mut grpc = Grpc::new(channel).with_async_interceptor(Reject);
+
+    let status = call(&mut grpc).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    assert_eq!(status.message(), "rejected");
+}
+
+async fn call(grpc: &mut Grpc<Channel>) -> Result<Response<Output>, Status> {
+    grpc.ready().await.unwrap();
+    grpc.unary(
+        Request::new(Input {}),
+        "/test.Test/UnaryCall".parse().unwrap(),
+        ProstCodec::default(),
+    )
+    .await
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
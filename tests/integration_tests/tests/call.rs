@@ -0,0 +1,100 @@
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::{net::SocketAddr, pin::Pin};
+use tokio::net::TcpListener;
+use tokio_stream::Stream;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        unimplemented!()
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let mut response = Response::new(Box::pin(tokio_stream::iter(
+            req.into_inner()
+                .buf
+                .into_iter()
+                .map(|i| Ok(Output1 { buf: vec![i] })),
+        )) as Self::StreamCallStream);
+        response
+            .metadata_mut()
+            .insert("x-svc", "test1".parse().unwrap());
+        Ok(response)
+    }
+}
+
+#[tokio::test]
+async fn send_and_receive_manually() {
+    let grpc = Grpc::new(connect(run_service_in_background().await).await);
+    let mut call = grpc.open::<Input1, Output1, _>(
+        "/test.Test1/StreamCall".parse().unwrap(),
+        ProstCodec::default(),
+    );
+
+    call.send_message(Input1 { buf: vec![1, 2] }).await.unwrap();
+    call.finish_send();
+
+    let status = call
+        .send_message(Input1 { buf: vec![3] })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::FailedPrecondition);
+
+    assert_eq!(call.response_metadata().await.unwrap()["x-svc"], "test1");
+
+    let mut received = Vec::new();
+    while let Some(output) = call.recv_message().await.unwrap() {
+        received.extend(output.buf);
+    }
+    assert_eq!(received, [1, 2]);
+}
+
+#[tokio::test]
+async fn receive_reports_call_failure() {
+    let grpc = Grpc::new(connect(run_service_in_background().await).await);
+    let mut call = grpc.open::<Input1, Output1, _>(
+        "/test.Test1/Missing".parse().unwrap(),
+        ProstCodec::default(),
+    );
+
+    call.finish_send();
+
+    let status = call.recv_message().await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
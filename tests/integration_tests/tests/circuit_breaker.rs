@@ -0,0 +1,116 @@
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tonic::{
+    client::{CircuitBreakerConfig, Grpc, PerMethodCircuitBreaker, SlidingWindowCircuitBreaker},
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn open_circuit_rejects_calls() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = run_service_in_background(calls.clone()).await;
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let breaker = SlidingWindowCircuitBreaker::new(2, 2, Duration::from_secs(60));
+    let mut grpc = Grpc::new(channel).with_circuit_breaker(breaker);
+
+    for _ in 0..3 {
+        grpc.ready().await.unwrap();
+        let status = grpc
+            .unary::<_, Output1, _>(
+                Request::new(Input1::default()),
+                "/test.Test1/UnaryCall".parse().unwrap(),
+                ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+    }
+
+    // The third call was rejected without reaching the server.
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn per_method_circuit_is_shared_by_clones() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = run_service_in_background(calls.clone()).await;
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let breaker = PerMethodCircuitBreaker::new(
+        CircuitBreakerConfig::default()
+            .failure_threshold(2)
+            .cooldown(Duration::from_secs(60)),
+    );
+    let grpc = Grpc::new(channel).with_circuit_breaker(breaker);
+
+    for mut grpc in [grpc.clone(), grpc.clone(), grpc] {
+        grpc.ready().await.unwrap();
+        let status = grpc
+            .unary::<_, Output1, _>(
+                Request::new(Input1::default()),
+                "/test.Test1/UnaryCall".parse().unwrap(),
+                ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+    }
+
+    // The circuit opened through the first two clones and rejected the call of the third.
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+async fn run_service_in_background(calls: Arc<AtomicUsize>) -> SocketAddr {
+    struct Svc(Arc<AtomicUsize>);
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Err(Status::unavailable("backend is down"))
+        }
+
+        type StreamCallStream = std::pin::Pin<
+            Box<dyn tokio_stream::Stream<Item = Result<Output1, Status>> + Send + 'static>,
+        >;
+
+        async fn stream_call(
+            &self,
+            _: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            unimplemented!()
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc(calls)))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
@@ -68,3 +68,54 @@ async fn test_http_max_header_list_size_and_long_errors() {
 
     jh.await.unwrap();
 }
+
+/// This test checks that the server rejects requests whose headers exceed its
+/// max header list size.
+#[tokio::test]
+async fn test_server_rejects_large_request_headers() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let svc = test_server::TestServer::new(Svc);
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .http2_max_header_list_size(4096)
+            .add_service(svc)
+            .serve_with_incoming_shutdown(
+                tokio_stream::wrappers::TcpListenerStream::new(listener),
+                async { drop(rx.await) },
+            )
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    let mut request = Request::new(Input {});
+    request
+        .metadata_mut()
+        .insert("x-large", "a".repeat(8192).parse().unwrap());
+    client.unary_call(request).await.unwrap_err();
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
@@ -0,0 +1,73 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+#[tokio::test]
+async fn closes_idle_connections() {
+    let (addr, accepted) = run_service_in_background(Duration::from_millis(100)).await;
+
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    // The call takes longer than the idle timeout, which only starts once it is done.
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+    // The server closes the connection, so the client has to reconnect.
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+}
+
+async fn run_service_in_background(
+    max_connection_idle: Duration,
+) -> (SocketAddr, Arc<AtomicUsize>) {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let accepted = Arc::new(AtomicUsize::new(0));
+    let incoming = {
+        let accepted = accepted.clone();
+        tokio_stream::wrappers::TcpListenerStream::new(listener).map(move |io| {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            io
+        })
+    };
+
+    tokio::spawn(async move {
+        Server::builder()
+            .max_connection_idle(max_connection_idle)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+
+    (addr, accepted)
+}
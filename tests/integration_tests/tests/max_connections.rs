@@ -0,0 +1,70 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{net::SocketAddr, time::Duration};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+#[tokio::test]
+async fn closes_connections_over_the_limit() {
+    let addr = run_service_in_background(1).await;
+
+    let mut client1 = TestClient::new(connect(addr).await.unwrap());
+    client1.unary_call(Input {}).await.unwrap();
+
+    // The connection is either refused during the handshake or closed right after it.
+    let rejected = match connect(addr).await {
+        Ok(channel) => TestClient::new(channel).unary_call(Input {}).await.is_err(),
+        Err(_) => true,
+    };
+    assert!(rejected);
+
+    // Closing the first connection makes room for a new one, once the server has seen it close.
+    drop(client1);
+    let accepted = async {
+        loop {
+            if let Ok(channel) = connect(addr).await {
+                if TestClient::new(channel).unary_call(Input {}).await.is_ok() {
+                    break;
+                }
+            }
+            tokio::task::yield_now().await;
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(5), accepted)
+        .await
+        .expect("no connection accepted after the first one closed");
+}
+
+async fn connect(addr: SocketAddr) -> Result<Channel, tonic::transport::Error> {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+}
+
+async fn run_service_in_background(max_connections: usize) -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .max_connections(max_connections)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
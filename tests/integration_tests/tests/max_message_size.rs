@@ -114,6 +114,41 @@ fn max_message_send_size() {
     });
 }
 
+#[test]
+fn max_message_size_from_endpoint() {
+    trace_init();
+
+    // The channel limits apply to clients that do not set their own
+    assert_test_case(TestCase {
+        server_blob_size: 1024,
+        channel_recv_max: Some(1000),
+        expected_code: Some(Code::OutOfRange),
+        ..Default::default()
+    });
+    assert_test_case(TestCase {
+        client_blob_size: 1024,
+        channel_send_max: Some(1000),
+        // TODO: This should return OutOfRange
+        // https://github.com/hyperium/tonic/issues/1334
+        expected_code: Some(Code::Internal),
+        ..Default::default()
+    });
+
+    // Limits set on the client take precedence
+    assert_test_case(TestCase {
+        server_blob_size: 1024,
+        channel_recv_max: Some(1000),
+        client_recv_max: Some(2048),
+        ..Default::default()
+    });
+    assert_test_case(TestCase {
+        client_blob_size: 1024,
+        channel_send_max: Some(1000),
+        client_send_max: Some(2048),
+        ..Default::default()
+    });
+}
+
 #[tokio::test]
 async fn response_stream_limit() {
     let client_blob = vec![0; 1];
@@ -269,6 +304,8 @@ struct TestCase {
     server_recv_max: Option<usize>,
     client_send_max: Option<usize>,
     server_send_max: Option<usize>,
+    channel_recv_max: Option<usize>,
+    channel_send_max: Option<usize>,
 
     expected_code: Option<Code>,
 }
@@ -326,8 +363,21 @@ async fn max_message_run(case: &TestCase) -> Result<(), Status> {
     // Move client to an option so we can _move_ the inner value
     // on the first attempt to connect. All other attempts will fail.
     let mut client = Some(client);
-    let channel = Endpoint::try_from("http://[::]:50051")
-        .unwrap()
+    let endpoint = Endpoint::try_from("http://[::]:50051").unwrap();
+
+    let endpoint = if let Some(size) = case.channel_recv_max {
+        endpoint.max_decoding_message_size(size)
+    } else {
+        endpoint
+    };
+
+    let endpoint = if let Some(size) = case.channel_send_max {
+        endpoint.max_encoding_message_size(size)
+    } else {
+        endpoint
+    };
+
+    let channel = endpoint
         .connect_with_connector(tower::service_fn(move |_| {
             let client = client.take();
 
@@ -0,0 +1,58 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, request: Request<Input>) -> Result<Response<Output>, Status> {
+        if request.metadata().contains_key("x-panic") {
+            panic!("handler failed");
+        }
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn panics_are_turned_into_status() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .handle_error(|panic| {
+                let message = panic.downcast_ref::<&str>().copied().unwrap_or_default();
+                Status::internal(format!("panicked: {}", message))
+            })
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = TestClient::new(connect(addr).await);
+
+    let mut request = Request::new(Input {});
+    request
+        .metadata_mut()
+        .insert("x-panic", "yes".parse().unwrap());
+    let status = client.unary_call(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+    assert_eq!(status.message(), "panicked: handler failed");
+
+    // The connection is still usable.
+    client.unary_call(Input {}).await.unwrap();
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
@@ -0,0 +1,67 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::{net::SocketAddr, time::Duration};
+use tokio::net::TcpListener;
+use tonic::{transport::Server, Code, Request, Response, Status};
+
+#[tokio::test]
+async fn rejects_requests_over_the_limit() {
+    let addr = run_service_in_background(1.0).await;
+
+    let mut client = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    let err = client.unary_call(Request::new(Input {})).await.unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+    let retry_after = err.parse_retry_after().unwrap();
+    assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn limit_is_shared_between_connections() {
+    let addr = run_service_in_background(1.0).await;
+
+    let mut client1 = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+    let mut client2 = test_client::TestClient::connect(format!("http://{}", addr))
+        .await
+        .unwrap();
+
+    client1.unary_call(Request::new(Input {})).await.unwrap();
+
+    let err = client2
+        .unary_call(Request::new(Input {}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::ResourceExhausted);
+}
+
+async fn run_service_in_background(requests_per_second: f64) -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _req: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let svc = test_server::TestServer::new(Svc);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .with_rate_limit(requests_per_second)
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
@@ -0,0 +1,91 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::net::TcpListener;
+use tonic::{
+    body::BoxBody,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn raw_interceptor_sees_every_request() {
+    let seen = Arc::new(AtomicUsize::new(0));
+    let interceptor = {
+        let seen = seen.clone();
+        move |request: &http::Request<BoxBody>| {
+            assert_eq!(request.uri().path(), "/test.Test/UnaryCall");
+            seen.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    };
+
+    let addr = run_service_in_background(interceptor).await;
+    let mut client = TestClient::new(connect(addr).await);
+
+    client.unary_call(Input {}).await.unwrap();
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(seen.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn raw_interceptor_rejects_request() {
+    let interceptor = |request: &http::Request<BoxBody>| match request.headers().get("x-blocked") {
+        Some(_) => Err(Status::permission_denied("blocked")),
+        None => Ok(()),
+    };
+
+    let addr = run_service_in_background(interceptor).await;
+    let mut client = TestClient::new(connect(addr).await);
+
+    client.unary_call(Input {}).await.unwrap();
+
+    let mut request = Request::new(Input {});
+    request
+        .metadata_mut()
+        .insert("x-blocked", "yes".parse().unwrap());
+    let status = client.unary_call(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    assert_eq!(status.message(), "blocked");
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn run_service_in_background<F>(interceptor: F) -> SocketAddr
+where
+    F: FnMut(&http::Request<BoxBody>) -> Result<(), Status> + Clone + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .with_raw_interceptor(interceptor)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
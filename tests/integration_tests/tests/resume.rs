@@ -0,0 +1,128 @@
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::net::TcpListener;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    client::{Grpc, ResumePolicy},
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+/// Streams the messages `[start]..[5]`, where `start` is read from the request. The first call
+/// fails with `code` after three messages.
+struct Svc {
+    calls: Arc<AtomicUsize>,
+    code: Code,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        unimplemented!()
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let start = req.into_inner().buf.first().copied().unwrap_or(0);
+        let first = self.calls.fetch_add(1, Ordering::SeqCst) == 0;
+        let code = self.code;
+
+        let stream = tokio_stream::iter(start..5).map(move |i| {
+            if first && i == 3 {
+                Err(Status::new(code, "connection lost"))
+            } else {
+                Ok(Output1 { buf: vec![i] })
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+fn policy() -> ResumePolicy<impl FnMut(&Output1) -> Option<u8>, impl FnMut(&mut Request<Input1>, u8)>
+{
+    ResumePolicy::new(
+        |output: &Output1| output.buf.first().map(|i| i + 1),
+        |request: &mut Request<Input1>, start| request.get_mut().buf = vec![start],
+    )
+}
+
+#[tokio::test]
+async fn resumes_from_last_token() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = run_service_in_background(calls.clone(), Code::Unavailable).await;
+    let mut grpc = Grpc::new(connect(addr).await);
+
+    let received = call(&mut grpc).await;
+
+    assert_eq!(received, Ok(vec![0, 1, 2, 3, 4]));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn does_not_resume_after_other_codes() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = run_service_in_background(calls.clone(), Code::InvalidArgument).await;
+    let mut grpc = Grpc::new(connect(addr).await);
+
+    let received = call(&mut grpc).await;
+
+    assert_eq!(received, Err(Code::InvalidArgument));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+async fn call(grpc: &mut Grpc<Channel>) -> Result<Vec<u8>, Code> {
+    grpc.ready().await.unwrap();
+    let stream = grpc
+        .server_streaming_with_resume(
+            Request::new(Input1 { buf: vec![] }),
+            "/test.Test1/StreamCall".parse().unwrap(),
+            ProstCodec::default(),
+            policy(),
+        )
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut stream = std::pin::pin!(stream);
+    let mut received = Vec::new();
+    while let Some(output) = stream.next().await {
+        received.extend(output.map_err(|status| status.code())?.buf);
+    }
+    Ok(received)
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn run_service_in_background(calls: Arc<AtomicUsize>, code: Code) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc { calls, code }))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
@@ -0,0 +1,70 @@
+use integration_tests::pb::{client_stream_server, Input1, Output1};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Request, Response, Status, Streaming,
+};
+
+#[tokio::test]
+async fn client_streaming_scoped_borrows_request_data() {
+    let addr = run_service_in_background().await;
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut grpc = Grpc::new(channel);
+    grpc.ready().await.unwrap();
+
+    let buf = vec![1u8, 2, 3];
+    // The stream borrows `buf`, so it isn't `'static`.
+    let stream = tokio_stream::iter(&buf).map(|byte| Input1 { buf: vec![*byte] });
+
+    let response = grpc
+        .client_streaming_scoped::<_, _, Output1, _>(
+            Request::new(stream),
+            "/test.ClientStream/ClientStreamCall".parse().unwrap(),
+            ProstCodec::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.into_inner().buf, buf);
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    struct Svc;
+
+    // Concatenates the bytes of the request messages.
+    #[tonic::async_trait]
+    impl client_stream_server::ClientStream for Svc {
+        async fn client_stream_call(
+            &self,
+            req: Request<Streaming<Input1>>,
+        ) -> Result<Response<Output1>, Status> {
+            let mut stream = req.into_inner();
+            let mut buf = Vec::new();
+            while let Some(input) = stream.message().await? {
+                buf.extend(input.buf);
+            }
+            Ok(Response::new(Output1 { buf }))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(client_stream_server::ClientStreamServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
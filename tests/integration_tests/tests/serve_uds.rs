@@ -0,0 +1,65 @@
+#![cfg(unix)]
+
+use hyper_util::rt::TokioIo;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use std::io;
+use tokio::{net::UnixStream, sync::oneshot};
+use tonic::{
+    transport::{server::UdsConnectInfo, Endpoint, Server, Uri},
+    Request, Response, Status,
+};
+use tower::service_fn;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        assert!(req.extensions().get::<UdsConnectInfo>().is_some());
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn serves_on_unix_socket_and_removes_it() {
+    let mut path = std::env::temp_dir();
+    path.push(format!("tonic-serve-uds-{}", std::process::id()));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let jh = tokio::spawn({
+        let path = path.clone();
+        async move {
+            Server::builder()
+                .add_service(test_server::TestServer::new(Svc))
+                .serve_uds_with_shutdown(path, async { drop(rx.await) })
+                .await
+                .unwrap();
+        }
+    });
+
+    let connect_path = path.clone();
+    let channel = Endpoint::try_from("http://[::]:50051")
+        .unwrap()
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let path = connect_path.clone();
+            async move {
+                // Wait for the server to bind the socket.
+                loop {
+                    match UnixStream::connect(&path).await {
+                        Ok(stream) => return Ok::<_, io::Error>(TokioIo::new(stream)),
+                        Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+                    }
+                }
+            }
+        }))
+        .await
+        .unwrap();
+
+    let mut client = test_client::TestClient::new(channel);
+    client.unary_call(Input {}).await.unwrap();
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+
+    assert!(!path.exists());
+}
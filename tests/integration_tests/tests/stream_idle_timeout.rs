@@ -0,0 +1,85 @@
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::{net::SocketAddr, time::Duration};
+use tokio::net::TcpListener;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn quiet_server_trips_idle_timeout() {
+    let addr = run_service_in_background().await;
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut grpc = Grpc::new(channel).with_stream_idle_timeout(Duration::from_millis(200));
+    grpc.ready().await.unwrap();
+
+    let mut stream = grpc
+        .server_streaming::<_, _, Output1, _>(
+            Request::new(Input1 { buf: vec![3] }),
+            "/test.Test1/StreamCall".parse().unwrap(),
+            ProstCodec::default(),
+        )
+        .await
+        .unwrap()
+        .into_inner();
+
+    // Messages arrive faster than the timeout, so the timer keeps being reset.
+    for _ in 0..3 {
+        stream.message().await.unwrap().unwrap();
+    }
+
+    // The server then goes quiet without closing the stream.
+    let status = stream.message().await.unwrap_err();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+    assert!(stream.message().await.unwrap().is_none());
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+            unimplemented!()
+        }
+
+        type StreamCallStream = std::pin::Pin<
+            Box<dyn tokio_stream::Stream<Item = Result<Output1, Status>> + Send + 'static>,
+        >;
+
+        async fn stream_call(
+            &self,
+            req: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let count = req.into_inner().buf[0] as usize;
+            let stream = async_stream::stream! {
+                for _ in 0..count {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    yield Ok(Output1::default());
+                }
+                std::future::pending::<()>().await;
+            };
+
+            Ok(Response::new(Box::pin(stream)))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
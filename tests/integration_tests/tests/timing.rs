@@ -0,0 +1,111 @@
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::{net::SocketAddr, time::Duration};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+const LATENCY: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+async fn unary_timed_measures_call() {
+    let addr = run_service_in_background().await;
+    let mut grpc = connect(addr).await;
+    grpc.ready().await.unwrap();
+
+    let (_, elapsed) = grpc
+        .unary_timed::<_, Output1, _>(
+            Request::new(Input1::default()),
+            "/test.Test1/UnaryCall".parse().unwrap(),
+            ProstCodec::default(),
+        )
+        .await
+        .unwrap();
+
+    assert!(elapsed >= LATENCY);
+}
+
+#[tokio::test]
+async fn server_streaming_timed_tracks_stream() {
+    let addr = run_service_in_background().await;
+    let mut grpc = connect(addr).await;
+    grpc.ready().await.unwrap();
+
+    let (response, timing) = grpc
+        .server_streaming_timed::<_, Output1, _>(
+            Request::new(Input1::default()),
+            "/test.Test1/StreamCall".parse().unwrap(),
+            ProstCodec::default(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(timing.time_to_first_message(), None);
+    assert_eq!(timing.total_duration(), None);
+
+    let mut stream = response.into_inner();
+    stream.next().await.unwrap().unwrap();
+    let first = timing.time_to_first_message().unwrap();
+    assert!(first >= LATENCY);
+    assert_eq!(timing.total_duration(), None);
+
+    while stream.next().await.is_some() {}
+    let total = timing.total_duration().unwrap();
+    assert!(total >= first + LATENCY);
+    assert_eq!(timing.time_to_first_message(), Some(first));
+}
+
+async fn connect(addr: SocketAddr) -> Grpc<Channel> {
+    let channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    Grpc::new(channel)
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+            tokio::time::sleep(LATENCY).await;
+            Ok(Response::new(Output1::default()))
+        }
+
+        type StreamCallStream = std::pin::Pin<
+            Box<dyn tokio_stream::Stream<Item = Result<Output1, Status>> + Send + 'static>,
+        >;
+
+        async fn stream_call(
+            &self,
+            _: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            let stream = tokio_stream::iter([Output1::default(), Output1::default()]).then(
+                |msg| async move {
+                    tokio::time::sleep(LATENCY).await;
+                    Ok(msg)
+                },
+            );
+            Ok(Response::new(Box::pin(stream)))
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
@@ -0,0 +1,91 @@
+use http::HeaderValue;
+use integration_tests::pb::{test_server, Input, Output};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tonic::{
+    client::{Grpc, StaticTokenSource, TokenFuture, TokenSource},
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        match req.metadata().get("authorization") {
+            Some(token) if token == "Bearer secret" => Ok(Response::new(Output {})),
+            _ => Err(Status::unauthenticated("invalid token")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn token_is_sent_as_bearer() {
+    let channel = connect(run_service_in_background().await).await;
+    let mut grpc = Grpc::new(channel)
+        .with_token_source(StaticTokenSource::new(HeaderValue::from_static("secret")));
+
+    // An authorization header set on the request is replaced.
+    let mut request = Request::new(Input {});
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer other".parse().unwrap());
+
+    call(&mut grpc, request).await.unwrap();
+}
+
+#[tokio::test]
+async fn token_source_errors_fail_the_call() {
+    struct Failing;
+
+    impl TokenSource for Failing {
+        fn token(&self) -> TokenFuture<'_> {
+            Box::pin(async { Err(Status::unavailable("token endpoint is down")) })
+        }
+    }
+
+    let channel = connect(run_service_in_background().await).await;
+    let mut grpc = Grpc::new(channel).with_token_source(Failing);
+
+    let status = call(&mut grpc, Request::new(Input {})).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "token endpoint is down");
+}
+
+async fn call(
+    grpc: &mut Grpc<Channel>,
+    request: Request<Input>,
+) -> Result<Response<Output>, Status> {
+    grpc.ready().await.unwrap();
+    grpc.unary(
+        request,
+        "/test.Test/UnaryCall".parse().unwrap(),
+        ProstCodec::default(),
+    )
+    .await
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
@@ -0,0 +1,51 @@
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{Channel, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn warm_up_connects_lazy_channel() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect_lazy();
+
+    channel.warm_up().await.unwrap();
+
+    // The channel is still usable for regular calls afterwards.
+    TestClient::new(channel).unary_call(Input {}).await.unwrap();
+}
+
+#[tokio::test]
+async fn warm_up_reports_connection_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let mut channel = Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect_lazy();
+
+    assert!(channel.warm_up().await.is_err());
+}
@@ -0,0 +1,84 @@
+use integration_tests::pb::{test1_server, Input1, Output1};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    transport::{Channel, Server},
+    Code, Request, Response, Status,
+};
+
+#[tokio::test]
+async fn with_inner_keeps_config() {
+    let first = connect(run_service_in_background().await).await;
+    let second = connect(run_service_in_background().await).await;
+
+    let grpc = Grpc::new(first).max_decoding_message_size(16);
+    let mut grpc = grpc.with_inner(second);
+
+    call(&mut grpc, 8).await.unwrap();
+
+    // The decoding limit set before swapping the channel still applies.
+    let status = call(&mut grpc, 64).await.unwrap_err();
+    assert_eq!(status.code(), Code::OutOfRange);
+
+    *grpc.get_mut_inner() = connect(run_service_in_background().await).await;
+
+    let status = call(&mut grpc, 64).await.unwrap_err();
+    assert_eq!(status.code(), Code::OutOfRange);
+}
+
+async fn call(grpc: &mut Grpc<Channel>, len: usize) -> Result<Response<Output1>, Status> {
+    grpc.ready().await.unwrap();
+    grpc.unary(
+        Request::new(Input1 { buf: vec![0; len] }),
+        "/test.Test1/UnaryCall".parse().unwrap(),
+        ProstCodec::default(),
+    )
+    .await
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Channel::from_shared(format!("http://{}", addr))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn run_service_in_background() -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test1_server::Test1 for Svc {
+        async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+            Ok(Response::new(Output1 {
+                buf: req.into_inner().buf,
+            }))
+        }
+
+        type StreamCallStream = std::pin::Pin<
+            Box<dyn tokio_stream::Stream<Item = Result<Output1, Status>> + Send + 'static>,
+        >;
+
+        async fn stream_call(
+            &self,
+            _: Request<Input1>,
+        ) -> Result<Response<Self::StreamCallStream>, Status> {
+            unimplemented!()
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
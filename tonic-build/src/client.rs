@@ -81,6 +81,11 @@ pub(crate) fn generate_internal<T: Service>(
                     Self { inner }
                 }
 
+                pub fn with_config(inner: T, config: tonic::client::GrpcConfig) -> Self {
+                    let inner = tonic::client::Grpc::with_config(inner, config);
+                    Self { inner }
+                }
+
                 pub fn with_interceptor<F>(inner: T, interceptor: F) -> #service_ident<InterceptedService<T, F>>
                 where
                     F: tonic::service::Interceptor,
@@ -168,6 +168,10 @@ pub mod server_reflection_client {
             let inner = tonic::client::Grpc::with_origin(inner, origin);
             Self { inner }
         }
+        pub fn with_config(inner: T, config: tonic::client::GrpcConfig) -> Self {
+            let inner = tonic::client::Grpc::with_config(inner, config);
+            Self { inner }
+        }
         pub fn with_interceptor<F>(
             inner: T,
             interceptor: F,
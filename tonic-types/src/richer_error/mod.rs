@@ -3,6 +3,7 @@ use prost::{
     DecodeError, Message,
 };
 use prost_types::Any;
+use std::collections::HashMap;
 use tonic::{metadata::MetadataMap, Code};
 
 mod error_details;
@@ -145,6 +146,37 @@ pub trait StatusExt: crate::sealed::Sealed {
         details: impl IntoIterator<Item = ErrorDetail>,
     ) -> tonic::Status;
 
+    /// Generates a `tonic::Status` with [`ErrorInfo`] details, the machine
+    /// readable error classification required by [AIP-193].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use tonic::{Code, Status};
+    /// use tonic_types::StatusExt;
+    ///
+    /// let mut metadata = HashMap::new();
+    /// metadata.insert("service".to_string(), "pubsub.googleapis.com".to_string());
+    ///
+    /// let status = Status::with_error_info(
+    ///     Code::PermissionDenied,
+    ///     "api disabled",
+    ///     "API_DISABLED",
+    ///     "googleapis.com",
+    ///     metadata,
+    /// );
+    /// ```
+    ///
+    /// [AIP-193]: https://google.aip.dev/193
+    fn with_error_info(
+        code: Code,
+        message: impl Into<String>,
+        reason: impl Into<String>,
+        domain: impl Into<String>,
+        metadata: impl Into<HashMap<String, String>>,
+    ) -> tonic::Status;
+
     /// Can be used to check if the error details contained in `tonic::Status`
     /// are malformed or not. Tries to get an [`ErrorDetails`] struct from a
     /// `tonic::Status`. If some `prost::DecodeError` occurs, it will be
@@ -603,6 +635,20 @@ impl StatusExt for tonic::Status {
         )
     }
 
+    fn with_error_info(
+        code: Code,
+        message: impl Into<String>,
+        reason: impl Into<String>,
+        domain: impl Into<String>,
+        metadata: impl Into<HashMap<String, String>>,
+    ) -> Self {
+        tonic::Status::with_error_details(
+            code,
+            message,
+            ErrorDetails::with_error_info(reason, domain, metadata),
+        )
+    }
+
     fn check_error_details(&self) -> Result<ErrorDetails, DecodeError> {
         let status = pb::Status::decode(self.details())?;
 
@@ -1065,4 +1111,26 @@ mod tests {
             "Extracted details vec differs from original details vec"
         );
     }
+
+    #[test]
+    fn gen_status_with_error_info() {
+        let mut metadata = HashMap::new();
+        metadata.insert("instanceLimitPerRequest".into(), "100".into());
+
+        let status = Status::with_error_info(
+            Code::ResourceExhausted,
+            "too many instances",
+            "INSTANCE_LIMIT",
+            "example.local",
+            metadata.clone(),
+        );
+
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "too many instances");
+
+        let error_info = status.get_details_error_info().unwrap();
+        assert_eq!(error_info.reason, "INSTANCE_LIMIT");
+        assert_eq!(error_info.domain, "example.local");
+        assert_eq!(error_info.metadata, metadata);
+    }
 }
@@ -0,0 +1,154 @@
+use bencher::{benchmark_group, benchmark_main, Bencher};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use flate2::{read::GzEncoder, Compression};
+use http_body::{Body, Frame, SizeHint};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::Read,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Once,
+    },
+    task::{Context, Poll},
+};
+use tonic::{
+    codec::{CompressionEncoding, DecodeBuf, Decoder},
+    Status, Streaming,
+};
+
+/// Counts allocations, to report the allocations made per decompressed message.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+macro_rules! bench {
+    ($name:ident, $message_size:expr, $message_count:expr) => {
+        fn $name(b: &mut Bencher) {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+
+            let body = MockBody(make_payload($message_size, $message_count));
+            b.bytes = ($message_size * $message_count) as u64;
+
+            let decode = || {
+                rt.block_on(async {
+                    let mut stream = Streaming::new_request(
+                        MockDecoder,
+                        body.clone(),
+                        Some(CompressionEncoding::Gzip),
+                        None,
+                    );
+
+                    let mut count = 0;
+                    while let Some(msg) = stream.message().await.unwrap() {
+                        assert_eq!($message_size, msg);
+                        count += 1;
+                    }
+                    assert_eq!(count, $message_count);
+                })
+            };
+
+            static REPORT: Once = Once::new();
+            REPORT.call_once(|| {
+                let before = ALLOCATIONS.load(Ordering::Relaxed);
+                decode();
+                let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+                eprintln!(
+                    "{}: {} allocations per message",
+                    stringify!($name),
+                    allocations / $message_count
+                );
+            });
+
+            b.iter(decode)
+        }
+    };
+}
+
+#[derive(Clone)]
+struct MockBody(Bytes);
+
+impl Body for MockBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        if self.0.has_remaining() {
+            let len = self.0.len();
+            Poll::Ready(Some(Ok(Frame::data(self.0.split_to(len)))))
+        } else {
+            Poll::Ready(None)
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(self.0.len() as u64)
+    }
+}
+
+/// Returns the length of each message, without copying it.
+struct MockDecoder;
+
+impl Decoder for MockDecoder {
+    type Item = usize;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let len = buf.remaining();
+        buf.advance(len);
+        Ok(Some(len))
+    }
+}
+
+fn make_payload(message_length: usize, message_count: usize) -> Bytes {
+    let mut msg = Vec::new();
+    GzEncoder::new(&vec![97u8; message_length][..], Compression::new(6))
+        .read_to_end(&mut msg)
+        .unwrap();
+
+    let mut buf = BytesMut::new();
+    for _ in 0..message_count {
+        buf.reserve(msg.len() + 5);
+        buf.put_u8(1);
+        buf.put_u32(msg.len() as u32);
+        buf.put(&msg[..]);
+    }
+
+    buf.freeze()
+}
+
+bench!(gzip_message_size_1k, 1_000, 100);
+bench!(gzip_message_size_10k, 10_000, 100);
+bench!(gzip_message_size_100k, 100_000, 100);
+
+benchmark_group!(
+    gzip,
+    gzip_message_size_1k,
+    gzip_message_size_10k,
+    gzip_message_size_100k
+);
+
+benchmark_main!(gzip);
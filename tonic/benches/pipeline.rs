@@ -0,0 +1,79 @@
+use bencher::{benchmark_group, benchmark_main, Bencher};
+use http::uri::PathAndQuery;
+use std::{convert::Infallible, time::Duration};
+use tokio_stream::StreamExt;
+use tonic::{body::BoxBody, client::Grpc, codec::ProstCodec, Request};
+
+const CALLS: usize = 16;
+
+// Echoes the request message back after a simulated network round-trip.
+async fn echo(request: http::Request<BoxBody>) -> Result<http::Response<BoxBody>, Infallible> {
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    Ok(http::Response::new(request.into_body()))
+}
+
+fn path() -> PathAndQuery {
+    PathAndQuery::from_static("/echo.Echo/Echo")
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .expect("runtime")
+}
+
+fn sequential(b: &mut Bencher) {
+    let rt = runtime();
+    let mut grpc = Grpc::new(tower::service_fn(echo));
+
+    b.iter(|| {
+        rt.block_on(async {
+            for i in 0..CALLS {
+                grpc.ready().await.unwrap();
+                let response = grpc
+                    .unary(
+                        Request::new(i.to_string()),
+                        path(),
+                        ProstCodec::<String, String>::default(),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(response.into_inner(), i.to_string());
+            }
+        })
+    })
+}
+
+fn pipelined(b: &mut Bencher) {
+    pipeline_calls(b, CALLS)
+}
+
+// Many calls in flight at once, which only pay for polling the calls that made progress.
+fn pipelined_many(b: &mut Bencher) {
+    pipeline_calls(b, 64 * CALLS)
+}
+
+fn pipeline_calls(b: &mut Bencher, calls: usize) {
+    let rt = runtime();
+    let grpc = Grpc::new(tower::service_fn(echo));
+
+    b.iter(|| {
+        rt.block_on(async {
+            let requests = (0..calls).map(|i| Request::new(i.to_string()));
+            let mut responses =
+                grpc.pipeline(requests, path(), ProstCodec::<String, String>::default());
+
+            let mut i = 0;
+            while let Some(response) = responses.next().await {
+                assert_eq!(response.unwrap().into_inner(), i.to_string());
+                i += 1;
+            }
+            assert_eq!(i, calls);
+        })
+    })
+}
+
+benchmark_group!(pipeline, sequential, pipelined, pipelined_many);
+
+benchmark_main!(pipeline);
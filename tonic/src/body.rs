@@ -14,6 +14,18 @@ where
     body.map_err(crate::Status::map_error).boxed_unsync()
 }
 
+/// Create a `BoxBody` holding the given bytes.
+///
+/// ```rust
+/// # use tonic::body::from_bytes;
+/// let body = from_bytes(bytes::Bytes::from_static(b"hello"));
+/// ```
+pub fn from_bytes(bytes: bytes::Bytes) -> BoxBody {
+    http_body_util::Full::new(bytes)
+        .map_err(|err| match err {})
+        .boxed_unsync()
+}
+
 /// Create an empty `BoxBody`
 pub fn empty_body() -> BoxBody {
     http_body_util::Empty::new()
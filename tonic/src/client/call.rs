@@ -0,0 +1,298 @@
+use super::resume::CallFuture;
+use crate::{codec::Streaming, metadata::MetadataMap, Response, Status};
+use futures_sink::Sink;
+use std::{
+    fmt, future,
+    future::Future,
+    pin::{pin, Pin},
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::Sender;
+use tokio_util::sync::PollSender;
+
+/// A streaming call driven message by message, returned by [`Grpc::open`].
+///
+/// Unlike the canonical call shapes, the request stream is fed imperatively with
+/// [`Call::send_message`] and half-closed with [`Call::finish_send`], while the responses are
+/// read with [`Call::recv_message`]. Sending and receiving can be interleaved in any order, which
+/// is useful for conformance tests and protocol experiments.
+///
+/// The call is dispatched when it is first driven by one of its methods. Dropping the [`Call`]
+/// cancels it.
+///
+/// [`Grpc::open`]: super::Grpc::open
+pub struct Call<M1, M2> {
+    sender: Option<Sender<M1>>,
+    state: State<M2>,
+}
+
+enum State<M2> {
+    Opening(CallFuture<M2>),
+    Open(Box<Response<Streaming<M2>>>),
+    Failed(Status),
+}
+
+impl<M1, M2> Call<M1, M2> {
+    pub(crate) fn new(sender: Sender<M1>, response: CallFuture<M2>) -> Self {
+        Self {
+            sender: Some(sender),
+            state: State::Opening(response),
+        }
+    }
+
+    /// Send a message on the request stream.
+    ///
+    /// Waits until the request stream has room for the message. Fails with a `FailedPrecondition`
+    /// status once the request stream is closed, either by [`Call::finish_send`] or because the
+    /// call has ended, in which case [`Call::recv_message`] returns the outcome of the call.
+    pub async fn send_message(&mut self, message: M1) -> Result<(), Status> {
+        let Some(sender) = &self.sender else {
+            return Err(request_stream_closed());
+        };
+
+        let state = &mut self.state;
+        let mut send = pin!(sender.send(message));
+        future::poll_fn(|cx| {
+            if let Poll::Ready(result) = send.as_mut().poll(cx) {
+                return Poll::Ready(result.map_err(|_| request_stream_closed()));
+            }
+
+            // The request stream is only read once the call is dispatched.
+            let _ = state.poll_open(cx);
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Half-close the request stream, signaling the server that no more messages will be sent.
+    pub fn finish_send(&mut self) {
+        self.sender = None;
+    }
+
+    /// Receive the next message of the response stream.
+    ///
+    /// Returns `Ok(None)` once the server has ended the call successfully, and the status of the
+    /// call if it failed.
+    pub async fn recv_message(&mut self) -> Result<Option<M2>, Status> {
+        self.open().await?.get_mut().message().await
+    }
+
+    /// Wait for the response headers and return their metadata.
+    pub async fn response_metadata(&mut self) -> Result<&MetadataMap, Status> {
+        Ok(self.open().await?.metadata())
+    }
+
+    /// Return the trailing metadata, reading the rest of the response stream first.
+    ///
+    /// Like [`Streaming::trailers`], this discards any remaining response messages.
+    pub async fn trailers(&mut self) -> Result<Option<MetadataMap>, Status> {
+        self.open().await?.get_mut().trailers().await
+    }
+
+    async fn open(&mut self) -> Result<&mut Response<Streaming<M2>>, Status> {
+        future::poll_fn(|cx| self.state.poll_open(cx)).await?;
+
+        match &mut self.state {
+            State::Open(response) => Ok(response),
+            _ => unreachable!("the call is open"),
+        }
+    }
+}
+
+impl<M2> State<M2> {
+    /// Drive the call until the response headers are received.
+    fn poll_open(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        if let State::Opening(response) = self {
+            *self = match response.as_mut().poll(cx) {
+                Poll::Ready(Ok(response)) => State::Open(Box::new(response)),
+                Poll::Ready(Err(status)) => State::Failed(status),
+                Poll::Pending => return Poll::Pending,
+            };
+        }
+
+        match self {
+            State::Opening(_) => unreachable!("the call has been dispatched"),
+            State::Open(_) => Poll::Ready(Ok(())),
+            State::Failed(status) => Poll::Ready(Err(status.clone())),
+        }
+    }
+}
+
+impl<M1, M2> fmt::Debug for Call<M1, M2> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::Opening(_) => "Opening",
+            State::Open(_) => "Open",
+            State::Failed(_) => "Failed",
+        };
+
+        f.debug_struct("Call")
+            .field("send_closed", &self.sender.is_none())
+            .field("state", &state)
+            .finish()
+    }
+}
+
+/// The request stream of a client side streaming call, returned by
+/// [`Grpc::client_streaming_sink`].
+///
+/// Closing the sink, or dropping it, ends the request stream. Sending fails with a
+/// `FailedPrecondition` status once the call has ended, in which case the response future returns
+/// the outcome of the call.
+///
+/// [`Grpc::client_streaming_sink`]: super::Grpc::client_streaming_sink
+pub struct RequestSink<M> {
+    inner: PollSender<M>,
+}
+
+impl<M: Send + 'static> RequestSink<M> {
+    pub(crate) fn new(sender: Sender<M>) -> Self {
+        Self {
+            inner: PollSender::new(sender),
+        }
+    }
+
+    /// The number of messages sent to the sink that the call hasn't picked up yet.
+    ///
+    /// Messages are queued until the request body is polled by the transport, which encodes
+    /// them and writes them as HTTP/2 flow control allows. A count that stays above zero means
+    /// the server, or the connection, doesn't keep up with the messages being sent. Messages
+    /// picked up but not written yet are not counted. Returns 0 once the sink is closed.
+    pub fn n_pending(&self) -> usize {
+        self.inner
+            .get_ref()
+            .map_or(0, |sender| sender.max_capacity() - sender.capacity())
+    }
+
+    /// Whether every message sent to the sink has been picked up by the call, see
+    /// [`RequestSink::n_pending`].
+    pub fn is_caught_up(&self) -> bool {
+        self.n_pending() == 0
+    }
+}
+
+impl<M: Send> Sink<M> for RequestSink<M> {
+    type Error = Status;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Pin::new(&mut self.inner)
+            .poll_ready(cx)
+            .map_err(|_| request_stream_closed())
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, message: M) -> Result<(), Status> {
+        Pin::new(&mut self.inner)
+            .start_send(message)
+            .map_err(|_| request_stream_closed())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Pin::new(&mut self.inner)
+            .poll_flush(cx)
+            .map_err(|_| request_stream_closed())
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        Pin::new(&mut self.inner)
+            .poll_close(cx)
+            .map_err(|_| request_stream_closed())
+    }
+}
+
+impl<M> fmt::Debug for RequestSink<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSink").finish_non_exhaustive()
+    }
+}
+
+fn request_stream_closed() -> Status {
+    Status::failed_precondition("the request stream of the call is closed")
+}
+
+#[cfg(all(test, feature = "prost"))]
+mod tests {
+    use crate::{body::BoxBody, client::Grpc, codec::ProstCodec, Code, Request};
+    use futures_util::SinkExt;
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn interleaves_send_and_receive() {
+        // Echoes the request frames back, the end of the request stream ends the call.
+        let echo = tower::service_fn(|request: http::Request<BoxBody>| async {
+            Ok::<_, Infallible>(http::Response::new(request.into_body()))
+        });
+
+        let grpc = Grpc::new(echo);
+        let mut call = grpc
+            .open::<String, String, _>("/echo.Echo/Echo".parse().unwrap(), ProstCodec::default());
+
+        for message in ["a", "b"] {
+            call.send_message(message.to_string()).await.unwrap();
+            assert_eq!(call.recv_message().await.unwrap().unwrap(), message);
+        }
+
+        call.finish_send();
+        let status = call.send_message("c".to_string()).await.unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        assert_eq!(call.recv_message().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn client_streaming_sink_sends_messages() {
+        let echo = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            assert_eq!(request.headers()["x-client"], "sink");
+            Ok::<_, Infallible>(http::Response::new(request.into_body()))
+        });
+
+        let grpc = Grpc::new(echo);
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert("x-client", "sink".parse().unwrap());
+        let (mut sink, response) = grpc.client_streaming_sink::<String, String, _>(
+            request,
+            "/echo.Echo/Echo".parse().unwrap(),
+            ProstCodec::default(),
+        );
+        let response = tokio::spawn(response);
+
+        sink.send("a".to_string()).await.unwrap();
+        sink.close().await.unwrap();
+
+        let response = response.await.unwrap().unwrap();
+        assert_eq!(response.into_inner(), "a");
+
+        let status = sink.send("b".to_string()).await.unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+
+    #[tokio::test]
+    async fn request_sink_counts_pending_messages() {
+        let echo = tower::service_fn(|request: http::Request<BoxBody>| async {
+            Ok::<_, Infallible>(http::Response::new(request.into_body()))
+        });
+
+        let grpc = Grpc::new(echo);
+        let (mut sink, response) = grpc.client_streaming_sink::<String, String, _>(
+            Request::new(()),
+            "/echo.Echo/Echo".parse().unwrap(),
+            ProstCodec::default(),
+        );
+        assert!(sink.is_caught_up());
+        sink.send("a".to_string()).await.unwrap();
+        // The call is not dispatched yet, so the message waits in the sink.
+        assert_eq!(sink.n_pending(), 1);
+        assert!(!sink.is_caught_up());
+
+        let response = tokio::spawn(response);
+        while !sink.is_caught_up() {
+            tokio::task::yield_now().await;
+        }
+        sink.close().await.unwrap();
+        assert_eq!(sink.n_pending(), 0);
+
+        let response = response.await.unwrap().unwrap();
+        assert_eq!(response.into_inner(), "a");
+    }
+}
@@ -0,0 +1,549 @@
+use crate::{Code, Status};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Guards a [`Grpc`] client against a failing backend.
+///
+/// Installed with [`Grpc::with_circuit_breaker`]. Before each call the client checks
+/// [`CircuitBreaker::is_open_for`] and fails fast with an `Unavailable` status while the circuit
+/// is open. Once a call completes, its outcome is reported through [`CircuitBreaker::record`].
+/// For calls with a streaming response, that is when the response stream ends.
+///
+/// By default the whole backend shares one circuit: `is_open_for` asks
+/// [`CircuitBreaker::is_open`], and `record` reports statuses that indicate an unhealthy backend
+/// (`Unavailable`, `DeadlineExceeded`, `Internal`, `Unknown` and `DataLoss`) through
+/// [`CircuitBreaker::record_failure`]. Any other outcome means the backend handled the request
+/// and is reported through [`CircuitBreaker::record_success`]. Breakers that track each method
+/// separately override both methods.
+///
+/// The breaker is shared by every clone of the client, so implementations use interior
+/// mutability.
+///
+/// [`Grpc`]: super::Grpc
+/// [`Grpc::with_circuit_breaker`]: super::Grpc::with_circuit_breaker
+pub trait CircuitBreaker: Send + Sync + 'static {
+    /// Returns `true` if calls should be rejected without reaching the backend.
+    fn is_open(&self) -> bool;
+
+    /// Records a call that reached a healthy backend.
+    fn record_success(&self);
+
+    /// Records a call that failed because of an unhealthy backend.
+    fn record_failure(&self);
+
+    /// Returns `true` if calls to the method at `path` should be rejected without reaching the
+    /// backend.
+    fn is_open_for(&self, path: &str) -> bool {
+        let _ = path;
+        self.is_open()
+    }
+
+    /// Records the outcome of a call to the method at `path`, `status` is `None` if it succeeded.
+    fn record(&self, path: &str, status: Option<&Status>) {
+        let _ = path;
+        match status {
+            Some(status) if is_failure(status) => self.record_failure(),
+            _ => self.record_success(),
+        }
+    }
+}
+
+const FAILURE_CODES: [Code; 5] = [
+    Code::Unavailable,
+    Code::DeadlineExceeded,
+    Code::Internal,
+    Code::Unknown,
+    Code::DataLoss,
+];
+
+fn is_failure(status: &Status) -> bool {
+    FAILURE_CODES.contains(&status.code())
+}
+
+/// A [`CircuitBreaker`] based on the outcomes of the most recent calls.
+///
+/// The circuit opens once `failure_threshold` of the last `window_size` calls have failed. It
+/// stays open for `open_duration`, after which it is half-open: a single probe call is let
+/// through while other calls are still rejected. If the first call to complete after that fails,
+/// the circuit opens again right away. If it succeeds, the window is cleared and the circuit
+/// closes. A probe that doesn't report back within `open_duration`, for example because the call
+/// was cancelled, is replaced by a new one. Outcomes of calls that complete while the circuit is
+/// open are ignored.
+pub struct SlidingWindowCircuitBreaker {
+    window_size: usize,
+    failure_threshold: usize,
+    open_duration: Duration,
+    state: Mutex<WindowState>,
+}
+
+#[derive(Debug)]
+struct WindowState {
+    /// `true` for every failed call in the window.
+    outcomes: VecDeque<bool>,
+    failures: usize,
+    opened_at: Option<Instant>,
+    /// When the probe of the half-open circuit was let through.
+    probe_since: Option<Instant>,
+}
+
+impl SlidingWindowCircuitBreaker {
+    /// Creates a new breaker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure_threshold` is zero or greater than `window_size`.
+    pub fn new(window_size: usize, failure_threshold: usize, open_duration: Duration) -> Self {
+        assert!(
+            failure_threshold > 0 && failure_threshold <= window_size,
+            "failure_threshold must be between 1 and window_size"
+        );
+
+        Self {
+            window_size,
+            failure_threshold,
+            open_duration,
+            state: Mutex::new(WindowState {
+                outcomes: VecDeque::with_capacity(window_size),
+                failures: 0,
+                opened_at: None,
+                probe_since: None,
+            }),
+        }
+    }
+
+    fn record(&self, failed: bool) {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(opened_at) = state.opened_at {
+            if opened_at.elapsed() < self.open_duration {
+                // A call that started before the circuit opened.
+                return;
+            }
+
+            // The open period elapsed and this is the first call to complete since.
+            state.probe_since = None;
+            if failed {
+                state.opened_at = Some(Instant::now());
+            } else {
+                state.opened_at = None;
+                state.outcomes.clear();
+                state.failures = 0;
+            }
+            return;
+        }
+
+        if state.outcomes.len() == self.window_size && state.outcomes.pop_front() == Some(true) {
+            state.failures -= 1;
+        }
+        state.outcomes.push_back(failed);
+        state.failures += failed as usize;
+
+        if state.failures >= self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Opens when 5 of the last 10 calls failed and stays open for 30 seconds.
+impl Default for SlidingWindowCircuitBreaker {
+    fn default() -> Self {
+        Self::new(10, 5, Duration::from_secs(30))
+    }
+}
+
+impl CircuitBreaker for SlidingWindowCircuitBreaker {
+    /// Returns `true` while the circuit is open, or half-open with a probe in flight.
+    fn is_open(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        [state.opened_at, state.probe_since]
+            .into_iter()
+            .flatten()
+            .any(|since| since.elapsed() < self.open_duration)
+    }
+
+    fn is_open_for(&self, path: &str) -> bool {
+        let _ = path;
+        let mut state = self.state.lock().unwrap();
+        let Some(opened_at) = state.opened_at else {
+            return false;
+        };
+        if opened_at.elapsed() < self.open_duration
+            || state
+                .probe_since
+                .is_some_and(|probe_since| probe_since.elapsed() < self.open_duration)
+        {
+            return true;
+        }
+
+        // This call is the probe.
+        state.probe_since = Some(Instant::now());
+        false
+    }
+
+    fn record_success(&self) {
+        self.record(false);
+    }
+
+    fn record_failure(&self) {
+        self.record(true);
+    }
+}
+
+impl fmt::Debug for SlidingWindowCircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlidingWindowCircuitBreaker")
+            .field("window_size", &self.window_size)
+            .field("failure_threshold", &self.failure_threshold)
+            .field("open_duration", &self.open_duration)
+            .field("is_open", &self.is_open())
+            .finish()
+    }
+}
+
+/// Configuration of a [`PerMethodCircuitBreaker`].
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    failure_threshold: usize,
+    cooldown: Duration,
+    failure_codes: Vec<Code>,
+}
+
+impl CircuitBreakerConfig {
+    /// Open the circuit of a method after `threshold` consecutive failed calls.
+    ///
+    /// Defaults to 5.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is zero.
+    pub fn failure_threshold(mut self, threshold: usize) -> Self {
+        assert!(threshold > 0, "failure_threshold must be at least 1");
+        self.failure_threshold = threshold;
+        self
+    }
+
+    /// Keep an open circuit open for `cooldown` before letting a probe call through.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Count calls that fail with one of `codes` as failures, any other outcome is a success.
+    ///
+    /// Defaults to `Unavailable`, `DeadlineExceeded`, `Internal`, `Unknown` and `DataLoss`.
+    pub fn failure_codes(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.failure_codes = codes.into_iter().collect();
+        self
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+            failure_codes: FAILURE_CODES.to_vec(),
+        }
+    }
+}
+
+/// A [`CircuitBreaker`] with a circuit for each method, based on consecutive failures.
+///
+/// The circuit of a method opens after [`CircuitBreakerConfig::failure_threshold`] consecutive
+/// calls to it have failed, any successful call resets the count. Once the cooldown elapsed, the
+/// circuit is half-open: a single probe call is let through while other calls are still
+/// rejected. If the probe fails the circuit opens again, if it succeeds the circuit closes. A probe
+/// that doesn't report back within the cooldown, for example because the call was cancelled, is
+/// replaced by a new one.
+///
+/// Outcomes reported through [`CircuitBreaker::record_success`] and
+/// [`CircuitBreaker::record_failure`], which don't name a method, share a single circuit.
+///
+/// ```
+/// # use tonic::client::{CircuitBreakerConfig, PerMethodCircuitBreaker};
+/// # use tonic::Code;
+/// # use std::time::Duration;
+/// let breaker = PerMethodCircuitBreaker::new(
+///     CircuitBreakerConfig::default()
+///         .failure_threshold(3)
+///         .cooldown(Duration::from_secs(10))
+///         .failure_codes([Code::Unavailable, Code::ResourceExhausted]),
+/// );
+/// ```
+pub struct PerMethodCircuitBreaker {
+    config: CircuitBreakerConfig,
+    methods: Mutex<HashMap<String, MethodState>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MethodState {
+    Closed { failures: usize },
+    Open { since: Instant },
+    HalfOpen { probe_since: Instant },
+}
+
+impl PerMethodCircuitBreaker {
+    /// Creates a new breaker with all circuits closed.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            methods: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn update(&self, path: &str, failed: bool) {
+        let mut methods = self.methods.lock().unwrap();
+        if !methods.contains_key(path) {
+            if !failed {
+                return;
+            }
+            methods.insert(path.to_string(), MethodState::Closed { failures: 0 });
+        }
+        let state = methods.get_mut(path).unwrap();
+
+        *state = match (*state, failed) {
+            (MethodState::Closed { failures }, true) => {
+                if failures + 1 >= self.config.failure_threshold {
+                    MethodState::Open {
+                        since: Instant::now(),
+                    }
+                } else {
+                    MethodState::Closed {
+                        failures: failures + 1,
+                    }
+                }
+            }
+            (MethodState::Closed { .. }, false) => MethodState::Closed { failures: 0 },
+            // A call that started before the circuit opened.
+            (MethodState::Open { .. }, _) => return,
+            (MethodState::HalfOpen { .. }, true) => MethodState::Open {
+                since: Instant::now(),
+            },
+            (MethodState::HalfOpen { .. }, false) => MethodState::Closed { failures: 0 },
+        };
+    }
+}
+
+impl From<CircuitBreakerConfig> for PerMethodCircuitBreaker {
+    fn from(config: CircuitBreakerConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+impl CircuitBreaker for PerMethodCircuitBreaker {
+    /// Returns `true` while the circuit of any method is open.
+    fn is_open(&self) -> bool {
+        let methods = self.methods.lock().unwrap();
+        methods.values().any(|state| match *state {
+            MethodState::Closed { .. } => false,
+            MethodState::Open { since } => since.elapsed() < self.config.cooldown,
+            MethodState::HalfOpen { .. } => true,
+        })
+    }
+
+    fn record_success(&self) {
+        self.update("", false);
+    }
+
+    fn record_failure(&self) {
+        self.update("", true);
+    }
+
+    fn is_open_for(&self, path: &str) -> bool {
+        let mut methods = self.methods.lock().unwrap();
+        let Some(state) = methods.get_mut(path) else {
+            return false;
+        };
+
+        match *state {
+            MethodState::Closed { .. } => false,
+            MethodState::Open { since } | MethodState::HalfOpen { probe_since: since } => {
+                if since.elapsed() < self.config.cooldown {
+                    return true;
+                }
+
+                // This call is the probe.
+                *state = MethodState::HalfOpen {
+                    probe_since: Instant::now(),
+                };
+                false
+            }
+        }
+    }
+
+    fn record(&self, path: &str, status: Option<&Status>) {
+        let failed =
+            status.is_some_and(|status| self.config.failure_codes.contains(&status.code()));
+        self.update(path, failed);
+    }
+}
+
+impl fmt::Debug for PerMethodCircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PerMethodCircuitBreaker")
+            .field("config", &self.config)
+            .field("methods", &self.methods.lock().unwrap())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_within_window() {
+        let breaker = SlidingWindowCircuitBreaker::new(4, 2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_success();
+        breaker.record_success();
+        // The first failure slid out of the window.
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn half_open_after_open_duration() {
+        let breaker = SlidingWindowCircuitBreaker::new(2, 1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        // A failure while half-open reopens the circuit.
+        breaker.record_failure();
+        assert!(breaker.state.lock().unwrap().opened_at.is_some());
+
+        // A success while half-open closes it.
+        breaker.record_success();
+        let state = breaker.state.lock().unwrap();
+        assert!(state.opened_at.is_none());
+        assert!(state.outcomes.is_empty());
+    }
+
+    #[test]
+    fn half_open_lets_one_probe_through() {
+        let breaker = SlidingWindowCircuitBreaker::new(2, 1, Duration::from_millis(20));
+
+        breaker.record_failure();
+        assert!(breaker.is_open_for(METHOD));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!breaker.is_open());
+        assert!(!breaker.is_open_for(METHOD));
+        assert!(breaker.is_open_for(METHOD));
+        assert!(breaker.is_open());
+
+        // The probe never reported back, another one is let through.
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!breaker.is_open_for(METHOD));
+
+        breaker.record_success();
+        assert!(!breaker.is_open_for(METHOD));
+        assert!(!breaker.is_open_for(METHOD));
+    }
+
+    const METHOD: &str = "/test.Test/Method";
+
+    fn per_method(threshold: usize, cooldown: Duration) -> PerMethodCircuitBreaker {
+        PerMethodCircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .failure_threshold(threshold)
+                .cooldown(cooldown),
+        )
+    }
+
+    #[test]
+    fn per_method_opens_after_consecutive_failures() {
+        let breaker = per_method(2, Duration::from_secs(60));
+        let unavailable = Status::unavailable("");
+
+        breaker.record(METHOD, Some(&unavailable));
+        // A success resets the count.
+        breaker.record(METHOD, None);
+        breaker.record(METHOD, Some(&unavailable));
+        assert!(!breaker.is_open_for(METHOD));
+
+        breaker.record(METHOD, Some(&unavailable));
+        assert!(breaker.is_open_for(METHOD));
+        assert!(breaker.is_open());
+
+        // Other methods are unaffected.
+        assert!(!breaker.is_open_for("/test.Test/Other"));
+    }
+
+    #[test]
+    fn per_method_half_open_lets_one_probe_through() {
+        let breaker = per_method(1, Duration::ZERO);
+        let unavailable = Status::unavailable("");
+
+        breaker.record(METHOD, Some(&unavailable));
+        assert!(matches!(
+            breaker.methods.lock().unwrap()[METHOD],
+            MethodState::Open { .. }
+        ));
+
+        // The cooldown elapsed, the first call is the probe.
+        assert!(!breaker.is_open_for(METHOD));
+        assert!(matches!(
+            breaker.methods.lock().unwrap()[METHOD],
+            MethodState::HalfOpen { .. }
+        ));
+
+        // A failed probe opens the circuit again.
+        breaker.record(METHOD, Some(&unavailable));
+        assert!(matches!(
+            breaker.methods.lock().unwrap()[METHOD],
+            MethodState::Open { .. }
+        ));
+
+        // A successful probe closes it.
+        assert!(!breaker.is_open_for(METHOD));
+        breaker.record(METHOD, None);
+        assert_eq!(
+            breaker.methods.lock().unwrap()[METHOD],
+            MethodState::Closed { failures: 0 }
+        );
+    }
+
+    #[test]
+    fn per_method_rejects_calls_while_probing() {
+        let breaker = per_method(1, Duration::from_millis(20));
+
+        breaker.record(METHOD, Some(&Status::unavailable("")));
+        assert!(breaker.is_open_for(METHOD));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!breaker.is_open_for(METHOD));
+        assert!(breaker.is_open_for(METHOD));
+
+        // The probe never reported back, another one is let through.
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!breaker.is_open_for(METHOD));
+    }
+
+    #[test]
+    fn per_method_failure_codes() {
+        let breaker = PerMethodCircuitBreaker::new(
+            CircuitBreakerConfig::default()
+                .failure_threshold(1)
+                .failure_codes([Code::ResourceExhausted]),
+        );
+
+        breaker.record(METHOD, Some(&Status::unavailable("")));
+        assert!(!breaker.is_open_for(METHOD));
+
+        breaker.record(METHOD, Some(&Status::resource_exhausted("")));
+        assert!(breaker.is_open_for(METHOD));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{util::rng::SplitMix64, Code, Status};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Failures injected into the calls of a [`Grpc`] client, see [`Grpc::with_fault_injection`].
+///
+/// Each call draws a random number to decide whether it fails with `abort_code` without being
+/// dispatched, is dispatched after an added delay of `latency_injection`, or is dispatched as
+/// usual. The numbers come from a generator seeded with `seed`, so a client that makes the same
+/// sequence of calls sees the same faults on every run.
+///
+/// ```
+/// # use tonic::client::FaultConfig;
+/// # use std::time::Duration;
+/// let faults = FaultConfig {
+///     abort_probability: 0.1,
+///     latency_probability: 0.2,
+///     latency_injection: Duration::from_millis(500),
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`Grpc`]: super::Grpc
+/// [`Grpc::with_fault_injection`]: super::Grpc::with_fault_injection
+#[derive(Debug, Clone)]
+pub struct FaultConfig {
+    /// The probability, between 0 and 1, that a call fails without being dispatched.
+    pub abort_probability: f64,
+    /// The code of the status aborted calls fail with, `Unavailable` by default.
+    pub abort_code: Code,
+    /// The probability, between 0 and 1, that a call is delayed by `latency_injection`.
+    pub latency_probability: f64,
+    /// The delay added before dispatching a call.
+    pub latency_injection: Duration,
+    /// The seed of the generator deciding which calls get a fault.
+    pub seed: u64,
+}
+
+impl Default for FaultConfig {
+    /// Injects no faults.
+    fn default() -> Self {
+        Self {
+            abort_probability: 0.0,
+            abort_code: Code::Unavailable,
+            latency_probability: 0.0,
+            latency_injection: Duration::ZERO,
+            seed: 0,
+        }
+    }
+}
+
+/// The fault injected into a call.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Fault {
+    Abort(Code),
+    Delay(Duration),
+}
+
+#[derive(Debug)]
+pub(crate) struct FaultInjector {
+    config: FaultConfig,
+    state: AtomicU64,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(config: FaultConfig) -> Self {
+        let valid = |p: f64| (0.0..=1.0).contains(&p);
+        assert!(
+            valid(config.abort_probability)
+                && valid(config.latency_probability)
+                && config.abort_probability + config.latency_probability <= 1.0,
+            "fault probabilities must be between 0 and 1 and add up to at most 1"
+        );
+
+        Self {
+            state: AtomicU64::new(config.seed),
+            config,
+        }
+    }
+
+    /// Draw the fault of the next call, if any.
+    pub(crate) fn next_fault(&self) -> Option<Fault> {
+        let roll = self.next_f64();
+        if roll < self.config.abort_probability {
+            Some(Fault::Abort(self.config.abort_code))
+        } else if roll < self.config.abort_probability + self.config.latency_probability {
+            Some(Fault::Delay(self.config.latency_injection))
+        } else {
+            None
+        }
+    }
+
+    /// Fail or delay the next call, depending on its fault.
+    pub(crate) async fn inject(&self) -> Result<(), Status> {
+        match self.next_fault() {
+            Some(Fault::Abort(code)) => Err(Status::new(code, "Fault injected by the client")),
+            Some(Fault::Delay(delay)) => {
+                tokio::time::sleep(delay).await;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// A number in `[0, 1)` from a SplitMix64 generator.
+    fn next_f64(&self) -> f64 {
+        let state = self
+            .state
+            .fetch_add(SplitMix64::GAMMA, Ordering::Relaxed)
+            .wrapping_add(SplitMix64::GAMMA);
+        SplitMix64::to_f64(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faults(config: FaultConfig, calls: usize) -> Vec<Option<Fault>> {
+        let injector = FaultInjector::new(config);
+        (0..calls).map(|_| injector.next_fault()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_faults() {
+        let config = FaultConfig {
+            abort_probability: 0.3,
+            latency_probability: 0.3,
+            latency_injection: Duration::from_millis(10),
+            seed: 7,
+            ..Default::default()
+        };
+
+        let first = faults(config.clone(), 100);
+        assert_eq!(first, faults(config, 100));
+
+        let aborted = first
+            .iter()
+            .filter(|fault| **fault == Some(Fault::Abort(Code::Unavailable)))
+            .count();
+        assert!((15..=45).contains(&aborted), "{} aborted calls", aborted);
+        assert!(first.contains(&Some(Fault::Delay(Duration::from_millis(10)))));
+        assert!(first.contains(&None));
+    }
+
+    #[test]
+    fn certain_and_impossible_faults() {
+        let always = FaultConfig {
+            abort_probability: 1.0,
+            abort_code: Code::ResourceExhausted,
+            ..Default::default()
+        };
+        assert!(faults(always, 20)
+            .iter()
+            .all(|fault| *fault == Some(Fault::Abort(Code::ResourceExhausted))));
+
+        assert!(faults(FaultConfig::default(), 20)
+            .iter()
+            .all(Option::is_none));
+    }
+
+    #[test]
+    #[should_panic(expected = "fault probabilities")]
+    fn rejects_probabilities_over_one() {
+        FaultInjector::new(FaultConfig {
+            abort_probability: 0.6,
+            latency_probability: 0.6,
+            ..Default::default()
+        });
+    }
+}
@@ -1,20 +1,42 @@
-use crate::codec::compression::{CompressionEncoding, EnabledCompressionEncodings};
-use crate::metadata::GRPC_CONTENT_TYPE;
+use crate::codec::compression::{
+    CompressionEncoding, CompressionPolicy, EnabledCompressionEncodings,
+    SingleMessageCompressionOverride,
+};
+use crate::metadata::{MetadataMap, MetadataValue, GRPC_CONTENT_TYPE, GRPC_TIMEOUT_HEADER};
 use crate::{
     body::BoxBody,
-    client::GrpcService,
-    codec::{encode_client, Codec, Decoder, Streaming},
+    client::{
+        pipeline::PipelinedFuture, resume, token_source, AsyncInterceptor, CallTiming,
+        CircuitBreaker, GrpcService, Pipeline, RequestId, ResumePolicy, TokenSource,
+    },
+    codec::{
+        encode_client_results, encode_frame, Codec, DecodeTransform, Decoder, DefaultLimits,
+        OutgoingTrailers, StreamProgress, Streaming, WrappedDecoder, TERMINAL_METADATA_HEADER,
+    },
+    extensions::{MessageSizeDefaults, TerminalMetadataAcknowledged},
     request::SanitizeHeaders,
     Code, Request, Response, Status,
 };
+use bytes::Bytes;
 use http::{
-    header::{HeaderValue, CONTENT_TYPE, TE},
+    header::{HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, TE},
     uri::{PathAndQuery, Uri},
 };
 use http_body::Body;
-use std::{fmt, future, pin::pin};
+use std::{
+    fmt, future,
+    pin::pin,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
+};
 use tokio_stream::{Stream, StreamExt};
 
+#[cfg(feature = "channel")]
+use crate::client::{Call, RequestSink};
+#[cfg(feature = "channel")]
+use std::future::Future;
+
 /// A gRPC client dispatcher.
 ///
 /// This will wrap some inner [`GrpcService`] and will encode/decode
@@ -31,20 +53,82 @@ use tokio_stream::{Stream, StreamExt};
 pub struct Grpc<T> {
     inner: T,
     config: GrpcConfig,
+    /// The limits of the channel the calls are dispatched to, for those the config doesn't set.
+    size_defaults: MessageSizeDefaults,
 }
 
-struct GrpcConfig {
+/// The complete configuration of a [`Grpc`] client.
+///
+/// This allows configuring a client in one step, for example from settings read from a file,
+/// with [`Grpc::with_config`]. Each setter mirrors the [`Grpc`] builder method of the same name.
+///
+/// ```rust
+/// # use tonic::client::{Grpc, GrpcConfig};
+/// # use tonic::codec::CompressionEncoding;
+/// # fn build<T>(channel: T) -> Grpc<T> {
+/// let config = GrpcConfig::default()
+///     .origin("https://example.com".parse().unwrap())
+///     .max_decoding_message_size(8 * 1024 * 1024)
+///     .max_encoding_message_size(8 * 1024 * 1024);
+///
+/// Grpc::with_config(channel, config)
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct GrpcConfig {
     origin: Uri,
     /// Which compression encodings does the client accept?
     accept_compression_encodings: EnabledCompressionEncodings,
+    /// Decode responses compressed with an encoding the client didn't advertise.
+    accept_unadvertised_compression: bool,
     /// The compression encoding that will be applied to requests.
     send_compression_encodings: Option<CompressionEncoding>,
+    /// Which request messages get compressed.
+    compression_policy: CompressionPolicy,
     /// Limits the maximum size of a decoded message.
     max_decoding_message_size: Option<usize>,
     /// Limits the maximum size of an encoded message.
     max_encoding_message_size: Option<usize>,
+    /// The limits that apply when the ones above are not set.
+    default_limits: Option<DefaultLimits>,
+    /// Initial capacity of the buffer requests are encoded into.
+    send_buffer_size: Option<usize>,
+    /// Pauses request bodies while the transport holds more than this many of their bytes.
+    max_inflight_bytes: Option<usize>,
+    /// Rejects calls while the backend is considered unhealthy.
+    circuit_breaker: Option<Arc<dyn CircuitBreaker>>,
+    /// Provides the bearer token sent with each call.
+    token_source: Option<Arc<dyn TokenSource>>,
+    /// Runs before each call is dispatched.
+    interceptor: Option<Arc<dyn AsyncInterceptor>>,
+    /// Reject responses that don't declare the content subtype of the codec.
+    strict_content_subtype: bool,
+    /// Ignore anything the server sends after the single response message of unary calls.
+    lenient_unary: bool,
+    /// Decode response frames flagged as compressed as is when no encoding was negotiated.
+    lenient_compression_flag: bool,
+    /// Fail calls with `Unavailable` instead of waiting for the inner service to be ready.
+    fail_fast: bool,
+    /// The header that carries a generated ID on calls that don't set it.
+    auto_request_id: Option<HeaderName>,
+    /// Fails response streams that go quiet for longer than this.
+    #[cfg(feature = "channel")]
+    stream_idle_timeout: Option<Duration>,
+    /// Transforms the bytes of each response message before it is decoded.
+    response_body_transform: Option<DecodeTransform>,
+    /// Converts the errors of the inner service into statuses.
+    transport_error_mapper: Option<TransportErrorMapper>,
+    /// Fails calls before they are dispatched.
+    short_circuit: Option<ShortCircuit>,
+    /// Fails or delays calls at random, for testing.
+    #[cfg(feature = "test-util")]
+    fault_injection: Option<Arc<super::fault::FaultInjector>>,
 }
 
+type ShortCircuit = Arc<dyn Fn(&Request<()>, &PathAndQuery) -> Option<Status> + Send + Sync>;
+
+type TransportErrorMapper = Arc<dyn Fn(crate::Error) -> Status + Send + Sync>;
+
 impl<T> Grpc<T> {
     /// Creates a new gRPC client with the provided [`GrpcService`].
     pub fn new(inner: T) -> Self {
@@ -56,15 +140,15 @@ impl<T> Grpc<T> {
     /// The provided Uri will use only the scheme and authority parts as the
     /// path_and_query portion will be set for each method.
     pub fn with_origin(inner: T, origin: Uri) -> Self {
+        Self::with_config(inner, GrpcConfig::default().origin(origin))
+    }
+
+    /// Creates a new gRPC client with the provided [`GrpcService`] and [`GrpcConfig`].
+    pub fn with_config(inner: T, config: GrpcConfig) -> Self {
         Self {
             inner,
-            config: GrpcConfig {
-                origin,
-                send_compression_encodings: None,
-                accept_compression_encodings: EnabledCompressionEncodings::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            },
+            config,
+            size_defaults: MessageSizeDefaults::default(),
         }
     }
 
@@ -99,6 +183,15 @@ impl<T> Grpc<T> {
         self
     }
 
+    /// Set which request messages are compressed when [`Grpc::send_compressed`] is enabled.
+    ///
+    /// Defaults to [`CompressionPolicy::Always`]. See [`CompressionPolicy::ExceedsLimitFraction`]
+    /// for only compressing messages that are close to [`Grpc::max_encoding_message_size`].
+    pub fn compression_policy(mut self, policy: CompressionPolicy) -> Self {
+        self.config.compression_policy = policy;
+        self
+    }
+
     /// Enable accepting compressed responses.
     ///
     /// Requires the server to also support sending compressed responses.
@@ -130,8 +223,23 @@ impl<T> Grpc<T> {
         self
     }
 
+    /// Decode responses compressed with an encoding that wasn't enabled through
+    /// [`Grpc::accept_compressed`].
+    ///
+    /// A server that uses such an encoding violates the compression negotiation, which by default
+    /// fails the call with an `Internal` status. Enabling this downgrades the check to a
+    /// best-effort decode: the response is decompressed as long as the encoding is supported by
+    /// this build of tonic.
+    pub fn accept_unadvertised_compression(mut self, enabled: bool) -> Self {
+        self.config.accept_unadvertised_compression = enabled;
+        self
+    }
+
     /// Limits the maximum size of a decoded message.
     ///
+    /// When no limit is set here or with [`Grpc::with_default_limits`], the limit set on the
+    /// `Endpoint` of a `Channel` applies, see `Endpoint::max_decoding_message_size`.
+    ///
     /// # Example
     ///
     /// The most common way of using this is through a client generated by tonic-build:
@@ -162,6 +270,9 @@ impl<T> Grpc<T> {
 
     /// Limits the maximum size of an encoded message.
     ///
+    /// When no limit is set here or with [`Grpc::with_default_limits`], the limit set on the
+    /// `Endpoint` of a `Channel` applies, see `Endpoint::max_encoding_message_size`.
+    ///
     /// # Example
     ///
     /// The most common way of using this is through a client generated by tonic-build:
@@ -190,18 +301,454 @@ impl<T> Grpc<T> {
         self
     }
 
+    /// Use `limits` for the message sizes that aren't limited with
+    /// [`Grpc::max_decoding_message_size`] or [`Grpc::max_encoding_message_size`].
+    ///
+    /// This replaces the built-in defaults of this client, see [`DefaultLimits`]. To change them
+    /// for every client of an application, create the clients from a [`GrpcConfig`] with
+    /// [`GrpcConfig::default_limits`].
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, codec::DefaultLimits};
+    /// # fn ex<T>(grpc: Grpc<T>) {
+    /// let grpc = grpc.with_default_limits(DefaultLimits {
+    ///     max_decoding_message_size: 16 * 1024 * 1024,
+    ///     ..Default::default()
+    /// });
+    /// # }
+    /// ```
+    pub fn with_default_limits(mut self, limits: DefaultLimits) -> Self {
+        self.config.default_limits = Some(limits);
+        self
+    }
+
+    /// Guard calls with the provided [`CircuitBreaker`].
+    ///
+    /// While the breaker is open, calls fail with an `Unavailable` status without reaching the
+    /// inner [`GrpcService`]. The breaker is shared with every clone of this client. It only
+    /// records the outcome of calls that are dispatched to the inner service, calls failed by the
+    /// [`AsyncInterceptor`] or the [`TokenSource`] beforehand are not recorded. The outcome of a
+    /// server streaming or bi-directional streaming call is recorded once its response stream
+    /// ends, and not at all if the stream is dropped before.
+    ///
+    /// See [`SlidingWindowCircuitBreaker`] and [`PerMethodCircuitBreaker`] for the
+    /// implementations shipped with tonic.
+    ///
+    /// [`SlidingWindowCircuitBreaker`]: crate::client::SlidingWindowCircuitBreaker
+    /// [`PerMethodCircuitBreaker`]: crate::client::PerMethodCircuitBreaker
+    pub fn with_circuit_breaker(mut self, breaker: impl CircuitBreaker) -> Self {
+        self.config.circuit_breaker = Some(Arc::new(breaker));
+        self
+    }
+
+    /// Authenticate calls with bearer tokens from the provided [`TokenSource`].
+    ///
+    /// The source is asked for a token before each call, which is sent in the `authorization`
+    /// header. Wrap the source in a [`CachingTokenSource`] to avoid fetching a new token every
+    /// time.
+    ///
+    /// [`CachingTokenSource`]: crate::client::CachingTokenSource
+    pub fn with_token_source(mut self, source: impl TokenSource) -> Self {
+        self.config.token_source = Some(Arc::new(source));
+        self
+    }
+
+    /// Run the provided [`AsyncInterceptor`] before each call.
+    ///
+    /// The interceptor is awaited before the request is encoded and sent. If it returns an error,
+    /// the call fails with that status.
+    pub fn with_async_interceptor(mut self, interceptor: impl AsyncInterceptor) -> Self {
+        self.config.interceptor = Some(Arc::new(interceptor));
+        self
+    }
+
+    /// Inject failures into calls to test how the application copes with them.
+    ///
+    /// Right before a call would be dispatched to the inner [`GrpcService`], it may fail with a
+    /// synthetic status or be delayed, as described in [`FaultConfig`]. Injected failures are
+    /// returned like real ones. Since they happen before the call reaches the transport, they
+    /// are neither recorded by the circuit breaker nor retried by a [`Channel`]'s retry policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the probabilities of `config` are not between 0 and 1 or add up to more than 1.
+    ///
+    /// [`FaultConfig`]: crate::client::FaultConfig
+    /// [`Channel`]: crate::transport::Channel
+    #[cfg(feature = "test-util")]
+    pub fn with_fault_injection(mut self, config: super::FaultConfig) -> Self {
+        self.config.fault_injection = Some(Arc::new(super::fault::FaultInjector::new(config)));
+        self
+    }
+
+    /// Fail calls for which `f` returns a status, without dispatching them.
+    ///
+    /// `f` is consulted at the start of each call with its request metadata and extensions and
+    /// its path, before the circuit breaker and the interceptor. When it returns a status, the
+    /// call fails with it and the inner [`GrpcService`] is not called. Such calls are not recorded
+    /// by the circuit breaker.
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, Status};
+    /// # fn ex(grpc: Grpc<tonic::transport::Channel>) {
+    /// let grpc = grpc.short_circuit_if(|_request, path| {
+    ///     path.path()
+    ///         .starts_with("/admin.")
+    ///         .then(|| Status::permission_denied("admin calls are disabled"))
+    /// });
+    /// # }
+    /// ```
+    pub fn short_circuit_if<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request<()>, &PathAndQuery) -> Option<Status> + Send + Sync + 'static,
+    {
+        self.config.short_circuit = Some(Arc::new(f));
+        self
+    }
+
+    /// Ignore trailing data after the response message of calls that expect exactly one.
+    ///
+    /// Some non-conformant servers send extra bytes after the single message of a unary
+    /// response, which by default fails the call. When enabled, [`Grpc::unary`] and
+    /// [`Grpc::client_streaming`] decode exactly one message frame and discard the rest of the
+    /// response body before reading the trailers. Streaming responses are not affected.
+    ///
+    /// Defaults to `false`.
+    pub fn with_lenient_unary(mut self, enabled: bool) -> Self {
+        self.config.lenient_unary = enabled;
+        self
+    }
+
+    /// Send a generated ID in the `header_name` header of calls that don't set it.
+    ///
+    /// Calls whose metadata already contain `header_name` are sent as is, the header is never
+    /// overwritten. For the others, a random UUID is generated and the same [`RequestId`] is
+    /// added to the extensions of the [`Response`], so that it can be logged or reported along
+    /// with the outcome of the call.
+    ///
+    /// ```rust
+    /// # use tonic::client::Grpc;
+    /// # fn ex(grpc: Grpc<tonic::transport::Channel>) {
+    /// let grpc = grpc.with_auto_request_id(http::HeaderName::from_static("x-request-id"));
+    /// # }
+    /// ```
+    pub fn with_auto_request_id(mut self, header_name: HeaderName) -> Self {
+        self.config.auto_request_id = Some(header_name);
+        self
+    }
+
+    /// Accept response messages flagged as compressed when the server didn't send a
+    /// `grpc-encoding`.
+    ///
+    /// The gRPC spec requires such messages to fail the call with an `Internal` status, which is
+    /// the default. Some non-conformant servers set the flag on uncompressed messages, when
+    /// enabled their messages are decoded as is. Messages flagged as compressed with an encoding
+    /// are always decompressed, and unflagged ones are never.
+    ///
+    /// Defaults to `false`.
+    pub fn with_lenient_compression_flag(mut self, enabled: bool) -> Self {
+        self.config.lenient_compression_flag = enabled;
+        self
+    }
+
+    /// Fail calls with an `Unavailable` status when the inner [`GrpcService`] is not ready,
+    /// instead of waiting for it.
+    ///
+    /// Readiness is checked once, with [`GrpcService::poll_ready`], right before the call is
+    /// dispatched, and the call fails right away if the service is not ready. When enabled,
+    /// [`Grpc::ready`] no longer waits either, the readiness of the service is checked by each
+    /// call instead.
+    ///
+    /// This only gives the gRPC fail fast semantic, failing calls while there is no connection,
+    /// with services whose readiness reflects the state of their connection. A [`Channel`] is
+    /// ready as long as its request buffer has room, whether or not it is connected: its calls
+    /// are only failed fast when the buffer is full, which sheds load instead of queueing the
+    /// calls. Calls made while it connects still wait for the connection, and fail once the
+    /// connection attempt does.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// ```rust
+    /// # use tonic::client::Grpc;
+    /// # fn ex<T>(grpc: Grpc<T>) {
+    /// let grpc = grpc.with_fail_fast(true);
+    /// # }
+    /// ```
+    ///
+    /// [`Channel`]: crate::transport::Channel
+    pub fn with_fail_fast(mut self, enabled: bool) -> Self {
+        self.config.fail_fast = enabled;
+        self
+    }
+
+    /// Reject responses whose `content-type` doesn't declare a subtype, such as a plain
+    /// `application/grpc`, when the codec has a [`Codec::CONTENT_SUBTYPE`].
+    ///
+    /// Responses declaring a different subtype than the codec always fail with an `Internal`
+    /// status. By default responses without a subtype are accepted, since many servers omit it.
+    pub fn with_strict_content_subtype(mut self, enabled: bool) -> Self {
+        self.config.strict_content_subtype = enabled;
+        self
+    }
+
+    /// Set the initial capacity of the buffer request messages are encoded into.
+    ///
+    /// Tuning this to the typical size of the encoded requests avoids growing the buffer for
+    /// large messages, or allocating more than needed for small ones. The buffer still grows as
+    /// needed. Defaults to the buffer size of the codec's [`BufferSettings`].
+    ///
+    /// [`BufferSettings`]: crate::codec::BufferSettings
+    pub fn with_send_buffer_size(mut self, size: usize) -> Self {
+        self.config.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Limit the bytes of a request stream that can be buffered by the transport.
+    ///
+    /// Messages of a client streaming upload are encoded as soon as the request stream yields
+    /// them, and handed to the transport, which keeps them until it has written them to the
+    /// connection. When the server reads slowly, HTTP/2 flow control holds the writes back and
+    /// the data piles up. With a limit, the request stream is not polled while `max` bytes or
+    /// more handed to the transport have not been released yet, bounding the memory used by the
+    /// upload.
+    ///
+    /// The transport doesn't expose which bytes the server has acknowledged through flow
+    /// control, so the bytes still buffered are used as an approximation. Since they include
+    /// those waiting to be written to the socket, a limit below the HTTP/2 window lowers the
+    /// throughput of uploads, as the stream pauses before the window is used up. The chunk of
+    /// encoded messages yielded last is not split, so the buffered bytes can exceed `max` by up
+    /// to the size of one chunk.
+    ///
+    /// Defaults to no limit.
+    pub fn with_max_inflight_bytes(mut self, max: usize) -> Self {
+        self.config.max_inflight_bytes = Some(max);
+        self
+    }
+
+    /// Fail response streams that don't receive any bytes for `timeout` while waiting for a
+    /// message.
+    ///
+    /// Unlike the overall call deadline, this targets servers that stall without closing the
+    /// stream: the timer runs while waiting for a message and restarts whenever bytes of the
+    /// response are received, so a large message that keeps arriving doesn't time out. Once it
+    /// elapses the [`Streaming`] yields a `DeadlineExceeded` status and resets the HTTP/2 stream.
+    /// Calls with a single response message wait for it under the same timeout.
+    #[cfg(feature = "channel")]
+    pub fn with_stream_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.stream_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Transform the bytes of each response message before it is decoded.
+    ///
+    /// The transform runs on every message of the response stream, after decompression and
+    /// before the [`Decoder`] of the codec, for example to decrypt payloads that are encrypted by
+    /// the application. An error returned by the transform ends the stream with that status.
+    /// This is the fallible counterpart of [`Codec::wrap_decoder`], for every call of this client.
+    ///
+    /// ```rust
+    /// # use tonic::client::Grpc;
+    /// # use std::sync::Arc;
+    /// # fn ex(grpc: Grpc<tonic::transport::Channel>) {
+    /// let grpc = grpc.with_response_body_transform(Arc::new(|message| {
+    ///     // Decrypt `message` here.
+    ///     Ok(message)
+    /// }));
+    /// # }
+    /// ```
+    ///
+    /// [`Decoder`]: crate::codec::Decoder
+    pub fn with_response_body_transform(
+        mut self,
+        transform: Arc<dyn Fn(Bytes) -> Result<Bytes, Status> + Send + Sync>,
+    ) -> Self {
+        self.config.response_body_transform = Some(DecodeTransform::Fallible(transform));
+        self
+    }
+
+    /// Convert the errors of the inner [`GrpcService`] into statuses with `mapper`.
+    ///
+    /// Calls fail with the status returned by `mapper` when the inner service returns an error,
+    /// either while waiting for it to be ready or when calling it. By default, such errors are
+    /// converted with [`Status::from_error`], which recognizes statuses and HTTP/2 errors and
+    /// maps anything else to `Unknown`. A custom transport can use this to give its own errors
+    /// a more accurate code, and fall back to [`Status::from_error`] for the others.
+    ///
+    /// Errors of [`Grpc::warm_up`] and [`Grpc::call_raw`] are returned as is.
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, Status};
+    /// # use std::sync::Arc;
+    /// # #[derive(Debug)]
+    /// # struct PoolExhausted;
+    /// # impl std::fmt::Display for PoolExhausted {
+    /// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// #         f.write_str("connection pool exhausted")
+    /// #     }
+    /// # }
+    /// # impl std::error::Error for PoolExhausted {}
+    /// # fn ex(grpc: Grpc<tonic::transport::Channel>) {
+    /// let grpc = grpc.with_transport_error_mapper(Arc::new(|err| {
+    ///     if err.is::<PoolExhausted>() {
+    ///         Status::resource_exhausted(err.to_string())
+    ///     } else {
+    ///         Status::from_error(err)
+    ///     }
+    /// }));
+    /// # }
+    /// ```
+    pub fn with_transport_error_mapper(
+        mut self,
+        mapper: Arc<dyn Fn(crate::Error) -> Status + Send + Sync>,
+    ) -> Self {
+        self.config.transport_error_mapper = Some(mapper);
+        self
+    }
+
+    /// Replace the inner [`GrpcService`], keeping the rest of the configuration.
+    ///
+    /// This is useful to point an existing client at a new connection, for example after a
+    /// failover, without having to apply its settings again.
+    pub fn with_inner<U>(self, inner: U) -> Grpc<U> {
+        // The new connection may lead to a server without terminal metadata support.
+        Grpc::with_config(inner, self.config)
+    }
+
+    /// Get a mutable reference to the inner [`GrpcService`].
+    pub fn get_mut_inner(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
     /// Check if the inner [`GrpcService`] is able to accept a  new request.
     ///
     /// This will call [`GrpcService::poll_ready`] until it returns ready or
     /// an error. If this returns ready the inner [`GrpcService`] is ready to
     /// accept one more request.
+    ///
+    /// With [`Grpc::with_fail_fast`], this returns right away if the service isn't ready, and the
+    /// next call fails with an `Unavailable` status unless the service became ready meanwhile.
     pub async fn ready(&mut self) -> Result<(), T::Error>
+    where
+        T: GrpcService<BoxBody>,
+    {
+        if self.config.fail_fast {
+            return match self.poll_ready_once().await {
+                Poll::Ready(result) => result,
+                Poll::Pending => Ok(()),
+            };
+        }
+
+        self.wait_ready().await
+    }
+
+    async fn wait_ready(&mut self) -> Result<(), T::Error>
     where
         T: GrpcService<BoxBody>,
     {
         future::poll_fn(|cx| self.inner.poll_ready(cx)).await
     }
 
+    async fn poll_ready_once(&mut self) -> Poll<Result<(), T::Error>>
+    where
+        T: GrpcService<BoxBody>,
+    {
+        future::poll_fn(|cx| Poll::Ready(self.inner.poll_ready(cx))).await
+    }
+
+    /// The limit of decoded messages, falling back to the one of the channel.
+    fn decoding_limit(&self) -> Option<usize> {
+        self.config
+            .decoding_limit()
+            .or_else(|| self.size_defaults.max_decoding_message_size())
+    }
+
+    /// The limit of encoded messages, falling back to the one of the channel.
+    fn encoding_limit(&self) -> Option<usize> {
+        self.config
+            .encoding_limit()
+            .or_else(|| self.size_defaults.max_encoding_message_size())
+    }
+
+    /// Wait for the inner [`GrpcService`] to be ready ahead of the first call.
+    ///
+    /// Unlike [`Grpc::ready`], this waits even with [`Grpc::with_fail_fast`]. Nothing is sent:
+    /// services whose readiness tracks their connection are connected once this returns, which
+    /// keeps connection latency out of the first call. A [`Channel`] is ready while its buffer
+    /// has room, whether it is connected or not, use [`Channel::warm_up`] to connect it.
+    ///
+    /// [`Channel`]: crate::transport::Channel
+    /// [`Channel::warm_up`]: crate::transport::Channel::warm_up
+    pub async fn warm_up(&mut self) -> Result<(), T::Error>
+    where
+        T: GrpcService<BoxBody>,
+    {
+        self.wait_ready().await
+    }
+
+    /// Send a raw HTTP request and return the response untouched.
+    ///
+    /// Waits for the inner [`GrpcService`] to be [ready](Grpc::ready) and calls it with `request`
+    /// as is. Neither the request nor the response are interpreted: no gRPC headers are added, the
+    /// content type is not checked and no status is extracted from the headers or trailers. This is
+    /// meant for diagnostic tools, such as health or reflection probes, that need to inspect exactly
+    /// what a server sent.
+    pub async fn call_raw(
+        &mut self,
+        request: http::Request<BoxBody>,
+    ) -> Result<http::Response<T::ResponseBody>, T::Error>
+    where
+        T: GrpcService<BoxBody>,
+    {
+        self.wait_ready().await?;
+        self.inner.call(request).await
+    }
+
+    /// Encode `message` into the bytes [`Grpc::unary`] would send for it.
+    ///
+    /// The result is the complete gRPC frame, with its compressed flag and length prefix,
+    /// compressed as configured by [`Grpc::send_compressed`] and [`Grpc::compression_policy`],
+    /// against the size limit of this client. Unary calls encode their
+    /// message with the same settings, and encoding is deterministic, so a signature computed
+    /// over these bytes, for example an HMAC sent in a header of the call, matches the body of a
+    /// unary call made with the same `message` and the same `codec` configuration.
+    ///
+    /// The bytes differ from what is sent when the call disables compression with
+    /// [`Request::disable_compression`] for its message.
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, codec::ProstCodec, Request};
+    /// # use http::uri::PathAndQuery;
+    /// # async fn ex(mut grpc: Grpc<tonic::transport::Channel>, key: &[u8]) -> Result<(), tonic::Status> {
+    /// # fn hmac(_: &[u8], _: &[u8]) -> String { String::new() }
+    /// let mut codec = ProstCodec::<String, String>::default();
+    /// let message = "hello".to_string();
+    ///
+    /// let signature = hmac(key, &grpc.encode_unary(&mut codec, &message)?);
+    /// let mut request = Request::new(message);
+    /// request.metadata_mut().insert("x-signature", signature.parse().unwrap());
+    ///
+    /// grpc.unary(request, PathAndQuery::from_static("/hello.Hello/Say"), codec).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[allow(clippy::result_large_err)]
+    pub fn encode_unary<M1, C>(&self, codec: &mut C, message: &M1) -> Result<Bytes, Status>
+    where
+        C: Codec<Encode = M1>,
+        M1: Clone + Send + Sync + 'static,
+    {
+        let send_encoding = self.config.send_encoding(codec.preferred_compression());
+
+        encode_frame(
+            &mut codec.encoder(),
+            message.clone(),
+            send_encoding,
+            self.config.compression_policy,
+            self.encoding_limit(),
+            self.config.send_buffer_size,
+        )
+    }
+
     /// Send a single unary gRPC request.
     pub async fn unary<M1, M2, C>(
         &mut self,
@@ -221,10 +768,14 @@ impl<T> Grpc<T> {
         self.client_streaming(request, path, codec).await
     }
 
-    /// Send a client side streaming gRPC request.
-    pub async fn client_streaming<S, M1, M2, C>(
+    /// Send a single unary gRPC request built from a template that is kept for later calls.
+    ///
+    /// This is useful to send the same request repeatedly, for example when polling. The message,
+    /// metadata and extensions of `request` are cloned for this call, so changes made to them
+    /// along the way, for example by an [`AsyncInterceptor`], don't affect the template.
+    pub async fn unary_ref<M1, M2, C>(
         &mut self,
-        request: Request<S>,
+        request: &Request<M1>,
         path: PathAndQuery,
         codec: C,
     ) -> Result<Response<M2>, Status>
@@ -232,39 +783,25 @@ impl<T> Grpc<T> {
         T: GrpcService<BoxBody>,
         T::ResponseBody: Body + Send + 'static,
         <T::ResponseBody as Body>::Error: Into<crate::Error>,
-        S: Stream<Item = M1> + Send + 'static,
         C: Codec<Encode = M1, Decode = M2>,
-        M1: Send + Sync + 'static,
+        M1: Clone + Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
-        let (mut parts, body, extensions) =
-            self.streaming(request, path, codec).await?.into_parts();
-
-        let mut body = pin!(body);
-
-        let message = body
-            .try_next()
-            .await
-            .map_err(|mut status| {
-                status.metadata_mut().merge(parts.clone());
-                status
-            })?
-            .ok_or_else(|| Status::internal("Missing response message."))?;
-
-        if let Some(trailers) = body.trailers().await? {
-            parts.merge(trailers);
-        }
-
-        Ok(Response::from_parts(parts, message, extensions))
+        self.unary(request.clone(), path, codec).await
     }
 
-    /// Send a server side streaming gRPC request.
-    pub async fn server_streaming<M1, M2, C>(
+    /// Send a single unary gRPC request and measure how long it took.
+    ///
+    /// The returned [`Duration`] covers the time from just before the request is handed to the
+    /// inner [`GrpcService`] until the response message and trailers have been received. It does
+    /// not include time spent waiting for the service to become ready (see [`Grpc::ready`]), so
+    /// it reflects server and network time.
+    pub async fn unary_timed<M1, M2, C>(
         &mut self,
         request: Request<M1>,
         path: PathAndQuery,
         codec: C,
-    ) -> Result<Response<Streaming<M2>>, Status>
+    ) -> Result<(Response<M2>, Duration), Status>
     where
         T: GrpcService<BoxBody>,
         T::ResponseBody: Body + Send + 'static,
@@ -274,204 +811,2755 @@ impl<T> Grpc<T> {
         M2: Send + Sync + 'static,
     {
         let request = request.map(|m| tokio_stream::once(m));
-        self.streaming(request, path, codec).await
+        self.client_streaming_timed(request, path, codec).await
     }
 
-    /// Send a bi-directional streaming gRPC request.
-    pub async fn streaming<S, M1, M2, C>(
-        &mut self,
-        request: Request<S>,
+    /// Send unary gRPC requests without waiting for the previous responses.
+    ///
+    /// Each request is sent on its own clone of this client, which is awaited to be ready first.
+    /// The calls are all dispatched as soon as the returned [`Pipeline`] is first polled, and
+    /// their responses are yielded in the order of `requests`.
+    ///
+    /// This only saves round-trips on transports that multiplex calls over a connection, such as
+    /// a [`Channel`] to an HTTP/2 server. Other transports accept calls one at a time, in which
+    /// case the calls are made one after the other, as if awaited in turn. Since all the calls
+    /// are in flight at once, keep the number of requests within the concurrency limits of the
+    /// transport and the server.
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, codec::Codec, Request, Status};
+    /// # use tokio_stream::StreamExt;
+    /// # async fn ex<C>(grpc: Grpc<tonic::transport::Channel>, codec: C) -> Result<(), Status>
+    /// # where C: Codec<Encode = String, Decode = String> + Clone + Send + 'static {
+    /// let requests = (0..10).map(|i| Request::new(i.to_string()));
+    /// let mut responses = grpc.pipeline(requests, "/echo.Echo/Echo".parse().unwrap(), codec);
+    ///
+    /// while let Some(response) = responses.next().await {
+    ///     println!("{}", response?.into_inner());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Channel`]: crate::transport::Channel
+    pub fn pipeline<M1, M2, C>(
+        &self,
+        requests: impl IntoIterator<Item = Request<M1>>,
         path: PathAndQuery,
-        mut codec: C,
-    ) -> Result<Response<Streaming<M2>>, Status>
+        codec: C,
+    ) -> Pipeline<M2>
     where
-        T: GrpcService<BoxBody>,
+        T: GrpcService<BoxBody> + Clone + Send + Sync + 'static,
+        T::Future: Send,
         T::ResponseBody: Body + Send + 'static,
         <T::ResponseBody as Body>::Error: Into<crate::Error>,
-        S: Stream<Item = M1> + Send + 'static,
-        C: Codec<Encode = M1, Decode = M2>,
+        C: Codec<Encode = M1, Decode = M2> + Clone + Send + 'static,
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
-        let request = request
-            .map(|s| {
-                encode_client(
-                    codec.encoder(),
-                    s,
-                    self.config.send_compression_encodings,
-                    self.config.max_encoding_message_size,
-                )
-            })
-            .map(BoxBody::new);
-
-        let request = self.config.prepare_request(request, path);
-
-        let response = self
-            .inner
-            .call(request)
-            .await
-            .map_err(Status::from_error_generic)?;
-
-        let decoder = codec.decoder();
-
-        self.create_response(decoder, response)
+        Pipeline::new(requests.into_iter().map(|request| {
+            let mut grpc = self.clone();
+            let path = path.clone();
+            let codec = codec.clone();
+            Box::pin(async move {
+                grpc.ready()
+                    .await
+                    .map_err(|err| grpc.config.transport_error(err))?;
+                grpc.unary(request, path, codec).await
+            }) as PipelinedFuture<M2>
+        }))
     }
 
-    // Keeping this code in a separate function from Self::streaming lets functions that return the
-    // same output share the generated binary code
-    fn create_response<M2>(
-        &self,
-        decoder: impl Decoder<Item = M2, Error = Status> + Send + 'static,
-        response: http::Response<T::ResponseBody>,
-    ) -> Result<Response<Streaming<M2>>, Status>
+    /// Send a client side streaming gRPC request.
+    pub async fn client_streaming<S, M1, M2, C>(
+        &mut self,
+        request: Request<S>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<M2>, Status>
     where
         T: GrpcService<BoxBody>,
         T::ResponseBody: Body + Send + 'static,
         <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
     {
-        let encoding = CompressionEncoding::from_encoding_header(
-            response.headers(),
-            self.config.accept_compression_encodings,
+        let request = self.config.check_short_circuit(request, &path)?;
+        self.config.check_circuit_breaker(&path)?;
+        let result = match self
+            .call_streaming(request.map(|s| s.map(Ok)), path.clone(), codec)
+            .await?
+        {
+            Ok((response, _)) => self.single_message(response).await,
+            Err(status) => Err(status),
+        };
+        self.config.record_outcome(&path, &result);
+        result
+    }
+
+    /// Send a client side streaming gRPC request that ends with `metadata`.
+    ///
+    /// gRPC clients can't send trailers, so as a tonic extension the metadata is encoded as a
+    /// final frame of the request stream, described in [`TERMINAL_METADATA_FLAG`], once the
+    /// request stream has ended. If the request stream fails, the metadata isn't sent. Tonic
+    /// servers return it from [`Streaming::trailers`].
+    ///
+    /// This is not interoperable, so the frame is negotiated with the
+    /// [`TERMINAL_METADATA_HEADER`] header: the request offers it, and the metadata is only sent
+    /// if the server handling the call acknowledged the offer in its response headers, received
+    /// before the request stream ended. A [`Channel`] also sends it on a connection whose server
+    /// acknowledged an earlier call, so calls balanced across several servers only send it to
+    /// those that read it. Servers that don't know the extension never acknowledge it, so the
+    /// metadata is dropped for them instead of failing the call. The first call on a connection
+    /// to a tonic server usually drops it too, since tonic servers only send their response
+    /// headers once the request stream was consumed.
+    ///
+    /// If the metadata is larger than [`Grpc::max_encoding_message_size`], the request stream
+    /// fails instead of sending it.
+    ///
+    /// [`TERMINAL_METADATA_FLAG`]: crate::codec::TERMINAL_METADATA_FLAG
+    /// [`TERMINAL_METADATA_HEADER`]: crate::codec::TERMINAL_METADATA_HEADER
+    /// [`Channel`]: crate::transport::Channel
+    pub async fn client_streaming_with_terminal_metadata<S, M1, M2, C>(
+        &mut self,
+        mut request: Request<S>,
+        metadata: MetadataMap,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<M2>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        request
+            .extensions_mut()
+            .insert(TerminalMetadata::Fixed(metadata));
+        self.client_streaming(request, path, codec).await
+    }
+
+    /// Send a client side streaming gRPC request that ends with the metadata returned by
+    /// `trailers`.
+    ///
+    /// Like [`Grpc::client_streaming_with_terminal_metadata`], the metadata is sent in a terminal
+    /// metadata frame negotiated with the [`TERMINAL_METADATA_HEADER`] header, and isn't sent if
+    /// the request stream fails. But instead of being fixed when the call starts, it is computed
+    /// by calling `trailers` once the request stream has ended, so that it can depend on what was
+    /// sent, such as a checksum of the messages maintained by the request stream.
+    ///
+    /// This is not interoperable either, see [`Grpc::client_streaming_with_terminal_metadata`].
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, codec::ProstCodec, metadata::MetadataMap, Request};
+    /// # use http::uri::PathAndQuery;
+    /// # use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+    /// # async fn ex(mut grpc: Grpc<tonic::transport::Channel>) -> Result<(), tonic::Status> {
+    /// let sent_bytes = Arc::new(AtomicU64::new(0));
+    /// let messages = {
+    ///     let sent_bytes = sent_bytes.clone();
+    ///     tokio_stream::iter(["a", "b"].map(move |m: &str| {
+    ///         sent_bytes.fetch_add(m.len() as u64, Ordering::SeqCst);
+    ///         m.to_string()
+    ///     }))
+    /// };
+    ///
+    /// let trailers = Arc::new(move || {
+    ///     let mut trailers = MetadataMap::new();
+    ///     trailers.insert("x-sent-bytes", sent_bytes.load(Ordering::SeqCst).into());
+    ///     trailers
+    /// });
+    /// let codec = ProstCodec::<String, String>::default();
+    /// let path = PathAndQuery::from_static("/upload.Upload/Send");
+    /// grpc.client_streaming_with_outgoing_trailers(Request::new(messages), trailers, path, codec)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`TERMINAL_METADATA_HEADER`]: crate::codec::TERMINAL_METADATA_HEADER
+    pub async fn client_streaming_with_outgoing_trailers<S, M1, M2, C>(
+        &mut self,
+        mut request: Request<S>,
+        trailers: Arc<dyn Fn() -> MetadataMap + Send + Sync>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<M2>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        request
+            .extensions_mut()
+            .insert(TerminalMetadata::Deferred(trailers));
+        self.client_streaming(request, path, codec).await
+    }
+
+    /// Send a client side streaming gRPC request and measure how long it took.
+    ///
+    /// See [`Grpc::unary_timed`] for what the returned [`Duration`] covers. Since the request
+    /// stream is sent while waiting for the response, the duration includes the time taken to
+    /// produce the request messages.
+    pub async fn client_streaming_timed<S, M1, M2, C>(
+        &mut self,
+        request: Request<S>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<(Response<M2>, Duration), Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let request = self.config.check_short_circuit(request, &path)?;
+        self.config.check_circuit_breaker(&path)?;
+        let result = match self
+            .call_streaming(request.map(|s| s.map(Ok)), path.clone(), codec)
+            .await?
+        {
+            Ok((response, start)) => self
+                .single_message(response)
+                .await
+                .map(|response| (response, start.elapsed())),
+            Err(status) => Err(status),
+        };
+        self.config.record_outcome(&path, &result);
+        result
+    }
+
+    /// Send a client side streaming gRPC request from a stream that borrows data.
+    ///
+    /// Unlike [`Grpc::client_streaming`], the request stream doesn't have to be `'static`. The
+    /// stream is polled by the returned future itself, which forwards each message to the request
+    /// body, so the borrow ends once the call has completed. This allows building the request from
+    /// borrowed buffers in scoped tasks. The messages themselves are still owned.
+    ///
+    /// If the call completes before the request stream is exhausted, the rest of the stream is
+    /// not polled.
+    #[cfg(feature = "channel")]
+    pub async fn client_streaming_scoped<'a, S, M1, M2, C>(
+        &mut self,
+        request: Request<S>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<M2>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'a,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let (metadata, extensions, stream) = request.into_parts();
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        let forward = async move {
+            let mut stream = pin!(stream);
+            while let Some(message) = stream.next().await {
+                if tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        };
+
+        let request = Request::from_parts(
+            metadata,
+            extensions,
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        );
+        let call = self.client_streaming(request, path, codec);
+
+        let mut forward = pin!(forward);
+        let mut forwarded = false;
+        let mut call = pin!(call);
+        future::poll_fn(|cx| {
+            if !forwarded {
+                forwarded = forward.as_mut().poll(cx).is_ready();
+            }
+            call.as_mut().poll(cx)
+        })
+        .await
+    }
+
+    /// Send a client side streaming gRPC request whose messages are written into a sink.
+    ///
+    /// Returns a [`RequestSink`] to write the request messages into, and a future resolving to
+    /// the response. This suits producers that are driven by events rather than pulled like a
+    /// [`Stream`]. The request stream ends once the sink is closed or dropped. The metadata and
+    /// extensions of `request` are sent along with the call.
+    ///
+    /// The call is made on a clone of this client, which is awaited to be ready first. The sink
+    /// only accepts messages while the response future is polled, so it must be awaited or spawned
+    /// concurrently with writing into the sink.
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, codec::Codec, Request, Status};
+    /// # use futures_util::SinkExt;
+    /// # async fn ex<C>(grpc: Grpc<tonic::transport::Channel>, codec: C) -> Result<(), Status>
+    /// # where C: Codec<Encode = String, Decode = String> + Send + 'static {
+    /// let mut request = Request::new(());
+    /// request.metadata_mut().insert("x-client", "mouse".parse().unwrap());
+    /// let (mut sink, response) = grpc.client_streaming_sink(
+    ///     request,
+    ///     "/echo.Echo/ClientStreamingEcho".parse().unwrap(),
+    ///     codec,
+    /// );
+    /// let response = tokio::spawn(response);
+    ///
+    /// sink.send("a".to_string()).await?;
+    /// sink.send("b".to_string()).await?;
+    /// sink.close().await?;
+    ///
+    /// let response = response.await.unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "channel")]
+    pub fn client_streaming_sink<M1, M2, C>(
+        &self,
+        request: Request<()>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> (
+        RequestSink<M1>,
+        impl Future<Output = Result<Response<M2>, Status>> + 'static,
+    )
+    where
+        T: GrpcService<BoxBody> + Clone + Send + 'static,
+        T::Future: Send,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        C: Codec<Encode = M1, Decode = M2> + Send + 'static,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        let mut grpc = self.clone();
+        let response = async move {
+            grpc.ready()
+                .await
+                .map_err(|err| grpc.config.transport_error(err))?;
+            let (metadata, extensions, ()) = request.into_parts();
+            let request = Request::from_parts(
+                metadata,
+                extensions,
+                tokio_stream::wrappers::ReceiverStream::new(rx),
+            );
+            grpc.client_streaming(request, path, codec).await
+        };
+
+        (RequestSink::new(tx), response)
+    }
+
+    /// Upload the contents of a reader as a client side streaming gRPC request.
+    ///
+    /// The reader is read in pieces of `chunk_size` bytes (the last one may be shorter), each of
+    /// which is turned into a request message by `wrap`. The metadata and extensions of `request`
+    /// are sent along with the call.
+    ///
+    /// If reading fails, the request stream is aborted and an `Internal` status describing the
+    /// read error is returned. Such failures aren't reported to the circuit breaker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    #[cfg(feature = "channel")]
+    pub async fn upload<R, F, M1, M2, C>(
+        &mut self,
+        request: Request<R>,
+        chunk_size: usize,
+        wrap: F,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<M2>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        R: tokio::io::AsyncRead + Send + 'static,
+        F: FnMut(bytes::Bytes) -> M1 + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let request = self.config.check_short_circuit(request, &path)?;
+        self.config.check_circuit_breaker(&path)?;
+
+        let read_error = Arc::default();
+        let request = request.map(|reader| {
+            super::upload::ReadChunks::new(reader, chunk_size, wrap, Arc::clone(&read_error))
+        });
+
+        let result = match self.call_streaming(request, path.clone(), codec).await? {
+            Ok((response, _)) => self.single_message(response).await,
+            Err(status) => Err(status),
+        };
+
+        if let Some(status) = read_error.get() {
+            return Err(status.clone());
+        }
+
+        self.config.record_outcome(&path, &result);
+        result
+    }
+
+    async fn single_message<M2>(
+        &self,
+        response: Response<Streaming<M2>>,
+    ) -> Result<Response<M2>, Status> {
+        let (mut parts, body, extensions) = response.into_parts();
+
+        let mut body = pin!(body);
+
+        let message = body
+            .try_next()
+            .await
+            .map_err(|mut status| {
+                status.metadata_mut().merge(parts.clone());
+                status
+            })?
+            .ok_or_else(|| Status::internal("Missing response message."))?;
+
+        let trailers = if self.config.lenient_unary {
+            body.trailers_skipping_data().await?
+        } else {
+            body.trailers().await?
+        };
+
+        if let Some(trailers) = trailers {
+            parts.merge(trailers);
+        }
+
+        Ok(Response::from_parts(parts, message, extensions))
+    }
+
+    /// Send a server side streaming gRPC request.
+    ///
+    /// Returns once the response headers are received, without waiting for any message. The
+    /// metadata of the returned [`Response`] is the complete initial metadata sent by the server,
+    /// so it can be acted upon before the first message is read from the [`Streaming`].
+    pub async fn server_streaming<M1, M2, C>(
+        &mut self,
+        request: Request<M1>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<Streaming<M2>>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let request = request.map(|m| tokio_stream::once(m));
+        self.streaming(request, path, codec).await
+    }
+
+    /// Send a server side streaming gRPC request that resumes after transient failures.
+    ///
+    /// When the response stream fails with a retryable status, the call is issued again on a
+    /// clone of this client with the request updated by the [`ResumePolicy`], and the returned
+    /// stream continues with the messages of the new call. The response metadata is the one of
+    /// the first call. See [`ResumePolicy`] for the delivery guarantees.
+    ///
+    /// Failures of the first call are returned as is.
+    ///
+    /// ```rust
+    /// # use tonic::{client::{Grpc, ResumePolicy}, codec::Codec, Request, Status};
+    /// # use tokio_stream::{Stream, StreamExt};
+    /// # #[derive(Clone)] struct Query { offset: u64 }
+    /// # struct Record { offset: u64 }
+    /// # async fn ex<C>(mut grpc: Grpc<tonic::transport::Channel>, codec: C) -> Result<(), Status>
+    /// # where C: Codec<Encode = Query, Decode = Record> + Clone + Send + 'static,
+    /// #       C::Encoder: Send + 'static, C::Decoder: Send + 'static {
+    /// let policy = ResumePolicy::new(
+    ///     |record: &Record| Some(record.offset + 1),
+    ///     |request: &mut Request<Query>, offset| request.get_mut().offset = offset,
+    /// );
+    ///
+    /// let records = grpc
+    ///     .server_streaming_with_resume(
+    ///         Request::new(Query { offset: 0 }),
+    ///         "/log.Log/Tail".parse().unwrap(),
+    ///         codec,
+    ///         policy,
+    ///     )
+    ///     .await?
+    ///     .into_inner();
+    ///
+    /// let mut records = std::pin::pin!(records);
+    /// while let Some(record) = records.try_next().await? {
+    ///     println!("{}", record.offset);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn server_streaming_with_resume<M1, M2, C, K, E, I>(
+        &mut self,
+        request: Request<M1>,
+        path: PathAndQuery,
+        codec: C,
+        policy: ResumePolicy<E, I>,
+    ) -> Result<Response<impl Stream<Item = Result<M2, Status>>>, Status>
+    where
+        T: GrpcService<BoxBody> + Clone + Send + 'static,
+        T::Future: Send,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        C: Codec<Encode = M1, Decode = M2> + Clone + Send + 'static,
+        M1: Clone + Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+        K: Clone,
+        E: FnMut(&M2) -> Option<K>,
+        I: FnMut(&mut Request<M1>, K),
+    {
+        let (metadata, extensions, message) = request.into_parts();
+        let first = Request::from_parts(metadata.clone(), extensions.clone(), message.clone());
+        let response = self
+            .server_streaming(first, path.clone(), codec.clone())
+            .await?;
+
+        let grpc = self.clone();
+        let call = move |request| -> resume::CallFuture<M2> {
+            let mut grpc = grpc.clone();
+            let path = path.clone();
+            let codec = codec.clone();
+            Box::pin(async move {
+                grpc.ready()
+                    .await
+                    .map_err(|err| grpc.config.transport_error(err))?;
+                grpc.server_streaming(request, path, codec).await
+            })
+        };
+
+        let request = Request::from_parts(metadata, extensions, message);
+        Ok(response.map(|streaming| resume::Resumable::new(request, policy, call, streaming)))
+    }
+
+    /// Send a server side streaming gRPC request and track how long it takes.
+    ///
+    /// The returned [`CallTiming`] records the time to the first response message and the
+    /// total time until the response stream completes, as the stream is consumed.
+    pub async fn server_streaming_timed<M1, M2, C>(
+        &mut self,
+        request: Request<M1>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<(Response<Streaming<M2>>, CallTiming), Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let request = request.map(|m| tokio_stream::once(m));
+        self.streaming_timed(request, path, codec).await
+    }
+
+    /// Send a bi-directional streaming gRPC request.
+    ///
+    /// Like [`Grpc::server_streaming`], returns once the response headers are received, with
+    /// the initial metadata of the server in the [`Response`].
+    ///
+    /// The request stream must be `'static` because it is erased into a [`BoxBody`] that is handed
+    /// to the inner [`GrpcService`], which may keep polling it after this method returns. See
+    /// [`Grpc::client_streaming_scoped`] for a variant that accepts borrowing streams.
+    pub async fn streaming<S, M1, M2, C>(
+        &mut self,
+        request: Request<S>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<Response<Streaming<M2>>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let request = self.config.check_short_circuit(request, &path)?;
+        self.config.check_circuit_breaker(&path)?;
+        let mut result = self
+            .call_streaming(request.map(|s| s.map(Ok)), path.clone(), codec)
+            .await?;
+        self.config.record_streaming_outcome(&path, &mut result);
+        let (response, _) = result?;
+        Ok(response)
+    }
+
+    /// Send a bi-directional streaming gRPC request and track how long it takes.
+    ///
+    /// See [`Grpc::server_streaming_timed`] for what the returned [`CallTiming`] records.
+    pub async fn streaming_timed<S, M1, M2, C>(
+        &mut self,
+        request: Request<S>,
+        path: PathAndQuery,
+        codec: C,
+    ) -> Result<(Response<Streaming<M2>>, CallTiming), Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = M1> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let request = self.config.check_short_circuit(request, &path)?;
+        self.config.check_circuit_breaker(&path)?;
+        let mut result = self
+            .call_streaming(request.map(|s| s.map(Ok)), path.clone(), codec)
+            .await?;
+        self.config.record_streaming_outcome(&path, &mut result);
+        let (mut response, start) = result?;
+        let timing = CallTiming::new(start);
+        let marks = timing.clone();
+        response
+            .get_mut()
+            .on_progress(move |progress| match progress {
+                StreamProgress::Message => marks.record_message(),
+                StreamProgress::End(_) => marks.record_end(),
+            });
+        Ok((response, timing))
+    }
+
+    /// Open a bi-directional streaming call that is driven message by message.
+    ///
+    /// The returned [`Call`] exposes the request and response streams imperatively, see its
+    /// documentation. The call is made on a clone of this client, which is awaited to be ready
+    /// first.
+    ///
+    /// ```rust
+    /// # use tonic::{client::Grpc, codec::Codec, Status};
+    /// # async fn ex<C>(grpc: Grpc<tonic::transport::Channel>, codec: C) -> Result<(), Status>
+    /// # where C: Codec<Encode = String, Decode = String> + Send + 'static {
+    /// let mut call = grpc.open("/echo.Echo/BidiEcho".parse().unwrap(), codec);
+    ///
+    /// call.send_message("ping".to_string()).await?;
+    /// let pong = call.recv_message().await?;
+    ///
+    /// call.finish_send();
+    /// while let Some(message) = call.recv_message().await? {
+    ///     println!("{message}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "channel")]
+    pub fn open<M1, M2, C>(&self, path: PathAndQuery, codec: C) -> Call<M1, M2>
+    where
+        T: GrpcService<BoxBody> + Clone + Send + 'static,
+        T::Future: Send,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        C: Codec<Encode = M1, Decode = M2> + Send + 'static,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+
+        let mut grpc = self.clone();
+        let response = Box::pin(async move {
+            grpc.ready()
+                .await
+                .map_err(|err| grpc.config.transport_error(err))?;
+            let request = Request::new(tokio_stream::wrappers::ReceiverStream::new(rx));
+            grpc.streaming(request, path, codec).await
+        });
+
+        Call::new(tx, response)
+    }
+
+    /// Dispatch a streaming request, returning the response along with the instant right
+    /// before the inner service was called.
+    ///
+    /// The outer error is returned when the call fails before it is dispatched, for example
+    /// because the interceptor rejected it, the inner result is the outcome of the dispatched
+    /// call. The circuit breaker is neither checked nor updated here, callers do that since they
+    /// know when the outcome of the call is final, and only record dispatched calls.
+    async fn call_streaming<S, M1, M2, C>(
+        &mut self,
+        mut request: Request<S>,
+        path: PathAndQuery,
+        mut codec: C,
+    ) -> Result<Result<(Response<Streaming<M2>>, Instant), Status>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+        S: Stream<Item = Result<M1, Status>> + Send + 'static,
+        C: Codec<Encode = M1, Decode = M2>,
+        M1: Send + Sync + 'static,
+        M2: Send + Sync + 'static,
+    {
+        let compression_override = request
+            .extensions_mut()
+            .remove::<SingleMessageCompressionOverride>()
+            .unwrap_or_default();
+        let send_encoding = match compression_override {
+            SingleMessageCompressionOverride::Inherit => {
+                self.config.send_encoding(codec.preferred_compression())
+            }
+            SingleMessageCompressionOverride::Disable => None,
+        };
+
+        tracing::debug!(
+            path = %path,
+            send_encoding = ?send_encoding,
+            accept_encodings = ?self
+                .config
+                .accept_compression_encodings
+                .into_accept_encoding_header_value(),
+            max_decoding_message_size = ?self.config.decoding_limit(),
+            max_encoding_message_size = ?self.config.encoding_limit(),
+            deadline = request.metadata().contains_key(GRPC_TIMEOUT_HEADER),
+            "starting call"
+        );
+
+        let terminal_metadata = request
+            .extensions_mut()
+            .remove::<TerminalMetadata>()
+            .map(|metadata| (metadata, TerminalMetadataAcknowledged::default()));
+        let acknowledged = terminal_metadata.as_ref().map(|(_, ack)| ack.clone());
+        if let Some(acknowledged) = &acknowledged {
+            request
+                .metadata_mut()
+                .insert(TERMINAL_METADATA_HEADER, MetadataValue::from_static("1"));
+            request.extensions_mut().insert(acknowledged.clone());
+        }
+
+        if let Some(interceptor) = &self.config.interceptor {
+            let (metadata, extensions, message) = request.into_parts();
+            let mut intercepted = Request::from_parts(metadata, extensions, ());
+            interceptor.intercept(&mut intercepted).await?;
+
+            let (metadata, extensions, ()) = intercepted.into_parts();
+            request = Request::from_parts(metadata, extensions, message);
+        }
+
+        // Limits not set on this client fall back to those of the channel, if any.
+        let size_defaults = (self.config.decoding_limit().is_none()
+            || self.config.encoding_limit().is_none())
+        .then(|| self.size_defaults.clone());
+        if let Some(size_defaults) = &size_defaults {
+            request.extensions_mut().insert(size_defaults.clone());
+        }
+
+        let request = request
+            .map(|s| {
+                let body = encode_client_results(
+                    codec.encoder(),
+                    s,
+                    send_encoding,
+                    self.config.compression_policy,
+                    self.config.encoding_limit(),
+                    self.config.send_buffer_size,
+                    size_defaults.clone(),
+                );
+
+                let body = match self.config.max_inflight_bytes {
+                    Some(max) => body.with_max_inflight_bytes(max),
+                    None => body,
+                };
+
+                match terminal_metadata {
+                    Some((TerminalMetadata::Fixed(metadata), acknowledged)) => body
+                        .with_terminal_metadata(metadata)
+                        .with_terminal_frame_acknowledged(acknowledged.flag()),
+                    Some((TerminalMetadata::Deferred(trailers), acknowledged)) => body
+                        .with_outgoing_trailers(trailers)
+                        .with_terminal_frame_acknowledged(acknowledged.flag()),
+                    None => body,
+                }
+            })
+            .map(BoxBody::new);
+
+        let mut request = self.config.prepare_request(request, path, send_encoding);
+        let request_id = request.extensions().get::<RequestId>().cloned();
+
+        if compression_override == SingleMessageCompressionOverride::Disable {
+            request.headers_mut().insert(
+                crate::codec::compression::ENCODING_HEADER,
+                HeaderValue::from_static("identity"),
+            );
+        }
+
+        if let Some(source) = &self.config.token_source {
+            let token = source.token().await?;
+            request
+                .headers_mut()
+                .insert(AUTHORIZATION, token_source::bearer(&token));
+        }
+
+        #[cfg(feature = "test-util")]
+        if let Some(faults) = &self.config.fault_injection {
+            faults.inject().await?;
+        }
+
+        if self.config.fail_fast {
+            match self.poll_ready_once().await {
+                Poll::Ready(result) => result.map_err(|err| self.config.transport_error(err))?,
+                Poll::Pending => return Err(Status::unavailable("service is not ready")),
+            }
+        }
+
+        let start = Instant::now();
+        let response = match self.inner.call(request).await {
+            Ok(response) => response,
+            Err(err) => return Ok(Err(self.config.transport_error(err))),
+        };
+
+        if let Some(acknowledged) = &acknowledged {
+            if response.headers().contains_key(TERMINAL_METADATA_HEADER) {
+                acknowledged.set();
+            }
+        }
+
+        let decoder = codec.decoder();
+
+        Ok(self
+            .create_response(decoder, C::CONTENT_SUBTYPE, response)
+            .map(|mut response| {
+                if let Some(request_id) = request_id {
+                    response.extensions_mut().insert(request_id);
+                }
+                (response, start)
+            }))
+    }
+
+    // Keeping this code in a separate function from Self::streaming lets functions that return the
+    // same output share the generated binary code
+    #[allow(clippy::result_large_err)]
+    fn create_response<M2>(
+        &self,
+        decoder: impl Decoder<Item = M2, Error = Status> + Send + 'static,
+        content_subtype: Option<&'static str>,
+        response: http::Response<T::ResponseBody>,
+    ) -> Result<Response<Streaming<M2>>, Status>
+    where
+        T: GrpcService<BoxBody>,
+        T::ResponseBody: Body + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<crate::Error>,
+    {
+        let encoding = CompressionEncoding::from_response_encoding_header(
+            response.headers(),
+            self.config.accept_compression_encodings,
+            self.config.accept_unadvertised_compression,
         )?;
+        let decoder = WrappedDecoder::new(decoder, self.config.response_body_transform.clone());
+
+        let status_code = response.status();
+        // HTTP/1.1 servers without support for trailers, such as gRPC-Web proxies, send them in
+        // the last frame of the body instead.
+        let in_body_trailers = response.version() < http::Version::HTTP_2;
+        let trailers_only_status = Status::from_header_map(response.headers());
+
+        // We do not need to check for trailers if the `grpc-status` header is present
+        // with a valid code.
+        let expect_additional_trailers = if let Some(status) = trailers_only_status {
+            if status.code() != Code::Ok {
+                return Err(status);
+            }
+
+            false
+        } else {
+            true
+        };
+
+        if let Some(expected) = content_subtype {
+            check_content_subtype(
+                response.headers(),
+                expected,
+                self.config.strict_content_subtype,
+            )?;
+        }
+
+        let response = response.map(|body| {
+            if expect_additional_trailers {
+                Streaming::new_response(decoder, body, status_code, encoding, self.decoding_limit())
+            } else {
+                Streaming::new_empty(decoder, body)
+            }
+        });
+
+        #[cfg(feature = "channel")]
+        let response = match self.config.stream_idle_timeout {
+            Some(timeout) => response.map(|streaming| streaming.with_idle_timeout(timeout)),
+            None => response,
+        };
+
+        let response = if self.config.lenient_compression_flag {
+            response.map(Streaming::with_lenient_compression_flag)
+        } else {
+            response
+        };
+
+        let response = if in_body_trailers {
+            response.map(Streaming::with_in_body_trailers)
+        } else {
+            response
+        };
+
+        Ok(Response::from_http(response))
+    }
+}
+
+impl GrpcConfig {
+    /// Set the origin requests are sent to, see [`Grpc::with_origin`].
+    pub fn origin(mut self, origin: Uri) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Compress requests with the provided encoding, see [`Grpc::send_compressed`].
+    pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+        self.send_compression_encodings = Some(encoding);
+        self
+    }
+
+    /// Set which request messages are compressed, see [`Grpc::compression_policy`].
+    pub fn compression_policy(mut self, policy: CompressionPolicy) -> Self {
+        self.compression_policy = policy;
+        self
+    }
+
+    /// Enable accepting compressed responses, see [`Grpc::accept_compressed`].
+    pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+        self.accept_compression_encodings.enable(encoding);
+        self
+    }
+
+    /// Decode responses compressed with an encoding that wasn't enabled, see
+    /// [`Grpc::accept_unadvertised_compression`].
+    pub fn accept_unadvertised_compression(mut self, enabled: bool) -> Self {
+        self.accept_unadvertised_compression = enabled;
+        self
+    }
+
+    /// Limits the maximum size of a decoded message, see [`Grpc::max_decoding_message_size`].
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.max_decoding_message_size = Some(limit);
+        self
+    }
+
+    /// Limits the maximum size of an encoded message, see [`Grpc::max_encoding_message_size`].
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.max_encoding_message_size = Some(limit);
+        self
+    }
+
+    /// Use `limits` for the message sizes that aren't limited, see
+    /// [`Grpc::with_default_limits`].
+    pub fn default_limits(mut self, limits: DefaultLimits) -> Self {
+        self.default_limits = Some(limits);
+        self
+    }
+
+    /// Guard calls with the provided [`CircuitBreaker`], see [`Grpc::with_circuit_breaker`].
+    pub fn circuit_breaker(mut self, breaker: impl CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(Arc::new(breaker));
+        self
+    }
+
+    /// Authenticate calls with tokens from the provided [`TokenSource`], see
+    /// [`Grpc::with_token_source`].
+    pub fn token_source(mut self, source: impl TokenSource) -> Self {
+        self.token_source = Some(Arc::new(source));
+        self
+    }
+
+    /// Run the provided [`AsyncInterceptor`] before each call, see
+    /// [`Grpc::with_async_interceptor`].
+    pub fn async_interceptor(mut self, interceptor: impl AsyncInterceptor) -> Self {
+        self.interceptor = Some(Arc::new(interceptor));
+        self
+    }
+
+    /// Inject failures into calls, see [`Grpc::with_fault_injection`].
+    #[cfg(feature = "test-util")]
+    pub fn fault_injection(mut self, config: super::FaultConfig) -> Self {
+        self.fault_injection = Some(Arc::new(super::fault::FaultInjector::new(config)));
+        self
+    }
+
+    /// Fail calls without dispatching them, see [`Grpc::short_circuit_if`].
+    pub fn short_circuit_if<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request<()>, &PathAndQuery) -> Option<Status> + Send + Sync + 'static,
+    {
+        self.short_circuit = Some(Arc::new(f));
+        self
+    }
+
+    /// Ignore trailing data after unary responses, see [`Grpc::with_lenient_unary`].
+    pub fn lenient_unary(mut self, enabled: bool) -> Self {
+        self.lenient_unary = enabled;
+        self
+    }
+
+    /// Generate an ID for calls without the `header_name` header, see
+    /// [`Grpc::with_auto_request_id`].
+    pub fn auto_request_id(mut self, header_name: HeaderName) -> Self {
+        self.auto_request_id = Some(header_name);
+        self
+    }
+
+    /// Accept messages flagged as compressed without an encoding, see
+    /// [`Grpc::with_lenient_compression_flag`].
+    pub fn lenient_compression_flag(mut self, enabled: bool) -> Self {
+        self.lenient_compression_flag = enabled;
+        self
+    }
+
+    /// Fail calls when the inner service is not ready, see [`Grpc::with_fail_fast`].
+    pub fn fail_fast(mut self, enabled: bool) -> Self {
+        self.fail_fast = enabled;
+        self
+    }
+
+    /// Reject responses without a content subtype, see [`Grpc::with_strict_content_subtype`].
+    pub fn strict_content_subtype(mut self, enabled: bool) -> Self {
+        self.strict_content_subtype = enabled;
+        self
+    }
+
+    /// Set the initial capacity of the request buffer, see [`Grpc::with_send_buffer_size`].
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Limit the request bytes held by the transport, see [`Grpc::with_max_inflight_bytes`].
+    pub fn max_inflight_bytes(mut self, max: usize) -> Self {
+        self.max_inflight_bytes = Some(max);
+        self
+    }
+
+    /// Fail response streams that go quiet, see [`Grpc::with_stream_idle_timeout`].
+    #[cfg(feature = "channel")]
+    pub fn stream_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Transform the bytes of each response message, see [`Grpc::with_response_body_transform`].
+    pub fn response_body_transform(
+        mut self,
+        transform: Arc<dyn Fn(Bytes) -> Result<Bytes, Status> + Send + Sync>,
+    ) -> Self {
+        self.response_body_transform = Some(DecodeTransform::Fallible(transform));
+        self
+    }
+
+    /// Convert the errors of the inner service into statuses with `mapper`, see
+    /// [`Grpc::with_transport_error_mapper`].
+    pub fn transport_error_mapper(
+        mut self,
+        mapper: Arc<dyn Fn(crate::Error) -> Status + Send + Sync>,
+    ) -> Self {
+        self.transport_error_mapper = Some(mapper);
+        self
+    }
+
+    /// The limit of decoded messages, if any is set.
+    fn decoding_limit(&self) -> Option<usize> {
+        self.max_decoding_message_size
+            .or_else(|| self.default_limits.map(|l| l.max_decoding_message_size))
+    }
+
+    /// The limit of encoded messages, if any is set.
+    fn encoding_limit(&self) -> Option<usize> {
+        self.max_encoding_message_size
+            .or_else(|| self.default_limits.map(|l| l.max_encoding_message_size))
+    }
+
+    /// Convert an error of the inner service into a status.
+    fn transport_error(&self, err: impl Into<crate::Error>) -> Status {
+        match &self.transport_error_mapper {
+            Some(mapper) => mapper(err.into()),
+            None => Status::from_error_generic(err),
+        }
+    }
+
+    /// Fail the call with the status returned by the short circuit, if any.
+    #[allow(clippy::result_large_err)]
+    fn check_short_circuit<S>(
+        &self,
+        request: Request<S>,
+        path: &PathAndQuery,
+    ) -> Result<Request<S>, Status> {
+        let Some(short_circuit) = &self.short_circuit else {
+            return Ok(request);
+        };
+
+        let (metadata, extensions, message) = request.into_parts();
+        let request = Request::from_parts(metadata, extensions, ());
+        if let Some(status) = short_circuit(&request, path) {
+            return Err(status);
+        }
+
+        let (metadata, extensions, ()) = request.into_parts();
+        Ok(Request::from_parts(metadata, extensions, message))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn check_circuit_breaker(&self, path: &PathAndQuery) -> Result<(), Status> {
+        match &self.circuit_breaker {
+            Some(breaker) if breaker.is_open_for(path.path()) => {
+                Err(Status::unavailable("Circuit breaker is open"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The encoding to compress requests with: the one chosen with [`Grpc::send_compressed`], or
+    /// else the `preferred` one of the codec, as long as this client advertises it in
+    /// `grpc-accept-encoding`.
+    fn send_encoding(&self, preferred: Option<CompressionEncoding>) -> Option<CompressionEncoding> {
+        self.send_compression_encodings.or_else(|| {
+            preferred.filter(|&encoding| self.accept_compression_encodings.is_enabled(encoding))
+        })
+    }
+
+    fn record_outcome<R>(&self, path: &PathAndQuery, result: &Result<R, Status>) {
+        if let Some(breaker) = &self.circuit_breaker {
+            breaker.record(path.path(), result.as_ref().err());
+        }
+    }
+
+    /// Records the outcome of a streaming call once its response stream ends, or right away if
+    /// the call failed before the response headers were received.
+    fn record_streaming_outcome<M, R>(
+        &self,
+        path: &PathAndQuery,
+        result: &mut Result<(Response<Streaming<M>>, R), Status>,
+    ) {
+        let Some(breaker) = &self.circuit_breaker else {
+            return;
+        };
+
+        match result {
+            Ok((response, _)) => {
+                let breaker = breaker.clone();
+                let path = path.path().to_string();
+                response.get_mut().on_progress(move |progress| {
+                    if let StreamProgress::End(status) = progress {
+                        breaker.record(&path, status);
+                    }
+                });
+            }
+            Err(status) => breaker.record(path.path(), Some(status)),
+        }
+    }
+
+    fn prepare_request(
+        &self,
+        request: Request<BoxBody>,
+        path: PathAndQuery,
+        send_encoding: Option<CompressionEncoding>,
+    ) -> http::Request<BoxBody> {
+        let mut parts = self.origin.clone().into_parts();
+
+        match &parts.path_and_query {
+            Some(pnq) if pnq != "/" => {
+                parts.path_and_query = Some(
+                    format!("{}{}", pnq.path(), path)
+                        .parse()
+                        .expect("must form valid path_and_query"),
+                )
+            }
+            _ => {
+                parts.path_and_query = Some(path);
+            }
+        }
+
+        let uri = Uri::from_parts(parts).expect("path_and_query only is valid Uri");
+
+        let mut request = request.into_http(
+            uri,
+            http::Method::POST,
+            http::Version::HTTP_2,
+            SanitizeHeaders::Yes,
+        );
+
+        // Add the gRPC related HTTP headers
+        request
+            .headers_mut()
+            .insert(TE, HeaderValue::from_static("trailers"));
+
+        // Set the content type
+        request
+            .headers_mut()
+            .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+
+        if let Some(header_name) = &self.auto_request_id {
+            if !request.headers().contains_key(header_name) {
+                let id = RequestId::generate();
+                request
+                    .headers_mut()
+                    .insert(header_name.clone(), id.header_value());
+                request.extensions_mut().insert(id);
+            }
+        }
+
+        #[cfg(any(feature = "gzip", feature = "zstd"))]
+        if let Some(encoding) = send_encoding {
+            request.headers_mut().insert(
+                crate::codec::compression::ENCODING_HEADER,
+                encoding.into_header_value(),
+            );
+        }
+        // There is no encoding to send without compression support.
+        #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+        let _ = send_encoding;
+
+        if let Some(header_value) = self
+            .accept_compression_encodings
+            .into_accept_encoding_header_value()
+        {
+            request.headers_mut().insert(
+                crate::codec::compression::ACCEPT_ENCODING_HEADER,
+                header_value,
+            );
+        }
+
+        request
+    }
+}
+
+/// Checks that the gRPC `content-type` of a response declares the `expected` subtype.
+///
+/// Content types other than `application/grpc` are left to the rest of the response handling.
+#[allow(clippy::result_large_err)]
+fn check_content_subtype(
+    headers: &http::HeaderMap,
+    expected: &str,
+    strict: bool,
+) -> Result<(), Status> {
+    let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+        return Ok(());
+    };
+
+    const GRPC: &str = "application/grpc";
+    let Some(rest) = content_type
+        .get(..GRPC.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(GRPC))
+        .map(|_| &content_type[GRPC.len()..])
+    else {
+        return Ok(());
+    };
+
+    let subtype = match rest.strip_prefix('+') {
+        Some(subtype) => subtype.split(';').next().unwrap_or_default().trim(),
+        None if rest.is_empty() || rest.starts_with(';') => {
+            if strict {
+                return Err(Status::internal(format!(
+                    "protocol error: received response with content-type {:?} without a subtype, expected {:?}",
+                    content_type, expected
+                )));
+            }
+            return Ok(());
+        }
+        // Some other type, such as `application/grpc-web`.
+        None => return Ok(()),
+    };
+
+    if subtype.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(Status::internal(format!(
+            "protocol error: received response with content-type {:?}, expected subtype {:?}",
+            content_type, expected
+        )))
+    }
+}
+
+/// Carries the metadata of [`Grpc::client_streaming_with_terminal_metadata`] and
+/// [`Grpc::client_streaming_with_outgoing_trailers`] to the dispatch.
+#[derive(Clone)]
+enum TerminalMetadata {
+    Fixed(MetadataMap),
+    Deferred(OutgoingTrailers),
+}
+
+impl<T: Clone> Clone for Grpc<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            config: self.config.clone(),
+            size_defaults: self.size_defaults.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Grpc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grpc")
+            .field("inner", &self.inner)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl fmt::Debug for GrpcConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("GrpcConfig");
+
+        f.field("origin", &self.origin);
+
+        f.field("compression_encoding", &self.send_compression_encodings);
+
+        f.field("compression_policy", &self.compression_policy);
+
+        f.field(
+            "accept_compression_encodings",
+            &self.accept_compression_encodings,
+        );
+
+        f.field(
+            "accept_unadvertised_compression",
+            &self.accept_unadvertised_compression,
+        );
+
+        f.field("max_decoding_message_size", &self.max_decoding_message_size);
+
+        f.field("max_encoding_message_size", &self.max_encoding_message_size);
+
+        f.field("default_limits", &self.default_limits);
+
+        f.field("send_buffer_size", &self.send_buffer_size);
+
+        f.field("max_inflight_bytes", &self.max_inflight_bytes);
+
+        f.field(
+            "circuit_breaker",
+            &self.circuit_breaker.as_ref().map(|_| "CircuitBreaker"),
+        );
+
+        f.field(
+            "token_source",
+            &self.token_source.as_ref().map(|_| "TokenSource"),
+        );
+
+        f.field(
+            "interceptor",
+            &self.interceptor.as_ref().map(|_| "AsyncInterceptor"),
+        );
+
+        f.field("strict_content_subtype", &self.strict_content_subtype);
+
+        f.field("lenient_unary", &self.lenient_unary);
+
+        f.field("lenient_compression_flag", &self.lenient_compression_flag);
+
+        f.field("fail_fast", &self.fail_fast);
+
+        f.field("auto_request_id", &self.auto_request_id);
+
+        #[cfg(feature = "channel")]
+        f.field("stream_idle_timeout", &self.stream_idle_timeout);
+
+        f.field(
+            "response_body_transform",
+            &self.response_body_transform.as_ref().map(|_| "Fn"),
+        );
+
+        f.field(
+            "transport_error_mapper",
+            &self.transport_error_mapper.as_ref().map(|_| "Fn"),
+        );
+
+        f.field("short_circuit", &self.short_circuit.as_ref().map(|_| "Fn"));
+
+        #[cfg(feature = "test-util")]
+        f.field("fault_injection", &self.fault_injection);
+
+        f.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::empty_body;
+
+    fn check(content_type: Option<&'static str>, strict: bool) -> Result<(), Code> {
+        let mut headers = http::HeaderMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        check_content_subtype(&headers, "proto", strict).map_err(|status| status.code())
+    }
+
+    #[test]
+    fn content_subtype_matches() {
+        assert_eq!(check(Some("application/grpc+proto"), true), Ok(()));
+        assert_eq!(check(Some("Application/gRPC+Proto"), true), Ok(()));
+        assert_eq!(
+            check(Some("application/grpc+proto; charset=utf-8"), true),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn content_subtype_mismatch() {
+        assert_eq!(
+            check(Some("application/grpc+json"), false),
+            Err(Code::Internal)
+        );
+        assert_eq!(
+            check(Some("application/grpc+json;charset=utf-8"), false),
+            Err(Code::Internal)
+        );
+    }
+
+    #[test]
+    fn missing_content_subtype_depends_on_strictness() {
+        assert_eq!(check(Some("application/grpc"), false), Ok(()));
+        assert_eq!(check(Some("application/grpc;x=y"), false), Ok(()));
+        assert_eq!(check(Some("application/grpc"), true), Err(Code::Internal));
+    }
+
+    #[test]
+    fn other_content_types_are_not_checked() {
+        assert_eq!(check(None, true), Ok(()));
+        assert_eq!(check(Some("text/html"), true), Ok(()));
+        assert_eq!(check(Some("application/grpc-web+json"), true), Ok(()));
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn extensions_reach_inner_service() {
+        #[derive(Clone)]
+        struct Shard(u32);
+
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            assert_eq!(request.extensions().get::<Shard>().unwrap().0, 3);
+            Ok::<_, std::convert::Infallible>(Status::unimplemented("").into_http())
+        });
+
+        let mut grpc = Grpc::new(svc);
+        let status = grpc
+            .unary::<String, String, _>(
+                Request::new(String::new()).with_extension(Shard(3)),
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+    }
+
+    #[tokio::test]
+    async fn warm_up_sends_nothing() {
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let svc = tower::service_fn({
+            let calls = calls.clone();
+            move |_: http::Request<BoxBody>| {
+                calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                future::ready(Ok::<_, std::convert::Infallible>(http::Response::new(
+                    empty_body(),
+                )))
+            }
+        });
+
+        let mut grpc = Grpc::new(svc);
+        grpc.warm_up().await.unwrap();
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn auto_request_id_is_sent_and_returned() {
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            let id = request.headers().get("x-request-id").unwrap().clone();
+            let mut response = http::Response::new(empty_body());
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            response
+                .headers_mut()
+                .insert("grpc-status", HeaderValue::from_static("0"));
+            response.headers_mut().insert("x-request-id", id);
+            Ok::<_, std::convert::Infallible>(response)
+        });
+
+        let mut grpc = Grpc::new(svc).with_auto_request_id(HeaderName::from_static("x-request-id"));
+        let path = || PathAndQuery::from_static("/test.Test/Call");
+        let codec = crate::codec::ProstCodec::<String, String>::default;
+
+        let response = grpc
+            .server_streaming(Request::new(String::new()), path(), codec())
+            .await
+            .unwrap();
+        let id = response.extensions().get::<RequestId>().unwrap();
+        let sent = response.metadata().get("x-request-id").unwrap();
+        assert_eq!(sent, id.as_str());
+
+        let mut request = Request::new(String::new());
+        request
+            .metadata_mut()
+            .insert("x-request-id", MetadataValue::from_static("mine"));
+        let response = grpc
+            .server_streaming(request, path(), codec())
+            .await
+            .unwrap();
+        assert!(response.extensions().get::<RequestId>().is_none());
+        assert_eq!(response.metadata().get("x-request-id").unwrap(), "mine");
+    }
+
+    #[cfg(all(feature = "prost", feature = "test-util"))]
+    #[tokio::test]
+    async fn injected_faults_skip_inner_service() {
+        let svc = tower::service_fn(|_: http::Request<BoxBody>| async move {
+            Ok::<_, std::convert::Infallible>(Status::internal("dispatched").into_http())
+        });
+
+        let mut grpc = Grpc::new(svc).with_fault_injection(crate::client::FaultConfig {
+            abort_probability: 1.0,
+            abort_code: Code::ResourceExhausted,
+            ..Default::default()
+        });
+        let status = grpc
+            .unary::<String, String, _>(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn circuit_breaker_only_records_dispatched_calls() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        #[derive(Clone, Default)]
+        struct Recorded(Arc<AtomicUsize>);
+
+        impl CircuitBreaker for Recorded {
+            fn is_open(&self) -> bool {
+                false
+            }
+
+            fn record_success(&self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+
+            fn record_failure(&self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        #[derive(Clone, Default)]
+        struct Reject(Arc<AtomicBool>);
+
+        impl AsyncInterceptor for Reject {
+            fn intercept<'a>(
+                &'a self,
+                _: &'a mut Request<()>,
+            ) -> crate::client::InterceptFuture<'a> {
+                let reject = self.0.load(Ordering::SeqCst);
+                Box::pin(async move {
+                    if reject {
+                        return Err(Status::unavailable("rejected"));
+                    }
+                    Ok(())
+                })
+            }
+        }
+
+        let svc = tower::service_fn(|_: http::Request<BoxBody>| async move {
+            Ok::<_, std::convert::Infallible>(Status::unavailable("dispatched").into_http())
+        });
+        let recorded = Recorded::default();
+        let reject = Reject::default();
+        let mut grpc = Grpc::new(svc)
+            .with_circuit_breaker(recorded.clone())
+            .with_async_interceptor(reject.clone());
+        let path = || PathAndQuery::from_static("/test.Test/Call");
+        let codec = crate::codec::ProstCodec::<String, String>::default;
+
+        reject.0.store(true, Ordering::SeqCst);
+        let status = grpc
+            .unary(Request::new(String::new()), path(), codec())
+            .await
+            .unwrap_err();
+        assert_eq!(status.message(), "rejected");
+        assert_eq!(recorded.0.load(Ordering::SeqCst), 0);
+
+        reject.0.store(false, Ordering::SeqCst);
+        let status = grpc
+            .unary(Request::new(String::new()), path(), codec())
+            .await
+            .unwrap_err();
+        assert_eq!(status.message(), "dispatched");
+        assert_eq!(recorded.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn circuit_breaker_records_streams_when_they_end() {
+        #[derive(Clone, Default)]
+        struct Recorded(Arc<std::sync::Mutex<Vec<Option<Code>>>>);
+
+        impl CircuitBreaker for Recorded {
+            fn is_open(&self) -> bool {
+                false
+            }
+
+            fn record_success(&self) {}
+
+            fn record_failure(&self) {}
+
+            fn record(&self, _: &str, status: Option<&Status>) {
+                self.0.lock().unwrap().push(status.map(Status::code));
+            }
+        }
+
+        // Sends its headers right away, then fails the stream.
+        let svc = tower::service_fn(|_: http::Request<BoxBody>| async {
+            let trailers = Status::unavailable("").to_header_map().unwrap();
+            let frames = [Ok::<_, Status>(http_body::Frame::trailers(trailers))];
+            let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+            let mut response = http::Response::new(crate::body::boxed(body));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            Ok::<_, std::convert::Infallible>(response)
+        });
+        let recorded = Recorded::default();
+        let mut grpc = Grpc::new(svc).with_circuit_breaker(recorded.clone());
+
+        let mut stream = grpc
+            .server_streaming::<String, String, _>(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Stream"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(recorded.0.lock().unwrap().is_empty());
+
+        assert_eq!(
+            stream.message().await.unwrap_err().code(),
+            Code::Unavailable
+        );
+        assert_eq!(stream.message().await.unwrap(), None);
+        assert_eq!(*recorded.0.lock().unwrap(), [Some(Code::Unavailable)]);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn unary_ref_keeps_the_template() {
+        struct AddTraceId;
+
+        impl AsyncInterceptor for AddTraceId {
+            fn intercept<'a>(
+                &'a self,
+                request: &'a mut Request<()>,
+            ) -> crate::client::InterceptFuture<'a> {
+                Box::pin(async move {
+                    assert!(request.metadata().get("x-trace-id").is_none());
+                    request
+                        .metadata_mut()
+                        .insert("x-trace-id", MetadataValue::from_static("1"));
+                    Ok(())
+                })
+            }
+        }
+
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            assert_eq!(request.headers()["x-client"], "probe");
+            assert_eq!(request.headers()["x-trace-id"], "1");
+            Ok::<_, std::convert::Infallible>(Status::unimplemented("").into_http())
+        });
+
+        let mut template = Request::new(String::new());
+        template
+            .metadata_mut()
+            .insert("x-client", MetadataValue::from_static("probe"));
+
+        let mut grpc = Grpc::new(svc).with_async_interceptor(AddTraceId);
+        for _ in 0..2 {
+            let status = grpc
+                .unary_ref::<String, String, _>(
+                    &template,
+                    PathAndQuery::from_static("/test.Test/Call"),
+                    crate::codec::ProstCodec::default(),
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Unimplemented);
+        }
+        assert!(template.metadata().get("x-trace-id").is_none());
+    }
+
+    #[cfg(all(feature = "gzip", feature = "prost"))]
+    #[tokio::test]
+    async fn disable_compression_per_call() {
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            assert_eq!(request.headers()["grpc-encoding"], "identity");
+
+            let body = http_body_util::BodyExt::collect(request.into_body())
+                .await
+                .unwrap()
+                .to_bytes();
+            // The compression flag of the message is unset.
+            assert_eq!(body[0], 0);
+
+            Ok::<_, std::convert::Infallible>(Status::unimplemented("").into_http())
+        });
+
+        let mut grpc = Grpc::new(svc).send_compressed(CompressionEncoding::Gzip);
+        let mut request = Request::new("a".repeat(1024));
+        request.disable_compression();
+
+        let status = grpc
+            .unary::<String, String, _>(
+                request,
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn response_body_transform_runs_before_decoding() {
+        use tokio_stream::StreamExt;
+
+        // Sends the encoded messages "ab", "cd" and "ef" with their bytes reversed.
+        let svc = tower::service_fn(|_: http::Request<BoxBody>| async {
+            let mut frames = Vec::<Result<_, Status>>::new();
+            for message in ["ab", "cd", "ef"] {
+                let encoded = [&[0x0a, 2][..], message.as_bytes()].concat();
+                frames.push(Ok(http_body::Frame::data(
+                    [
+                        &[0, 0, 0, 0, 4][..],
+                        &encoded.into_iter().rev().collect::<Vec<_>>(),
+                    ]
+                    .concat()
+                    .into(),
+                )));
+            }
+            frames.push(Ok(http_body::Frame::trailers(
+                Status::ok("").to_header_map().unwrap(),
+            )));
+
+            let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+            let mut response = http::Response::new(crate::body::boxed(body));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            Ok::<_, std::convert::Infallible>(response)
+        });
+
+        #[allow(clippy::result_large_err)]
+        let transform = |message: Bytes| match message.as_ref() {
+            [b'f', ..] => Err(Status::data_loss("cannot decrypt")),
+            _ => Ok(message.iter().rev().copied().collect()),
+        };
+        let mut grpc = Grpc::new(svc).with_response_body_transform(Arc::new(transform));
+
+        let mut stream = grpc
+            .server_streaming::<String, String, _>(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Stream"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), "ab");
+        assert_eq!(stream.next().await.unwrap().unwrap(), "cd");
+        let status = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::DataLoss);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn call_raw_returns_response_untouched() {
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            assert_eq!(request.uri(), "http://example.com/probe");
+            assert!(request.headers().get(CONTENT_TYPE).is_none());
 
-        let status_code = response.status();
-        let trailers_only_status = Status::from_header_map(response.headers());
+            let mut response = http::Response::new(crate::body::boxed(String::from("not grpc")));
+            *response.status_mut() = http::StatusCode::NOT_FOUND;
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+            Ok::<_, std::convert::Infallible>(response)
+        });
 
-        // We do not need to check for trailers if the `grpc-status` header is present
-        // with a valid code.
-        let expect_additional_trailers = if let Some(status) = trailers_only_status {
-            if status.code() != Code::Ok {
-                return Err(status);
-            }
+        let request = http::Request::builder()
+            .uri("http://example.com/probe")
+            .body(empty_body())
+            .unwrap();
+        let response = Grpc::new(svc).call_raw(request).await.unwrap();
 
-            false
-        } else {
-            true
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/plain");
+        let body = http_body_util::BodyExt::collect(response.into_body())
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(body, "not grpc");
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn short_circuit_skips_inner_service() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = {
+            let calls = calls.clone();
+            tower::service_fn(move |_: http::Request<BoxBody>| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<_, std::convert::Infallible>(Status::unimplemented("").into_http()) }
+            })
         };
 
-        let response = response.map(|body| {
-            if expect_additional_trailers {
-                Streaming::new_response(
-                    decoder,
-                    body,
-                    status_code,
-                    encoding,
-                    self.config.max_decoding_message_size,
-                )
-            } else {
-                Streaming::new_empty(decoder, body)
+        let mut grpc = Grpc::new(svc).short_circuit_if(|request, path| {
+            assert_eq!(request.metadata().get("x-tenant").unwrap(), "a");
+            (path.path() == "/test.Test/Blocked").then(|| Status::unavailable("blocked"))
+        });
+
+        let request = || {
+            let mut request = Request::new(String::new());
+            request
+                .metadata_mut()
+                .insert("x-tenant", "a".parse().unwrap());
+            request
+        };
+        let codec = crate::codec::ProstCodec::<String, String>::default;
+
+        let status = grpc
+            .unary(
+                request(),
+                PathAndQuery::from_static("/test.Test/Blocked"),
+                codec(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(status.message(), "blocked");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let status = grpc
+            .unary(
+                request(),
+                PathAndQuery::from_static("/test.Test/Call"),
+                codec(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(all(feature = "prost", feature = "channel"))]
+    #[tokio::test(start_paused = true)]
+    async fn pipeline_keeps_request_order() {
+        // Every call waits for the others to be dispatched, and the first ones answer last.
+        let barrier = Arc::new(tokio::sync::Barrier::new(3));
+        let svc = tower::service_fn(move |request: http::Request<BoxBody>| {
+            let barrier = barrier.clone();
+            async move {
+                let delay = match request.headers().get("x-delay") {
+                    Some(delay) => delay.to_str().unwrap().parse().unwrap(),
+                    None => 0,
+                };
+                barrier.wait().await;
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                echo(request).await
             }
         });
 
-        Ok(Response::from_http(response))
+        let requests = ["a", "b", "c"].into_iter().enumerate().map(|(i, message)| {
+            let mut request = Request::new(message.to_string());
+            let delay = (30 - i * 10).to_string();
+            request
+                .metadata_mut()
+                .insert("x-delay", delay.parse().unwrap());
+            request
+        });
+        let responses: Vec<_> = Grpc::new(svc)
+            .pipeline(
+                requests,
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::<String, String>::default(),
+            )
+            .map(|response| response.unwrap().into_inner())
+            .collect()
+            .await;
+
+        assert_eq!(responses, ["a", "b", "c"]);
     }
-}
 
-impl GrpcConfig {
-    fn prepare_request(
-        &self,
-        request: Request<BoxBody>,
-        path: PathAndQuery,
-    ) -> http::Request<BoxBody> {
-        let mut parts = self.origin.clone().into_parts();
+    // Answers with a single "hello" message, whose frame is flagged as compressed or not, and
+    // which is compressed with gzip if `gzip` is set.
+    #[cfg(all(feature = "prost", feature = "gzip"))]
+    fn flagged_response(compressed_flag: bool, gzip: bool) -> http::Response<BoxBody> {
+        use crate::codec::compression::{compress, CompressionSettings};
+        use crate::codec::{EncodeBuf, Encoder};
+        use bytes::BufMut;
 
-        match &parts.path_and_query {
-            Some(pnq) if pnq != "/" => {
-                parts.path_and_query = Some(
-                    format!("{}{}", pnq.path(), path)
-                        .parse()
-                        .expect("must form valid path_and_query"),
+        let mut message = bytes::BytesMut::new();
+        crate::codec::ProstCodec::<String, String>::default()
+            .encoder()
+            .encode("hello".to_string(), &mut EncodeBuf::new(&mut message))
+            .unwrap();
+        if compressed_flag && gzip {
+            let settings = CompressionSettings {
+                encoding: CompressionEncoding::Gzip,
+                buffer_growth_interval: 1024,
+            };
+            let len = message.len();
+            let mut compressed = bytes::BytesMut::new();
+            compress(settings, &mut message, &mut compressed, len).unwrap();
+            message = compressed;
+        }
+
+        let mut frame = bytes::BytesMut::new();
+        frame.put_u8(compressed_flag.into());
+        frame.put_u32(message.len() as u32);
+        frame.put(message);
+
+        let frames = [
+            Ok::<_, Status>(http_body::Frame::data(frame.freeze())),
+            Ok(http_body::Frame::trailers(
+                Status::ok("").to_header_map().unwrap(),
+            )),
+        ];
+        let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+        let mut response = http::Response::new(crate::body::boxed(body));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        if gzip {
+            response
+                .headers_mut()
+                .insert("grpc-encoding", HeaderValue::from_static("gzip"));
+        }
+        response
+    }
+
+    #[cfg(all(feature = "prost", feature = "gzip"))]
+    #[tokio::test]
+    async fn compression_flag_must_match_encoding() {
+        async fn call(compressed_flag: bool, gzip: bool, lenient: bool) -> Result<String, Status> {
+            let svc = tower::service_fn(move |_| async move {
+                Ok::<_, std::convert::Infallible>(flagged_response(compressed_flag, gzip))
+            });
+            Grpc::new(svc)
+                .accept_compressed(CompressionEncoding::Gzip)
+                .with_lenient_compression_flag(lenient)
+                .unary(
+                    Request::new(String::new()),
+                    PathAndQuery::from_static("/test.Test/Call"),
+                    crate::codec::ProstCodec::<String, String>::default(),
                 )
-            }
-            _ => {
-                parts.path_and_query = Some(path);
-            }
+                .await
+                .map(Response::into_inner)
         }
 
-        let uri = Uri::from_parts(parts).expect("path_and_query only is valid Uri");
+        for lenient in [false, true] {
+            assert_eq!(call(false, false, lenient).await.unwrap(), "hello");
+            assert_eq!(call(false, true, lenient).await.unwrap(), "hello");
+            assert_eq!(call(true, true, lenient).await.unwrap(), "hello");
+        }
 
-        let mut request = request.into_http(
-            uri,
-            http::Method::POST,
-            http::Version::HTTP_2,
-            SanitizeHeaders::Yes,
-        );
+        let status = call(true, false, false).await.unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+        assert!(status.message().contains("compressed-flag"));
+        assert_eq!(call(true, false, true).await.unwrap(), "hello");
+    }
 
-        // Add the gRPC related HTTP headers
-        request
+    // Answers over HTTP/1.1 with a "hello" message followed by a trailers frame in the body.
+    #[cfg(feature = "prost")]
+    async fn http1_response(
+        trailers: &'static str,
+    ) -> Result<http::Response<BoxBody>, std::convert::Infallible> {
+        use crate::codec::{EncodeBuf, Encoder};
+        use bytes::BufMut;
+
+        let mut message = bytes::BytesMut::new();
+        crate::codec::ProstCodec::<String, String>::default()
+            .encoder()
+            .encode("hello".to_string(), &mut EncodeBuf::new(&mut message))
+            .unwrap();
+
+        let mut body = bytes::BytesMut::new();
+        body.put_u8(0);
+        body.put_u32(message.len() as u32);
+        body.put(message);
+        body.put_u8(0x80);
+        body.put_u32(trailers.len() as u32);
+        body.put(trailers.as_bytes());
+
+        let mut response = http::Response::new(crate::body::from_bytes(body.freeze()));
+        *response.version_mut() = http::Version::HTTP_11;
+        response
             .headers_mut()
-            .insert(TE, HeaderValue::from_static("trailers"));
+            .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        Ok(response)
+    }
 
-        // Set the content type
-        request
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn http1_trailers_are_read_from_the_body() {
+        async fn call(trailers: &'static str) -> Streaming<String> {
+            let svc = tower::service_fn(move |_| http1_response(trailers));
+            Grpc::new(svc)
+                .server_streaming::<String, String, _>(
+                    Request::new(String::new()),
+                    PathAndQuery::from_static("/test.Test/Stream"),
+                    crate::codec::ProstCodec::default(),
+                )
+                .await
+                .unwrap()
+                .into_inner()
+        }
+
+        let mut stream = call("grpc-status: 0\r\nx-extra: value\r\n").await;
+        assert_eq!(stream.message().await.unwrap().unwrap(), "hello");
+        assert_eq!(stream.message().await.unwrap(), None);
+        let trailers = stream.trailers().await.unwrap().unwrap();
+        assert_eq!(trailers.get("x-extra").unwrap(), "value");
+
+        let mut stream = call("grpc-status: 5\r\ngrpc-message: missing\r\n").await;
+        assert_eq!(stream.message().await.unwrap().unwrap(), "hello");
+        let status = stream.message().await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "missing");
+
+        let mut stream = call("grpc-status 0\r\n").await;
+        assert_eq!(stream.message().await.unwrap().unwrap(), "hello");
+        assert_eq!(stream.message().await.unwrap_err().code(), Code::Internal);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn http1_trailers_frame_is_limited() {
+        // Announces a trailers frame of 4GB, which is never sent.
+        let svc = tower::service_fn(|_| async {
+            let body = bytes::Bytes::from_static(b"\x80\xff\xff\xff\xff");
+            let mut response = http::Response::new(crate::body::from_bytes(body));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            Ok::<_, std::convert::Infallible>(response)
+        });
+
+        let mut stream = Grpc::new(svc)
+            .server_streaming::<String, String, _>(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Stream"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+        let status = stream.message().await.unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+    }
+
+    // Answers with an OK status and no message, either in the headers of a trailers-only
+    // response or in trailers following an empty body.
+    #[cfg(feature = "prost")]
+    async fn empty_ok_response(
+        trailers_only: bool,
+    ) -> Result<http::Response<BoxBody>, std::convert::Infallible> {
+        if trailers_only {
+            return Ok(Status::ok("").into_http());
+        }
+
+        let trailers = Status::ok("").to_header_map().unwrap();
+        let frames = [Ok::<_, Status>(http_body::Frame::trailers(trailers))];
+        let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+        let mut response = http::Response::new(crate::body::boxed(body));
+        response
             .headers_mut()
             .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        Ok(response)
+    }
 
-        #[cfg(any(feature = "gzip", feature = "zstd"))]
-        if let Some(encoding) = self.send_compression_encodings {
-            request.headers_mut().insert(
-                crate::codec::compression::ENCODING_HEADER,
-                encoding.into_header_value(),
-            );
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn empty_ok_response_is_an_empty_stream() {
+        for trailers_only in [true, false] {
+            let svc = tower::service_fn(move |_| empty_ok_response(trailers_only));
+            let mut stream = Grpc::new(svc)
+                .server_streaming::<String, String, _>(
+                    Request::new(String::new()),
+                    PathAndQuery::from_static("/test.Test/Stream"),
+                    crate::codec::ProstCodec::default(),
+                )
+                .await
+                .unwrap()
+                .into_inner();
+
+            assert_eq!(stream.message().await.unwrap(), None, "{trailers_only}");
+            assert!(stream.trailers().await.is_ok(), "{trailers_only}");
         }
+    }
 
-        if let Some(header_value) = self
-            .accept_compression_encodings
-            .into_accept_encoding_header_value()
-        {
-            request.headers_mut().insert(
-                crate::codec::compression::ACCEPT_ENCODING_HEADER,
-                header_value,
-            );
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn empty_ok_response_fails_unary_calls() {
+        for trailers_only in [true, false] {
+            let svc = tower::service_fn(move |_| empty_ok_response(trailers_only));
+            let mut grpc = Grpc::new(svc);
+
+            let status = grpc
+                .unary::<String, String, _>(
+                    Request::new(String::new()),
+                    PathAndQuery::from_static("/test.Test/Call"),
+                    crate::codec::ProstCodec::default(),
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Internal, "{trailers_only}");
+            assert_eq!(status.message(), "Missing response message.");
+
+            let status = grpc
+                .client_streaming::<_, String, String, _>(
+                    Request::new(tokio_stream::empty()),
+                    PathAndQuery::from_static("/test.Test/Upload"),
+                    crate::codec::ProstCodec::default(),
+                )
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Internal, "{trailers_only}");
+            assert_eq!(status.message(), "Missing response message.");
         }
+    }
 
-        request
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn fail_fast_rejects_calls_until_ready() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        // Only ready once the flag is set.
+        #[derive(Clone)]
+        struct Gated(Arc<AtomicBool>);
+
+        impl tower_service::Service<http::Request<BoxBody>> for Gated {
+            type Response = http::Response<BoxBody>;
+            type Error = std::convert::Infallible;
+            type Future = std::pin::Pin<
+                Box<dyn future::Future<Output = Result<Self::Response, Self::Error>> + Send>,
+            >;
+
+            fn poll_ready(
+                &mut self,
+                _: &mut std::task::Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                if self.0.load(Ordering::SeqCst) {
+                    Poll::Ready(Ok(()))
+                } else {
+                    Poll::Pending
+                }
+            }
+
+            fn call(&mut self, _: http::Request<BoxBody>) -> Self::Future {
+                assert!(self.0.load(Ordering::SeqCst), "called before being ready");
+                Box::pin(empty_ok_response(true))
+            }
+        }
+
+        let ready = Arc::new(AtomicBool::new(false));
+        let mut grpc = Grpc::new(Gated(ready.clone())).with_fail_fast(true);
+
+        async fn call(grpc: &mut Grpc<Gated>) -> Result<Response<Streaming<String>>, Status> {
+            grpc.ready().await.unwrap();
+            grpc.server_streaming(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Stream"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+        }
+
+        let status = call(&mut grpc).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+
+        ready.store(true, Ordering::SeqCst);
+        call(&mut grpc).await.unwrap();
     }
-}
 
-impl<T: Clone> Clone for Grpc<T> {
-    fn clone(&self) -> Self {
-        Self {
-            inner: self.inner.clone(),
-            config: GrpcConfig {
-                origin: self.config.origin.clone(),
-                send_compression_encodings: self.config.send_compression_encodings,
-                accept_compression_encodings: self.config.accept_compression_encodings,
-                max_encoding_message_size: self.config.max_encoding_message_size,
-                max_decoding_message_size: self.config.max_decoding_message_size,
-            },
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn transport_errors_are_mapped() {
+        #[derive(Debug)]
+        struct PoolExhausted;
+
+        impl fmt::Display for PoolExhausted {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("connection pool exhausted")
+            }
         }
+
+        impl std::error::Error for PoolExhausted {}
+
+        type BoxedService = tower::util::BoxCloneService<
+            http::Request<BoxBody>,
+            http::Response<BoxBody>,
+            PoolExhausted,
+        >;
+
+        async fn call(mut grpc: Grpc<BoxedService>) -> Status {
+            grpc.unary::<String, String, _>(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap_err()
+        }
+
+        let svc = BoxedService::new(tower::service_fn(|_| async { Err(PoolExhausted) }));
+
+        let status = call(Grpc::new(svc.clone())).await;
+        assert_eq!(status.code(), Code::Unknown);
+
+        let grpc = Grpc::new(svc).with_transport_error_mapper(Arc::new(|err| {
+            if err.is::<PoolExhausted>() {
+                Status::resource_exhausted(err.to_string())
+            } else {
+                Status::from_error(err)
+            }
+        }));
+        let status = call(grpc).await;
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "connection pool exhausted");
     }
-}
 
-impl<T: fmt::Debug> fmt::Debug for Grpc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut f = f.debug_struct("Grpc");
+    #[cfg(all(feature = "prost", feature = "gzip"))]
+    #[tokio::test]
+    async fn encode_unary_matches_sent_bytes() {
+        use http_body_util::BodyExt;
 
-        f.field("inner", &self.inner);
+        let sent = Arc::new(std::sync::Mutex::new(Bytes::new()));
+        let svc = {
+            let sent = sent.clone();
+            tower::service_fn(move |request: http::Request<BoxBody>| {
+                let sent = sent.clone();
+                async move {
+                    *sent.lock().unwrap() = request.into_body().collect().await.unwrap().to_bytes();
+                    empty_ok_response(true).await
+                }
+            })
+        };
 
-        f.field("origin", &self.config.origin);
+        // Compressing with a policy only compresses messages larger than a fraction of the limit.
+        let policy = CompressionPolicy::exceeds_limit_fraction(0.5);
+        let cases = [
+            (None, None, 500, false),
+            (Some(CompressionEncoding::Gzip), None, 500, true),
+            (Some(CompressionEncoding::Gzip), Some(policy), 100, false),
+            (Some(CompressionEncoding::Gzip), Some(policy), 600, true),
+        ];
 
-        f.field(
-            "compression_encoding",
-            &self.config.send_compression_encodings,
-        );
+        for (encoding, policy, len, compressed) in cases {
+            let mut grpc = Grpc::new(svc.clone()).max_encoding_message_size(1024);
+            if let Some(encoding) = encoding {
+                grpc = grpc.send_compressed(encoding);
+            }
+            if let Some(policy) = policy {
+                grpc = grpc.compression_policy(policy);
+            }
+            let mut codec = crate::codec::ProstCodec::<String, String>::default();
+            let message = "a".repeat(len);
 
-        f.field(
-            "accept_compression_encodings",
-            &self.config.accept_compression_encodings,
+            let encoded = grpc.encode_unary(&mut codec, &message).unwrap();
+            assert_eq!(encoded[0], u8::from(compressed), "{len}");
+
+            let _ = grpc
+                .unary(
+                    Request::new(message),
+                    PathAndQuery::from_static("/test.Test/Call"),
+                    codec,
+                )
+                .await;
+            assert_eq!(*sent.lock().unwrap(), encoded, "{len}");
+        }
+    }
+
+    #[cfg(all(feature = "prost", feature = "gzip"))]
+    #[tokio::test]
+    async fn preferred_compression_needs_to_be_advertised() {
+        use crate::codec::ProstCodec;
+
+        #[derive(Default)]
+        struct PrefersGzip(ProstCodec<String, String>);
+
+        impl Codec for PrefersGzip {
+            type Encode = String;
+            type Decode = String;
+            type Encoder = <ProstCodec<String, String> as Codec>::Encoder;
+            type Decoder = <ProstCodec<String, String> as Codec>::Decoder;
+
+            fn encoder(&mut self) -> Self::Encoder {
+                self.0.encoder()
+            }
+
+            fn decoder(&mut self) -> Self::Decoder {
+                self.0.decoder()
+            }
+
+            fn preferred_compression(&self) -> Option<CompressionEncoding> {
+                Some(CompressionEncoding::Gzip)
+            }
+        }
+
+        let sent_encoding = Arc::new(std::sync::Mutex::new(None));
+        let svc = {
+            let sent_encoding = sent_encoding.clone();
+            tower::service_fn(move |request: http::Request<BoxBody>| {
+                *sent_encoding.lock().unwrap() = request.headers().get("grpc-encoding").cloned();
+                empty_ok_response(true)
+            })
+        };
+
+        for advertised in [false, true] {
+            let mut grpc = Grpc::new(svc.clone());
+            if advertised {
+                grpc = grpc.accept_compressed(CompressionEncoding::Gzip);
+            }
+            let mut codec = PrefersGzip::default();
+
+            let encoded = grpc.encode_unary(&mut codec, &"hello".to_string()).unwrap();
+            assert_eq!(encoded[0], u8::from(advertised));
+
+            let _ = grpc
+                .unary(
+                    Request::new("hello".to_string()),
+                    PathAndQuery::from_static("/test.Test/Call"),
+                    codec,
+                )
+                .await;
+            let sent_encoding = sent_encoding.lock().unwrap().take();
+            assert_eq!(sent_encoding.is_some(), advertised);
+        }
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn outgoing_trailers_only_end_their_call() {
+        use crate::codec::TERMINAL_METADATA_FLAG;
+        use http_body_util::BodyExt;
+
+        // The prefix of a frame, then the encoding of "a".
+        const HEADER_SIZE: usize = 5;
+        const MESSAGE_SIZE: usize = 3;
+
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let svc = {
+            let sent = sent.clone();
+            tower::service_fn(move |request: http::Request<BoxBody>| {
+                let sent = sent.clone();
+                async move {
+                    let offered = request.headers().contains_key(TERMINAL_METADATA_HEADER);
+                    // Acknowledge the frame before reading the request stream.
+                    let body = futures_util::stream::once(async move {
+                        let body = request.into_body().collect().await.unwrap().to_bytes();
+                        sent.lock().unwrap().push((offered, body));
+                        let trailers = Status::ok("").to_header_map().unwrap();
+                        Ok::<_, Status>(http_body::Frame::trailers(trailers))
+                    });
+                    let body = http_body_util::StreamBody::new(body);
+                    let mut response = http::Response::new(crate::body::boxed(body));
+                    response
+                        .headers_mut()
+                        .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+                    response
+                        .headers_mut()
+                        .insert(TERMINAL_METADATA_HEADER, HeaderValue::from_static("1"));
+                    Ok::<_, std::convert::Infallible>(response)
+                }
+            })
+        };
+
+        let mut grpc = Grpc::new(svc);
+        let path = PathAndQuery::from_static("/test.Test/Call");
+        let trailers = Arc::new(|| {
+            let mut trailers = MetadataMap::new();
+            trailers.insert("x-checksum", MetadataValue::from_static("42"));
+            trailers
+        });
+        let _ = grpc
+            .client_streaming_with_outgoing_trailers::<_, String, String, _>(
+                Request::new(tokio_stream::iter(["a".to_string()])),
+                trailers,
+                path.clone(),
+                crate::codec::ProstCodec::default(),
+            )
+            .await;
+        let _ = grpc
+            .unary::<String, String, _>(
+                Request::new("a".to_string()),
+                path,
+                crate::codec::ProstCodec::default(),
+            )
+            .await;
+
+        let sent = sent.lock().unwrap();
+        let (offered, body) = &sent[0];
+        assert!(offered);
+        let terminal = &body[HEADER_SIZE + MESSAGE_SIZE..];
+        assert_eq!(terminal[0], TERMINAL_METADATA_FLAG);
+        assert_eq!(&terminal[HEADER_SIZE..], b"x-checksum: 42\r\n");
+
+        let (offered, body) = &sent[1];
+        assert!(!offered);
+        assert_eq!(body.len(), HEADER_SIZE + MESSAGE_SIZE);
+    }
+
+    #[cfg(all(feature = "prost", feature = "server", feature = "channel"))]
+    type ReceivedChecksums =
+        Arc<std::sync::Mutex<Vec<Option<MetadataValue<crate::metadata::Ascii>>>>>;
+
+    // A tonic server of client streaming calls, recording the `x-checksum` terminal metadata
+    // each call ends with.
+    #[cfg(all(feature = "prost", feature = "server", feature = "channel"))]
+    fn checksum_server(
+        received: ReceivedChecksums,
+    ) -> tower::util::BoxCloneService<
+        http::Request<BoxBody>,
+        http::Response<BoxBody>,
+        std::convert::Infallible,
+    > {
+        let svc = tower::service_fn(move |request: http::Request<BoxBody>| {
+            let received = received.clone();
+            let handler = tower::service_fn(move |request: Request<Streaming<String>>| {
+                let received = received.clone();
+                async move {
+                    let mut stream = request.into_inner();
+                    while stream.message().await?.is_some() {}
+                    let trailers = stream.trailers().await?;
+                    received
+                        .lock()
+                        .unwrap()
+                        .push(trailers.and_then(|t| t.get("x-checksum").cloned()));
+                    Ok::<_, Status>(Response::new(String::new()))
+                }
+            });
+            let mut server =
+                crate::server::Grpc::new(crate::codec::ProstCodec::<String, String>::default());
+            async move {
+                Ok::<_, std::convert::Infallible>(server.client_streaming(handler, request).await)
+            }
+        });
+        tower::util::BoxCloneService::new(svc)
+    }
+
+    #[cfg(all(feature = "prost", feature = "server", feature = "channel"))]
+    #[tokio::test]
+    async fn terminal_metadata_is_read_by_tonic_servers() {
+        let received = ReceivedChecksums::default();
+        let addr = serve(checksum_server(received.clone())).await;
+        let channel = crate::transport::Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+
+        let mut grpc = Grpc::new(channel);
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-checksum", MetadataValue::from_static("42"));
+        for _ in 0..2 {
+            grpc.ready().await.unwrap();
+            grpc.client_streaming_with_terminal_metadata::<_, String, String, _>(
+                Request::new(tokio_stream::iter(["a".to_string()])),
+                metadata.clone(),
+                PathAndQuery::from_static("/test.Test/Upload"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // The server acknowledges the frame in the response to the first call, the channel
+        // remembers it for the later calls on the same connection.
+        assert_eq!(
+            *received.lock().unwrap(),
+            [None, Some(MetadataValue::from_static("42"))]
         );
+    }
 
-        f.field(
-            "max_decoding_message_size",
-            &self.config.max_decoding_message_size,
+    #[cfg(all(feature = "prost", feature = "server", feature = "channel"))]
+    #[tokio::test]
+    async fn outgoing_trailers_skip_servers_that_did_not_acknowledge_them() {
+        use http_body_util::BodyExt;
+
+        let received = ReceivedChecksums::default();
+        let tonic_server = serve(checksum_server(received.clone())).await;
+
+        // A server that doesn't know terminal metadata frames, recording the flags of the frames
+        // it receives.
+        let flags = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let other_server = {
+            let flags = flags.clone();
+            serve(tower::service_fn(move |request: http::Request<BoxBody>| {
+                let flags = flags.clone();
+                async move {
+                    let body = request.into_body().collect().await.unwrap().to_bytes();
+                    let mut frames = &body[..];
+                    while frames.len() >= 5 {
+                        let len = u32::from_be_bytes(frames[1..5].try_into().unwrap()) as usize;
+                        flags.lock().unwrap().push(frames[0]);
+                        frames = &frames[5 + len..];
+                    }
+
+                    // An empty message, then an OK status.
+                    let frames = [
+                        Ok::<_, Status>(http_body::Frame::data(Bytes::from_static(&[0; 5]))),
+                        Ok(http_body::Frame::trailers(
+                            Status::ok("").to_header_map().unwrap(),
+                        )),
+                    ];
+                    let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+                    let mut response = http::Response::new(crate::body::boxed(body));
+                    response
+                        .headers_mut()
+                        .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+                    Ok::<_, std::convert::Infallible>(response)
+                }
+            }))
+            .await
+        };
+
+        let mut channels = Vec::new();
+        for addr in [tonic_server, other_server] {
+            let channel = crate::transport::Endpoint::from_shared(format!("http://{addr}"))
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            channels.push(channel);
+        }
+        // Alternate between the servers, as a balanced channel may.
+        let next = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let svc = tower::steer::Steer::new(channels, move |_: &http::Request<BoxBody>, _: &[_]| {
+            next.fetch_add(1, std::sync::atomic::Ordering::SeqCst) % 2
+        });
+
+        let mut grpc = Grpc::new(svc);
+        let trailers = Arc::new(|| {
+            let mut trailers = MetadataMap::new();
+            trailers.insert("x-checksum", MetadataValue::from_static("42"));
+            trailers
+        });
+        for _ in 0..4 {
+            grpc.ready().await.unwrap();
+            grpc.client_streaming_with_outgoing_trailers::<_, String, String, _>(
+                Request::new(tokio_stream::iter(["a".to_string()])),
+                trailers.clone(),
+                PathAndQuery::from_static("/test.Test/Upload"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // The tonic server acknowledged the first call, which doesn't let the other one receive
+        // the frame.
+        assert_eq!(
+            *received.lock().unwrap(),
+            [None, Some(MetadataValue::from_static("42"))]
         );
+        // Only the message frames, none flagged with `TERMINAL_METADATA_FLAG`.
+        assert_eq!(*flags.lock().unwrap(), [0, 0]);
+    }
 
-        f.field(
-            "max_encoding_message_size",
-            &self.config.max_encoding_message_size,
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn initial_metadata_is_available_before_messages() {
+        // Sends its headers right away but no message, as a server that delays its first one.
+        let svc = tower::service_fn(|_| async {
+            let body = http_body_util::StreamBody::new(tokio_stream::pending::<
+                Result<http_body::Frame<Bytes>, Status>,
+            >());
+            let mut response = http::Response::new(crate::body::boxed(body));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            response
+                .headers_mut()
+                .insert("x-shard", HeaderValue::from_static("7"));
+            Ok::<_, std::convert::Infallible>(response)
+        });
+
+        let response = Grpc::new(svc)
+            .server_streaming::<String, String, _>(
+                Request::new(String::new()),
+                PathAndQuery::from_static("/test.Test/Stream"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.metadata().get("x-shard").unwrap(), "7");
+
+        let mut stream = response.into_inner();
+        let next = tokio::time::timeout(Duration::from_millis(10), stream.message()).await;
+        assert!(next.is_err(), "no message has been sent");
+    }
+
+    // Echoes the request body, or fails with the status the request body ended with.
+    #[cfg(feature = "prost")]
+    async fn echo(
+        request: http::Request<BoxBody>,
+    ) -> Result<http::Response<BoxBody>, std::convert::Infallible> {
+        match http_body_util::BodyExt::collect(request.into_body()).await {
+            Ok(body) => {
+                let mut response = http::Response::new(crate::body::boxed(body));
+                response
+                    .headers_mut()
+                    .insert(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+                Ok(response)
+            }
+            Err(status) => Ok(status.into_http()),
+        }
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn default_limits_apply_when_unset() {
+        let limits = DefaultLimits {
+            max_decoding_message_size: 8,
+            max_encoding_message_size: 8,
+        };
+        let call = |mut grpc: Grpc<_>| async move {
+            grpc.unary::<String, String, _>(
+                Request::new("longer than the limits".to_string()),
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .map_or_else(|status| status.code(), |_| Code::Ok)
+        };
+        let svc = tower::service_fn(echo);
+
+        let grpc = Grpc::new(svc).with_default_limits(limits);
+        assert_eq!(call(grpc.clone()).await, Code::OutOfRange);
+
+        let grpc = grpc.max_encoding_message_size(1024);
+        assert_eq!(call(grpc.clone()).await, Code::OutOfRange);
+
+        let grpc = grpc.max_decoding_message_size(1024);
+        assert_eq!(call(grpc).await, Code::Ok);
+    }
+
+    // Serves `svc` as `test.Test` over TCP.
+    #[cfg(all(feature = "server", feature = "channel"))]
+    async fn serve<S>(svc: S) -> std::net::SocketAddr
+    where
+        S: tower::Service<
+                http::Request<BoxBody>,
+                Response = http::Response<BoxBody>,
+                Error = std::convert::Infallible,
+            > + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        #[derive(Clone)]
+        struct TestService<S>(S);
+
+        impl<S> tower::Service<http::Request<BoxBody>> for TestService<S>
+        where
+            S: tower::Service<http::Request<BoxBody>>,
+        {
+            type Response = S::Response;
+            type Error = S::Error;
+            type Future = S::Future;
+
+            fn poll_ready(
+                &mut self,
+                cx: &mut std::task::Context<'_>,
+            ) -> Poll<Result<(), Self::Error>> {
+                self.0.poll_ready(cx)
+            }
+
+            fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+                self.0.call(request)
+            }
+        }
+
+        impl<S> crate::server::NamedService for TestService<S> {
+            const NAME: &'static str = "test.Test";
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            crate::transport::Server::builder()
+                .add_service(TestService(svc))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
         );
+        addr
+    }
 
-        f.finish()
+    #[cfg(all(feature = "prost", feature = "server", feature = "channel"))]
+    #[tokio::test]
+    async fn endpoint_limits_apply_to_clients_on_the_channel() {
+        let addr = serve(tower::service_fn(echo)).await;
+        let call = |mut grpc: Grpc<crate::transport::Channel>| async move {
+            grpc.ready().await.unwrap();
+            grpc.unary::<String, String, _>(
+                Request::new("longer than the limits".to_string()),
+                PathAndQuery::from_static("/test.Test/Call"),
+                crate::codec::ProstCodec::default(),
+            )
+            .await
+            .map_or_else(|status| status.code(), |_| Code::Ok)
+        };
+        let endpoint = crate::transport::Endpoint::from_shared(format!("http://{addr}")).unwrap();
+
+        // As generated clients are created with `FooClient::new(channel)`.
+        let channel = endpoint.clone().max_decoding_message_size(8).connect_lazy();
+        assert_eq!(call(Grpc::new(channel.clone())).await, Code::OutOfRange);
+        let grpc = Grpc::new(channel).max_decoding_message_size(1024);
+        assert_eq!(call(grpc).await, Code::Ok);
+
+        // The transport resets the stream when the request body fails, see
+        // https://github.com/hyperium/tonic/issues/1334
+        let channel = endpoint.clone().max_encoding_message_size(8).connect_lazy();
+        assert_eq!(call(Grpc::new(channel.clone())).await, Code::Internal);
+        let grpc = Grpc::new(channel).with_default_limits(DefaultLimits::default());
+        assert_eq!(call(grpc).await, Code::Ok);
+
+        assert_eq!(call(Grpc::new(endpoint.connect_lazy())).await, Code::Ok);
     }
 }
@@ -0,0 +1,40 @@
+use crate::{Request, Status};
+use std::{future::Future, pin::Pin};
+
+/// The future returned by [`AsyncInterceptor::intercept`].
+pub type InterceptFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Status>> + Send + 'a>>;
+
+/// An asynchronous interceptor run by a [`Grpc`] client before each call.
+///
+/// Installed with [`Grpc::with_async_interceptor`]. Like an [`Interceptor`], it can inspect and
+/// modify the metadata and extensions of the request, or cancel the call with a [`Status`], which
+/// is then returned without reaching the backend. Unlike an [`Interceptor`], it can await other
+/// work first, for example fetching a token from a cache that may have to hit the network.
+///
+/// The request message isn't available to the interceptor, it gets a [`Request<()>`] carrying
+/// the metadata and extensions of the call instead. The interceptor is shared by every clone of
+/// the client.
+///
+/// ```rust
+/// # use tonic::{client::{AsyncInterceptor, InterceptFuture}, Request};
+/// struct AddRequestId;
+///
+/// impl AsyncInterceptor for AddRequestId {
+///     fn intercept<'a>(&'a self, request: &'a mut Request<()>) -> InterceptFuture<'a> {
+///         Box::pin(async move {
+///             let id = next_request_id().await;
+///             request.metadata_mut().insert("x-request-id", id.into());
+///             Ok(())
+///         })
+///     }
+/// }
+/// # async fn next_request_id() -> u64 { 0 }
+/// ```
+///
+/// [`Grpc`]: super::Grpc
+/// [`Grpc::with_async_interceptor`]: super::Grpc::with_async_interceptor
+/// [`Interceptor`]: crate::service::Interceptor
+pub trait AsyncInterceptor: Send + Sync + 'static {
+    /// Intercept a request before it is sent, optionally cancelling it.
+    fn intercept<'a>(&'a self, request: &'a mut Request<()>) -> InterceptFuture<'a>;
+}
@@ -15,9 +15,51 @@
 //! which is cheap as all client instances will share the same channel for
 //! communication. For more details, see
 //! [transport::Channel](../transport/struct.Channel.html#multiplexing-requests).
+//!
+//! ## Trace context propagation
+//!
+//! tonic doesn't depend on OpenTelemetry, whose crates aren't stable yet and would tie tonic's
+//! releases to theirs. The trace context and baggage of a call can be injected into its metadata
+//! with an [`AsyncInterceptor`], by handing an injector writing to
+//! [`Request::metadata_mut`](crate::Request::metadata_mut) to
+//! `opentelemetry::global::get_text_map_propagator`. On the server, an
+//! [`Interceptor`](crate::service::Interceptor) extracts it the same way.
+//!
+//! ## Metrics
+//!
+//! tonic doesn't record metrics itself, for the same reason it doesn't depend on OpenTelemetry.
+//! The [`LoggingLayer`](crate::service::LoggingLayer) emits an event with the method, status
+//! code, duration and message counts and sizes of every call, client or server side, which a
+//! `tracing` subscriber can turn into counters and histograms for the exporter of your choice.
 
+#[cfg(feature = "channel")]
+mod call;
+mod circuit_breaker;
+#[cfg(feature = "test-util")]
+mod fault;
 mod grpc;
+mod interceptor;
+mod pipeline;
+mod request_id;
+mod resume;
 mod service;
+mod timing;
+mod token_source;
+#[cfg(feature = "channel")]
+mod upload;
 
-pub use self::grpc::Grpc;
-pub use self::service::GrpcService;
+#[cfg(feature = "channel")]
+pub use self::call::{Call, RequestSink};
+pub use self::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, PerMethodCircuitBreaker, SlidingWindowCircuitBreaker,
+};
+#[cfg(feature = "test-util")]
+pub use self::fault::FaultConfig;
+pub use self::grpc::{Grpc, GrpcConfig};
+pub use self::interceptor::{AsyncInterceptor, InterceptFuture};
+pub use self::pipeline::Pipeline;
+pub use self::request_id::RequestId;
+pub use self::resume::ResumePolicy;
+pub use self::service::{GrpcService, MapResponse, MapResponseFuture};
+pub use self::timing::CallTiming;
+pub use self::token_source::{CachingTokenSource, StaticTokenSource, TokenFuture, TokenSource};
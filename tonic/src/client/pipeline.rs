@@ -0,0 +1,51 @@
+use crate::{Response, Status};
+use futures_util::stream::FuturesOrdered;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio_stream::Stream;
+
+pub(crate) type PipelinedFuture<M> =
+    Pin<Box<dyn Future<Output = Result<Response<M>, Status>> + Send + 'static>>;
+
+/// The responses of pipelined unary calls, returned by [`Grpc::pipeline`].
+///
+/// All the calls make progress whenever the stream is polled, and their responses are yielded in
+/// the order of the requests. A failed call yields its status without affecting the others.
+///
+/// [`Grpc::pipeline`]: super::Grpc::pipeline
+pub struct Pipeline<M> {
+    // Only the calls that have been woken are polled again.
+    calls: FuturesOrdered<PipelinedFuture<M>>,
+}
+
+impl<M> Pipeline<M> {
+    pub(crate) fn new(calls: impl IntoIterator<Item = PipelinedFuture<M>>) -> Self {
+        Self {
+            calls: calls.into_iter().collect(),
+        }
+    }
+}
+
+impl<M> Stream for Pipeline<M> {
+    type Item = Result<Response<M>, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.calls).poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.calls.len(), Some(self.calls.len()))
+    }
+}
+
+impl<M> fmt::Debug for Pipeline<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("remaining", &self.calls.len())
+            .finish()
+    }
+}
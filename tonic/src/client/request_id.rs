@@ -0,0 +1,87 @@
+use http::HeaderValue;
+use std::{
+    fmt,
+    hash::{BuildHasher, RandomState},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The ID generated for a call by [`Grpc::with_auto_request_id`].
+///
+/// Found in the extensions of the [`Response`] of calls for which an ID was generated. Calls
+/// that already carried the header keep their own value and don't get a `RequestId`.
+///
+/// IDs are random [version 4 UUIDs] in their hyphenated form.
+///
+/// ```rust
+/// # use tonic::client::RequestId;
+/// # fn ex(response: tonic::Response<()>) {
+/// if let Some(id) = response.extensions().get::<RequestId>() {
+///     println!("request id: {id}");
+/// }
+/// # }
+/// ```
+///
+/// [`Grpc::with_auto_request_id`]: super::Grpc::with_auto_request_id
+/// [`Response`]: crate::Response
+/// [version 4 UUIDs]: https://www.rfc-editor.org/rfc/rfc9562#name-uuid-version-4
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Generate a new random ID.
+    pub(crate) fn generate() -> Self {
+        // Hashing a counter with randomly keyed SipHash is enough to get unpredictable,
+        // unique IDs without pulling in a random number generator.
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let state = RandomState::new();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let high = state.hash_one((count, 0u8));
+        let low = state.hash_one((count, 1u8));
+
+        let bits = (u128::from(high) << 64 | u128::from(low)) & !(0xf000u128 << 64 | 0xc000 << 48)
+            | (0x4000u128 << 64 | 0x8000 << 48);
+        let hex = format!("{bits:032x}");
+        Self(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        ))
+    }
+
+    pub(crate) fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.0).expect("UUIDs are valid header values")
+    }
+
+    /// The ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_unique_v4_uuids() {
+        let a = RequestId::generate();
+        let b = RequestId::generate();
+        assert_ne!(a, b);
+
+        for id in [a, b] {
+            let groups: Vec<_> = id.as_str().split('-').map(str::len).collect();
+            assert_eq!(groups, [8, 4, 4, 4, 12]);
+            assert_eq!(&id.as_str()[14..15], "4");
+            assert!(matches!(&id.as_str()[19..20], "8" | "9" | "a" | "b"));
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{codec::Streaming, metadata::MetadataMap, Code, Extensions, Request, Response, Status};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio_stream::Stream;
+
+/// How a server streaming call resumes after a transient failure, see
+/// [`Grpc::server_streaming_with_resume`].
+///
+/// `extract` reads the resume token carried by a response message, if any. When the stream fails
+/// with one of the retryable status codes, the call is issued again with the original request,
+/// into which `inject` writes the last token received, and the new response messages continue
+/// the same stream.
+///
+/// Delivery is at least once: messages the server sent after the last token was extracted are
+/// sent again by the resumed call, and if no token was received at all, the call starts over.
+/// Deduplicating messages is up to the caller.
+///
+/// [`Grpc::server_streaming_with_resume`]: super::Grpc::server_streaming_with_resume
+pub struct ResumePolicy<E, I> {
+    extract: E,
+    inject: I,
+    max_resumes: usize,
+    retryable_status_codes: Vec<Code>,
+}
+
+impl<E, I> ResumePolicy<E, I> {
+    /// Create a policy with the provided token accessors.
+    ///
+    /// The stream is resumed at most 3 times, and only after an `Unavailable` status.
+    pub fn new<M1, M2, K>(extract: E, inject: I) -> Self
+    where
+        E: FnMut(&M2) -> Option<K>,
+        I: FnMut(&mut Request<M1>, K),
+    {
+        Self {
+            extract,
+            inject,
+            max_resumes: 3,
+            retryable_status_codes: vec![Code::Unavailable],
+        }
+    }
+
+    /// Set how many times the stream is resumed at most over its lifetime.
+    pub fn max_resumes(mut self, max_resumes: usize) -> Self {
+        self.max_resumes = max_resumes;
+        self
+    }
+
+    /// Set the status codes after which the stream is resumed.
+    pub fn retryable_status_codes(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.retryable_status_codes = codes.into_iter().collect();
+        self
+    }
+}
+
+impl<E, I> fmt::Debug for ResumePolicy<E, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumePolicy")
+            .field("max_resumes", &self.max_resumes)
+            .field("retryable_status_codes", &self.retryable_status_codes)
+            .finish_non_exhaustive()
+    }
+}
+
+pub(crate) type CallFuture<M2> =
+    Pin<Box<dyn Future<Output = Result<Response<Streaming<M2>>, Status>> + Send + 'static>>;
+
+enum State<M2> {
+    Streaming(Box<Streaming<M2>>),
+    Resuming(CallFuture<M2>),
+    Done,
+}
+
+/// The stream returned by [`Grpc::server_streaming_with_resume`].
+///
+/// `call` issues the call again with the provided request.
+///
+/// [`Grpc::server_streaming_with_resume`]: super::Grpc::server_streaming_with_resume
+pub(crate) struct Resumable<M1, M2, K, E, I, F> {
+    metadata: MetadataMap,
+    extensions: Extensions,
+    message: M1,
+    policy: ResumePolicy<E, I>,
+    call: F,
+    token: Option<K>,
+    resumes: usize,
+    state: State<M2>,
+}
+
+impl<M1, M2, K, E, I, F> Unpin for Resumable<M1, M2, K, E, I, F> {}
+
+impl<M1, M2, K, E, I, F> Resumable<M1, M2, K, E, I, F>
+where
+    M1: Clone,
+    K: Clone,
+    I: FnMut(&mut Request<M1>, K),
+    F: FnMut(Request<M1>) -> CallFuture<M2>,
+{
+    pub(crate) fn new(
+        request: Request<M1>,
+        policy: ResumePolicy<E, I>,
+        call: F,
+        streaming: Streaming<M2>,
+    ) -> Self {
+        let (metadata, extensions, message) = request.into_parts();
+
+        Self {
+            metadata,
+            extensions,
+            message,
+            policy,
+            call,
+            token: None,
+            resumes: 0,
+            state: State::Streaming(Box::new(streaming)),
+        }
+    }
+
+    /// Resume the call if `status` allows it, otherwise end the stream.
+    fn resume_or_fail(&mut self, status: Status) -> Option<Status> {
+        let retryable = self.policy.retryable_status_codes.contains(&status.code());
+        if !retryable || self.resumes >= self.policy.max_resumes {
+            self.state = State::Done;
+            return Some(status);
+        }
+
+        self.resumes += 1;
+        tracing::debug!(resumes = self.resumes, code = ?status.code(), "resuming stream");
+
+        let mut request = Request::from_parts(
+            self.metadata.clone(),
+            self.extensions.clone(),
+            self.message.clone(),
+        );
+        if let Some(token) = self.token.clone() {
+            (self.policy.inject)(&mut request, token);
+        }
+
+        self.state = State::Resuming((self.call)(request));
+        None
+    }
+}
+
+impl<M1, M2, K, E, I, F> Stream for Resumable<M1, M2, K, E, I, F>
+where
+    M1: Clone,
+    K: Clone,
+    E: FnMut(&M2) -> Option<K>,
+    I: FnMut(&mut Request<M1>, K),
+    F: FnMut(Request<M1>) -> CallFuture<M2>,
+{
+    type Item = Result<M2, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        loop {
+            let status = match &mut this.state {
+                State::Streaming(streaming) => {
+                    match ready!(Pin::new(&mut **streaming).poll_next(cx)) {
+                        Some(Ok(message)) => {
+                            if let Some(token) = (this.policy.extract)(&message) {
+                                this.token = Some(token);
+                            }
+                            return Poll::Ready(Some(Ok(message)));
+                        }
+                        Some(Err(status)) => status,
+                        None => {
+                            this.state = State::Done;
+                            return Poll::Ready(None);
+                        }
+                    }
+                }
+                State::Resuming(call) => match ready!(call.as_mut().poll(cx)) {
+                    Ok(response) => {
+                        this.state = State::Streaming(Box::new(response.into_inner()));
+                        continue;
+                    }
+                    Err(status) => status,
+                },
+                State::Done => return Poll::Ready(None),
+            };
+
+            if let Some(status) = this.resume_or_fail(status) {
+                return Poll::Ready(Some(Err(status)));
+            }
+        }
+    }
+}
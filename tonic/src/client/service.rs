@@ -1,6 +1,9 @@
 use http_body::Body;
+use pin_project::pin_project;
+use std::fmt;
 use std::future::Future;
-use std::task::{Context, Poll};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
 use tower_service::Service;
 
 /// Definition of the gRPC trait alias for [`tower_service`].
@@ -27,6 +30,20 @@ pub trait GrpcService<ReqBody> {
     ///
     /// Reference [`Service::call`].
     fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future;
+
+    /// Transform every response of this service with `f` before it reaches [`Grpc`].
+    ///
+    /// This is the gRPC counterpart of `tower::ServiceExt::map_response`, and is mostly useful
+    /// to adapt the response body type without writing a wrapper service by hand.
+    ///
+    /// [`Grpc`]: ../client/struct.Grpc.html
+    fn map_response<F, B>(self, f: F) -> MapResponse<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(http::Response<Self::ResponseBody>) -> http::Response<B> + Clone,
+    {
+        MapResponse { inner: self, f }
+    }
 }
 
 impl<T, ReqBody, ResBody> GrpcService<ReqBody> for T
@@ -48,3 +65,101 @@ where
         Service::call(self, request)
     }
 }
+
+/// Service returned by [`GrpcService::map_response`].
+#[derive(Clone)]
+pub struct MapResponse<S, F> {
+    inner: S,
+    f: F,
+}
+
+impl<S, F, ReqBody, B> Service<http::Request<ReqBody>> for MapResponse<S, F>
+where
+    S: GrpcService<ReqBody>,
+    F: FnMut(http::Response<S::ResponseBody>) -> http::Response<B> + Clone,
+{
+    type Response = http::Response<B>;
+    type Error = S::Error;
+    type Future = MapResponseFuture<S::Future, F>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        MapResponseFuture {
+            inner: self.inner.call(request),
+            f: Some(self.f.clone()),
+        }
+    }
+}
+
+impl<S: fmt::Debug, F> fmt::Debug for MapResponse<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponse")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// Response future of [`MapResponse`].
+#[pin_project]
+pub struct MapResponseFuture<Fut, F> {
+    #[pin]
+    inner: Fut,
+    f: Option<F>,
+}
+
+impl<Fut, F, ResBody, B, E> Future for MapResponseFuture<Fut, F>
+where
+    Fut: Future<Output = Result<http::Response<ResBody>, E>>,
+    F: FnMut(http::Response<ResBody>) -> http::Response<B>,
+{
+    type Output = Result<http::Response<B>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.inner.poll(cx))?;
+        let mut f = this
+            .f
+            .take()
+            .expect("MapResponseFuture polled after completion");
+        Poll::Ready(Ok(f(response)))
+    }
+}
+
+impl<Fut, F> fmt::Debug for MapResponseFuture<Fut, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponseFuture").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::{empty_body, BoxBody};
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn map_response_transforms_response() {
+        let svc = tower::service_fn(|_: http::Request<BoxBody>| async {
+            Ok::<_, Infallible>(http::Response::new(empty_body()))
+        });
+
+        let mut svc = svc.map_response(|mut response: http::Response<BoxBody>| {
+            response
+                .headers_mut()
+                .insert("x-mapped", http::HeaderValue::from_static("yes"));
+            response
+        });
+
+        std::future::poll_fn(|cx| GrpcService::poll_ready(&mut svc, cx))
+            .await
+            .unwrap();
+        let response = GrpcService::call(&mut svc, http::Request::new(empty_body()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()["x-mapped"], "yes");
+    }
+}
@@ -0,0 +1,58 @@
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// Timing information for a streaming call.
+///
+/// Returned by [`Grpc::streaming_timed`] and [`Grpc::server_streaming_timed`]. The handle is
+/// cheap to clone and is updated as the response [`Streaming`] is consumed. All durations are
+/// measured from just before the request is handed to the inner service, so time spent waiting
+/// for [`Grpc::ready`] is not included.
+///
+/// [`Grpc::streaming_timed`]: super::Grpc::streaming_timed
+/// [`Grpc::server_streaming_timed`]: super::Grpc::server_streaming_timed
+/// [`Grpc::ready`]: super::Grpc::ready
+/// [`Streaming`]: crate::codec::Streaming
+#[derive(Clone, Debug)]
+pub struct CallTiming {
+    start: Instant,
+    marks: Arc<Marks>,
+}
+
+#[derive(Debug, Default)]
+struct Marks {
+    first_message: OnceLock<Duration>,
+    total: OnceLock<Duration>,
+}
+
+impl CallTiming {
+    pub(crate) fn new(start: Instant) -> Self {
+        Self {
+            start,
+            marks: Arc::default(),
+        }
+    }
+
+    pub(crate) fn record_message(&self) {
+        let _ = self.marks.first_message.set(self.start.elapsed());
+    }
+
+    pub(crate) fn record_end(&self) {
+        let _ = self.marks.total.set(self.start.elapsed());
+    }
+
+    /// The time until the first response message was received.
+    ///
+    /// Returns `None` until a message has been yielded by the response stream.
+    pub fn time_to_first_message(&self) -> Option<Duration> {
+        self.marks.first_message.get().copied()
+    }
+
+    /// The time until the response stream completed, either successfully or with an error.
+    ///
+    /// Returns `None` until the response stream has been fully consumed.
+    pub fn total_duration(&self) -> Option<Duration> {
+        self.marks.total.get().copied()
+    }
+}
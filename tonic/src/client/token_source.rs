@@ -0,0 +1,191 @@
+use crate::Status;
+use http::HeaderValue;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The future returned by [`TokenSource::token`].
+pub type TokenFuture<'a> = Pin<Box<dyn Future<Output = Result<HeaderValue, Status>> + Send + 'a>>;
+
+/// Provides the bearer tokens that authenticate the calls of a [`Grpc`] client.
+///
+/// Installed with [`Grpc::with_token_source`]. Before each call the client awaits
+/// [`TokenSource::token`] and sends the returned token in an `authorization: Bearer <token>`
+/// header, replacing any `authorization` header already set on the request. If the source fails,
+/// the call fails with the returned status without reaching the backend.
+///
+/// The source is shared by every clone of the client. See [`StaticTokenSource`] and
+/// [`CachingTokenSource`] for the implementations shipped with tonic.
+///
+/// ```rust
+/// # use tonic::client::{TokenFuture, TokenSource};
+/// # use http::HeaderValue;
+/// struct FromEnv;
+///
+/// impl TokenSource for FromEnv {
+///     fn token(&self) -> TokenFuture<'_> {
+///         Box::pin(async {
+///             let token = std::env::var("API_TOKEN")
+///                 .map_err(|_| tonic::Status::unauthenticated("API_TOKEN is not set"))?;
+///             HeaderValue::try_from(token)
+///                 .map_err(|_| tonic::Status::unauthenticated("API_TOKEN is not a valid token"))
+///         })
+///     }
+/// }
+/// ```
+///
+/// [`Grpc`]: super::Grpc
+/// [`Grpc::with_token_source`]: super::Grpc::with_token_source
+pub trait TokenSource: Send + Sync + 'static {
+    /// Returns the token to send with the next call, without the `Bearer` prefix.
+    fn token(&self) -> TokenFuture<'_>;
+}
+
+/// Builds the `authorization` header value for `token`.
+pub(crate) fn bearer(token: &HeaderValue) -> HeaderValue {
+    let mut value = b"Bearer ".to_vec();
+    value.extend_from_slice(token.as_bytes());
+
+    let mut value = HeaderValue::from_bytes(&value).expect("token is a valid header value");
+    value.set_sensitive(true);
+    value
+}
+
+/// A [`TokenSource`] that always returns the same token.
+#[derive(Clone)]
+pub struct StaticTokenSource {
+    token: HeaderValue,
+}
+
+impl StaticTokenSource {
+    /// Creates a source returning `token`.
+    pub fn new(token: HeaderValue) -> Self {
+        Self { token }
+    }
+}
+
+impl TokenSource for StaticTokenSource {
+    fn token(&self) -> TokenFuture<'_> {
+        Box::pin(std::future::ready(Ok(self.token.clone())))
+    }
+}
+
+impl fmt::Debug for StaticTokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticTokenSource").finish_non_exhaustive()
+    }
+}
+
+/// A [`TokenSource`] that reuses the tokens of another source for a fixed duration.
+///
+/// The wrapped source is only asked for a new token once the cached one is older than `ttl`, or
+/// after [`CachingTokenSource::invalidate`] was called, for example because the server rejected
+/// the token. Calls that miss the cache at the same time each ask the wrapped source. Failures
+/// aren't cached.
+pub struct CachingTokenSource<S> {
+    source: S,
+    ttl: Duration,
+    cached: Mutex<Option<(HeaderValue, Instant)>>,
+}
+
+impl<S> CachingTokenSource<S> {
+    /// Creates a source caching the tokens of `source` for `ttl`.
+    pub fn new(source: S, ttl: Duration) -> Self {
+        Self {
+            source,
+            ttl,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Drops the cached token, so that the next call fetches a new one.
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+
+    fn cached(&self) -> Option<HeaderValue> {
+        match &*self.cached.lock().unwrap() {
+            Some((token, fetched_at)) if fetched_at.elapsed() < self.ttl => Some(token.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<S: TokenSource> TokenSource for CachingTokenSource<S> {
+    fn token(&self) -> TokenFuture<'_> {
+        Box::pin(async move {
+            if let Some(token) = self.cached() {
+                return Ok(token);
+            }
+
+            let token = self.source.token().await?;
+            *self.cached.lock().unwrap() = Some((token.clone(), Instant::now()));
+            Ok(token)
+        })
+    }
+}
+
+impl<S> fmt::Debug for CachingTokenSource<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachingTokenSource")
+            .field("ttl", &self.ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingSource {
+        calls: AtomicUsize,
+    }
+
+    impl TokenSource for CountingSource {
+        fn token(&self) -> TokenFuture<'_> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Ok(HeaderValue::from(call)) })
+        }
+    }
+
+    #[test]
+    fn bearer_prefixes_token() {
+        let value = bearer(&HeaderValue::from_static("abc"));
+
+        assert_eq!(value, "Bearer abc");
+        assert!(value.is_sensitive());
+    }
+
+    #[tokio::test]
+    async fn static_source_returns_token() {
+        let source = StaticTokenSource::new(HeaderValue::from_static("abc"));
+
+        assert_eq!(source.token().await.unwrap(), "abc");
+        assert_eq!(source.token().await.unwrap(), "abc");
+    }
+
+    #[tokio::test]
+    async fn caching_source_reuses_token() {
+        let source = CachingTokenSource::new(CountingSource::default(), Duration::from_secs(60));
+
+        assert_eq!(source.token().await.unwrap(), "0");
+        assert_eq!(source.token().await.unwrap(), "0");
+
+        source.invalidate();
+        assert_eq!(source.token().await.unwrap(), "1");
+    }
+
+    #[tokio::test]
+    async fn caching_source_expires_token() {
+        let source = CachingTokenSource::new(CountingSource::default(), Duration::ZERO);
+
+        assert_eq!(source.token().await.unwrap(), "0");
+        assert_eq!(source.token().await.unwrap(), "1");
+    }
+}
@@ -0,0 +1,139 @@
+use crate::Status;
+use bytes::{Bytes, BytesMut};
+use pin_project::pin_project;
+use std::{
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio_stream::Stream;
+
+/// Reads `chunk_size` pieces from a reader and wraps each of them into a message.
+///
+/// A read error is recorded in `error` and yielded as a status, which aborts the request stream.
+#[pin_project]
+pub(crate) struct ReadChunks<R, F> {
+    #[pin]
+    reader: R,
+    wrap: F,
+    chunk_size: usize,
+    buf: BytesMut,
+    error: Arc<OnceLock<Status>>,
+    done: bool,
+}
+
+impl<R, F> ReadChunks<R, F> {
+    pub(crate) fn new(reader: R, chunk_size: usize, wrap: F, error: Arc<OnceLock<Status>>) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be greater than zero");
+
+        Self {
+            reader,
+            wrap,
+            chunk_size,
+            buf: BytesMut::with_capacity(chunk_size),
+            error,
+            done: false,
+        }
+    }
+}
+
+impl<R, F, M> Stream for ReadChunks<R, F>
+where
+    R: AsyncRead,
+    F: FnMut(Bytes) -> M,
+{
+    type Item = Result<M, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        while this.buf.len() < *this.chunk_size {
+            let filled = this.buf.len();
+            this.buf.resize(*this.chunk_size, 0);
+
+            let mut read_buf = ReadBuf::new(&mut this.buf[filled..]);
+            let result = this.reader.as_mut().poll_read(cx, &mut read_buf);
+            let n = read_buf.filled().len();
+            this.buf.truncate(filled + n);
+
+            match ready!(result) {
+                Ok(()) if n == 0 => {
+                    *this.done = true;
+                    break;
+                }
+                Ok(()) => {}
+                Err(err) => {
+                    *this.done = true;
+                    let status = Status::internal(format!("Failed to read upload: {err}"));
+                    let _ = this.error.set(status.clone());
+                    return Poll::Ready(Some(Err(status)));
+                }
+            }
+        }
+
+        if this.buf.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        let chunk = this.buf.split().freeze();
+        this.buf.reserve(*this.chunk_size);
+        Poll::Ready(Some(Ok((this.wrap)(chunk))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use tokio::io::AsyncReadExt;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn splits_into_chunks() {
+        let data: &[u8] = b"abcdefghij";
+        let error = Arc::default();
+        let chunks: Vec<_> = ReadChunks::new(data, 4, |chunk| chunk, Arc::clone(&error))
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(chunks, ["abcd", "efgh", "ij"]);
+        assert!(error.get().is_none());
+    }
+
+    #[tokio::test]
+    async fn read_error_aborts() {
+        struct Failing;
+
+        impl AsyncRead for Failing {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                _: &mut ReadBuf<'_>,
+            ) -> Poll<io::Result<()>> {
+                Poll::Ready(Err(io::Error::other("disk on fire")))
+            }
+        }
+
+        let reader = (&b"abcdef"[..]).chain(Failing);
+        let error = Arc::default();
+        let mut stream = std::pin::pin!(ReadChunks::new(
+            reader,
+            4,
+            |chunk| chunk,
+            Arc::clone(&error)
+        ));
+
+        assert_eq!(stream.next().await.unwrap().unwrap(), "abcd");
+        let status = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), crate::Code::Internal);
+        assert_eq!(status.message(), "Failed to read upload: disk on fire");
+        assert_eq!(error.get().unwrap().message(), status.message());
+        assert!(stream.next().await.is_none());
+    }
+}
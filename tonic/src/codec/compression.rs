@@ -1,7 +1,7 @@
 use crate::{metadata::MetadataValue, Status};
 use bytes::{Buf, BufMut, BytesMut};
 #[cfg(feature = "gzip")]
-use flate2::read::{GzDecoder, GzEncoder};
+use flate2::{bufread::GzDecoder, read::GzEncoder};
 use std::fmt;
 #[cfg(feature = "zstd")]
 use zstd::stream::read::{Decoder, Encoder};
@@ -97,6 +97,21 @@ impl CompressionEncoding {
         CompressionEncoding::Zstd,
     ];
 
+    /// The encodings compiled into this build of tonic, in order of preference.
+    ///
+    /// Each encoding is behind the cargo feature of the same name, `gzip` or `zstd`. Other
+    /// encodings, such as `deflate` or brotli, are not supported.
+    ///
+    /// ```rust
+    /// # use tonic::codec::CompressionEncoding;
+    /// for encoding in CompressionEncoding::supported() {
+    ///     println!("compression with {encoding} is available");
+    /// }
+    /// ```
+    pub fn supported() -> &'static [CompressionEncoding] {
+        Self::ENCODINGS
+    }
+
     /// Based on the `grpc-accept-encoding` header, pick an encoding to use.
     pub(crate) fn from_accept_encoding_header(
         map: &http::HeaderMap,
@@ -166,6 +181,51 @@ impl CompressionEncoding {
         }
     }
 
+    /// Get the value of the `grpc-encoding` header of a response.
+    ///
+    /// The server may only use an encoding that was advertised in the request's
+    /// `grpc-accept-encoding` header. Any other encoding is a violation of the negotiation and
+    /// results in an `Internal` error naming both the offered and the used encodings. If
+    /// `allow_unadvertised` is set, an unadvertised encoding is decoded anyway as long as it is
+    /// supported by this build.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn from_response_encoding_header(
+        map: &http::HeaderMap,
+        accepted_encodings: EnabledCompressionEncodings,
+        allow_unadvertised: bool,
+    ) -> Result<Option<Self>, Status> {
+        let Some(header_value) = map.get(ENCODING_HEADER) else {
+            return Ok(None);
+        };
+
+        if header_value == "identity" {
+            return Ok(None);
+        }
+
+        let encoding = Self::ENCODINGS
+            .iter()
+            .copied()
+            .find(|encoding| header_value == encoding.as_str());
+
+        match encoding {
+            Some(encoding) if accepted_encodings.is_enabled(encoding) || allow_unadvertised => {
+                Ok(Some(encoding))
+            }
+            _ => {
+                let offered = accepted_encodings
+                    .into_accept_encoding_header_value()
+                    .unwrap_or_else(|| http::HeaderValue::from_static("identity"));
+
+                Err(Status::internal(format!(
+                    "Server violated compression negotiation: response is compressed with `{}` \
+                     but the client only accepts `{}`",
+                    String::from_utf8_lossy(header_value.as_bytes()),
+                    String::from_utf8_lossy(offered.as_bytes()),
+                )))
+            }
+        }
+    }
+
     pub(crate) fn as_str(self) -> &'static str {
         match self {
             #[cfg(feature = "gzip")]
@@ -252,13 +312,15 @@ pub(crate) fn decompress(
 
     match settings.encoding {
         #[cfg(feature = "gzip")]
+        // The decoders read straight from the compressed frame, which is already in memory,
+        // instead of allocating an intermediate read buffer for every message.
         CompressionEncoding::Gzip => {
             let mut gzip_decoder = GzDecoder::new(&compressed_buf[0..len]);
             std::io::copy(&mut gzip_decoder, &mut out_writer)?;
         }
         #[cfg(feature = "zstd")]
         CompressionEncoding::Zstd => {
-            let mut zstd_decoder = Decoder::new(&compressed_buf[0..len])?;
+            let mut zstd_decoder = Decoder::with_buffer(&compressed_buf[0..len])?;
             std::io::copy(&mut zstd_decoder, &mut out_writer)?;
         }
     }
@@ -268,6 +330,61 @@ pub(crate) fn decompress(
     Ok(())
 }
 
+/// Decides which outgoing messages get compressed when a compression encoding is in use.
+///
+/// Messages that are not compressed are sent with the compressed-flag unset, which is valid even
+/// when a `grpc-encoding` has been negotiated for the stream.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum CompressionPolicy {
+    /// Compress every message.
+    ///
+    /// This is the default.
+    #[default]
+    Always,
+    /// Only compress messages whose uncompressed size exceeds the given fraction of the maximum
+    /// encoding message size.
+    ///
+    /// This "compress to fit" policy avoids spending CPU on small messages while still
+    /// compressing the ones at risk of hitting the limit. Since the limit is checked against the
+    /// compressed size, a message that is larger than the limit uncompressed is still sent
+    /// successfully if compression brings it under the limit.
+    ///
+    /// Without an explicit maximum encoding message size the limit is unbounded, so no message
+    /// will be compressed.
+    ///
+    /// The fraction must be between `0.0` and `1.0`, prefer creating this policy with
+    /// [`CompressionPolicy::exceeds_limit_fraction`], which checks it. Fractions outside of that
+    /// range are clamped to it, and a NaN fraction never compresses.
+    ExceedsLimitFraction(f64),
+}
+
+impl CompressionPolicy {
+    /// Only compress messages whose uncompressed size exceeds `fraction` of the maximum encoding
+    /// message size, see [`CompressionPolicy::ExceedsLimitFraction`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fraction` is not between `0.0` and `1.0`.
+    pub fn exceeds_limit_fraction(fraction: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&fraction),
+            "compression limit fraction must be between 0.0 and 1.0"
+        );
+
+        CompressionPolicy::ExceedsLimitFraction(fraction)
+    }
+
+    pub(crate) fn should_compress(self, len: usize, max_message_size: usize) -> bool {
+        match self {
+            CompressionPolicy::Always => true,
+            CompressionPolicy::ExceedsLimitFraction(fraction) => {
+                len as f64 > max_message_size as f64 * fraction.clamp(0.0, 1.0)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum SingleMessageCompressionOverride {
     /// Inherit whatever compression is already configured. If the stream is compressed this
@@ -286,6 +403,92 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn supported_encodings_match_features() {
+        let supported: Vec<_> = CompressionEncoding::supported()
+            .iter()
+            .map(|encoding| encoding.as_str())
+            .collect();
+
+        let mut expected = Vec::new();
+        if cfg!(feature = "gzip") {
+            expected.push("gzip");
+        }
+        if cfg!(feature = "zstd") {
+            expected.push("zstd");
+        }
+        assert_eq!(supported, expected);
+    }
+
+    #[test]
+    fn exceeds_limit_fraction_is_clamped() {
+        let half = CompressionPolicy::exceeds_limit_fraction(0.5);
+        assert!(!half.should_compress(50, 100));
+        assert!(half.should_compress(51, 100));
+
+        let over_one = CompressionPolicy::ExceedsLimitFraction(2.0);
+        assert!(!over_one.should_compress(100, 100));
+        assert!(over_one.should_compress(101, 100));
+        assert!(CompressionPolicy::ExceedsLimitFraction(-1.0).should_compress(1, 100));
+        assert!(!CompressionPolicy::ExceedsLimitFraction(f64::NAN).should_compress(101, 100));
+    }
+
+    #[test]
+    #[should_panic(expected = "compression limit fraction must be between 0.0 and 1.0")]
+    fn rejects_large_limit_fraction() {
+        let _ = CompressionPolicy::exceeds_limit_fraction(1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "compression limit fraction must be between 0.0 and 1.0")]
+    fn rejects_nan_limit_fraction() {
+        let _ = CompressionPolicy::exceeds_limit_fraction(f64::NAN);
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn assert_decompress_reuses_buffer(encoding: CompressionEncoding) {
+        let settings = CompressionSettings {
+            encoding,
+            buffer_growth_interval: 8 * 1024,
+        };
+        let message = BytesMut::from(&[7u8; 4096][..]);
+
+        let mut out_buf = BytesMut::new();
+        let mut ptr = None;
+        for _ in 0..3 {
+            let mut compressed = BytesMut::new();
+            compress(
+                settings,
+                &mut message.clone(),
+                &mut compressed,
+                message.len(),
+            )
+            .unwrap();
+
+            out_buf.clear();
+            let len = compressed.len();
+            decompress(settings, &mut compressed, &mut out_buf, len).unwrap();
+            assert_eq!(out_buf, message);
+
+            // The allocation of the previous message is reclaimed.
+            assert_eq!(*ptr.get_or_insert(out_buf.as_ptr()), out_buf.as_ptr());
+            // Consume the message like a decoder does.
+            out_buf.advance(out_buf.len());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn gzip_decompress_reuses_buffer() {
+        assert_decompress_reuses_buffer(CompressionEncoding::Gzip);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn zstd_decompress_reuses_buffer() {
+        assert_decompress_reuses_buffer(CompressionEncoding::Zstd);
+    }
+
     #[test]
     fn convert_none_into_header_value() {
         let encodings = EnabledCompressionEncodings::default();
@@ -293,6 +496,48 @@ mod tests {
         assert!(encodings.into_accept_encoding_header_value().is_none());
     }
 
+    #[test]
+    #[cfg(all(feature = "gzip", feature = "zstd"))]
+    fn response_encoding_violating_negotiation() {
+        let mut accepted = EnabledCompressionEncodings::default();
+        accepted.enable(CompressionEncoding::Gzip);
+
+        let mut map = http::HeaderMap::new();
+        map.insert(ENCODING_HEADER, HeaderValue::from_static("zstd"));
+
+        let status =
+            CompressionEncoding::from_response_encoding_header(&map, accepted, false).unwrap_err();
+        assert_eq!(status.code(), crate::Code::Internal);
+        assert_eq!(
+            status.message(),
+            "Server violated compression negotiation: response is compressed with `zstd` \
+             but the client only accepts `gzip,identity`"
+        );
+
+        let encoding =
+            CompressionEncoding::from_response_encoding_header(&map, accepted, true).unwrap();
+        assert_eq!(encoding, Some(CompressionEncoding::Zstd));
+    }
+
+    #[test]
+    fn response_encoding_unsupported_in_build() {
+        let mut map = http::HeaderMap::new();
+        map.insert(ENCODING_HEADER, HeaderValue::from_static("br"));
+
+        let status = CompressionEncoding::from_response_encoding_header(
+            &map,
+            EnabledCompressionEncodings::default(),
+            true,
+        )
+        .unwrap_err();
+        assert_eq!(status.code(), crate::Code::Internal);
+        assert_eq!(
+            status.message(),
+            "Server violated compression negotiation: response is compressed with `br` \
+             but the client only accepts `identity`"
+        );
+    }
+
     #[test]
     #[cfg(feature = "gzip")]
     fn convert_gzip_into_header_value() {
@@ -1,17 +1,25 @@
 use super::compression::{decompress, CompressionEncoding, CompressionSettings};
-use super::{BufferSettings, DecodeBuf, Decoder, DEFAULT_MAX_RECV_MESSAGE_SIZE, HEADER_SIZE};
+use super::{
+    BufferSettings, DecodeBuf, Decoder, DEFAULT_MAX_RECV_MESSAGE_SIZE, HEADER_SIZE,
+    TERMINAL_METADATA_FLAG,
+};
 use crate::{body::BoxBody, metadata::MetadataMap, Code, Status};
-use bytes::{Buf, BufMut, BytesMut};
-use http::{HeaderMap, StatusCode};
-use http_body::Body;
-use http_body_util::BodyExt;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use http_body::{Body, Frame};
+use http_body_util::{BodyExt, StreamBody};
 use std::{
-    fmt, future,
+    fmt, future, io,
     pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     task::ready,
     task::{Context, Poll},
+    time::Instant,
 };
-use tokio_stream::Stream;
+use tokio_stream::{Stream, StreamExt};
 use tracing::{debug, trace};
 
 /// Streaming requests and responses.
@@ -21,6 +29,171 @@ use tracing::{debug, trace};
 pub struct Streaming<T> {
     decoder: Box<dyn Decoder<Item = T, Error = Status> + Send + 'static>,
     inner: StreamingInner,
+    progress_observer: Option<ProgressObserver>,
+    decode_observer: Option<DecodeObserver>,
+    #[cfg(feature = "channel")]
+    idle_timeout: Option<IdleTimeout>,
+}
+
+type DecodeObserver = Arc<dyn Fn(DecodeSample) + Send + Sync>;
+
+type ProgressObserver = Box<dyn FnMut(StreamProgress<'_>) + Send>;
+
+/// What a [`Streaming`] yielded, see [`Streaming::on_progress`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum StreamProgress<'a> {
+    /// A message was yielded.
+    Message,
+    /// The stream ended, with the status it failed with if any.
+    End(Option<&'a Status>),
+}
+
+/// The framing state of a [`Streaming`], see [`Streaming::decode_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeState {
+    /// The number of bytes received but not decoded yet.
+    pub buffered_bytes: usize,
+    /// Whether the stream is waiting for the header of the next frame.
+    ///
+    /// The buffered bytes, if any, are the start of that header.
+    pub awaiting_frame_header: bool,
+    /// The number of bytes still missing from the message of the frame whose header has been
+    /// received.
+    pub partial_frame_remaining: Option<usize>,
+}
+
+/// The cost of decoding a single message, see [`Streaming::on_decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeSample {
+    /// The time spent in [`Decoder::decode`], in nanoseconds.
+    pub decode_nanos: u64,
+    /// The size of the message handed to the decoder, after decompression.
+    pub decoded_size: usize,
+}
+
+/// The aggregated cost of decoding the messages of a [`Streaming`].
+///
+/// Returned by [`Streaming::decode_stats`]. The handle is cheap to clone and is updated as
+/// messages are decoded.
+#[derive(Debug, Clone, Default)]
+pub struct DecodeStats {
+    inner: Arc<DecodeTotals>,
+}
+
+#[derive(Debug, Default)]
+struct DecodeTotals {
+    messages: AtomicU64,
+    decode_nanos: AtomicU64,
+    decoded_bytes: AtomicU64,
+}
+
+impl DecodeStats {
+    fn record(&self, sample: DecodeSample) {
+        self.inner.messages.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .decode_nanos
+            .fetch_add(sample.decode_nanos, Ordering::Relaxed);
+        self.inner
+            .decoded_bytes
+            .fetch_add(sample.decoded_size as u64, Ordering::Relaxed);
+    }
+
+    /// The number of messages decoded so far.
+    pub fn messages(&self) -> u64 {
+        self.inner.messages.load(Ordering::Relaxed)
+    }
+
+    /// The total time spent decoding messages, in nanoseconds.
+    pub fn decode_nanos(&self) -> u64 {
+        self.inner.decode_nanos.load(Ordering::Relaxed)
+    }
+
+    /// The total size of the decoded messages, after decompression.
+    pub fn decoded_bytes(&self) -> u64 {
+        self.inner.decoded_bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Iterator over the messages decoded from the complete frames at the start of a buffer with
+/// [`Decoder::decode_streaming`].
+///
+/// The iteration stops at the first incomplete frame, or after the first error.
+#[derive(Debug)]
+pub struct DecodeFrames<'a, D: ?Sized> {
+    decoder: &'a mut D,
+    src: &'a mut BytesMut,
+    done: bool,
+}
+
+impl<'a, D: ?Sized> DecodeFrames<'a, D> {
+    /// Decode the complete frames at the start of `src` with `decoder`.
+    pub fn new(decoder: &'a mut D, src: &'a mut BytesMut) -> Self {
+        Self {
+            decoder,
+            src,
+            done: false,
+        }
+    }
+}
+
+impl<D: Decoder + ?Sized> Iterator for DecodeFrames<'_, D> {
+    type Item = Result<D::Item, D::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let decoded = self.decoder.decode_streaming(self.src).transpose();
+        self.done = !matches!(decoded, Some(Ok(_)));
+        decoded
+    }
+}
+
+/// The default [`Decoder::decode_streaming`].
+pub(crate) fn decode_next_frame<D: Decoder + ?Sized>(
+    decoder: &mut D,
+    src: &mut BytesMut,
+) -> Result<Option<D::Item>, D::Error> {
+    loop {
+        if src.len() < HEADER_SIZE {
+            return Ok(None);
+        }
+
+        let compressed = src[0] != 0;
+        let len = u32::from_be_bytes(src[1..HEADER_SIZE].try_into().unwrap()) as usize;
+        if src.len() - HEADER_SIZE < len {
+            return Ok(None);
+        }
+
+        if compressed {
+            let err = io::Error::new(
+                io::ErrorKind::InvalidData,
+                "compressed frames can't be decoded without decompressing them",
+            );
+            return Err(err.into());
+        }
+
+        src.advance(HEADER_SIZE);
+        let mut buf = DecodeBuf::new(src, len);
+        let decoded = decoder.decode(&mut buf);
+        // Skip whatever the decoder left of the message.
+        let unread = buf.remaining();
+        src.advance(unread);
+
+        if let Some(item) = decoded? {
+            return Ok(Some(item));
+        }
+    }
+}
+
+/// Fails the stream when no bytes of it arrive within `duration` while waiting for a message.
+#[cfg(feature = "channel")]
+struct IdleTimeout {
+    duration: std::time::Duration,
+    sleep: Pin<Box<tokio::time::Sleep>>,
+    /// Whether the caller is waiting for a message, the timer only runs while it is.
+    waiting: bool,
 }
 
 struct StreamingInner {
@@ -28,10 +201,22 @@ struct StreamingInner {
     state: State,
     direction: Direction,
     buf: BytesMut,
+    /// Complete uncompressed frames split off the start of `buf`, decoded with
+    /// [`Decoder::decode_streaming`].
+    frames: BytesMut,
     trailers: Option<HeaderMap>,
+    /// Trailers carrying an error `grpc-status`, kept for [`Streaming::raw_trailers`].
+    status_trailers: Option<HeaderMap>,
     decompress_buf: BytesMut,
     encoding: Option<CompressionEncoding>,
     max_message_size: Option<usize>,
+    /// Decode frames flagged as compressed as is when no encoding was negotiated.
+    lenient_compression_flag: bool,
+    /// Read the trailers from a frame flagged with [`TERMINAL_METADATA_FLAG`] in the body.
+    in_body_trailers: bool,
+    /// Whether data was read from the body since the idle timeout was last restarted.
+    #[cfg(feature = "channel")]
+    data_received: bool,
 }
 
 impl<T> Unpin for Streaming<T> {}
@@ -43,6 +228,10 @@ enum State {
         compression: Option<CompressionEncoding>,
         len: usize,
     },
+    /// Reading a trailers frame sent in the body, see [`Streaming::with_in_body_trailers`].
+    ReadTrailers {
+        len: usize,
+    },
     Error(Option<Status>),
 }
 
@@ -109,6 +298,89 @@ impl<T> Streaming<T> {
         )
     }
 
+    /// Create a new streaming request from length-prefixed gRPC frames, such as the ones yielded
+    /// by [`Streaming::into_bytes_stream`].
+    ///
+    /// Frames may be split or merged arbitrarily across the items of `stream`. Compressed frames
+    /// are decompressed with `encoding`.
+    #[allow(clippy::result_large_err)]
+    pub fn from_bytes_stream<S, D>(
+        decoder: D,
+        stream: S,
+        encoding: Option<CompressionEncoding>,
+        max_message_size: Option<usize>,
+    ) -> Self
+    where
+        S: Stream<Item = Result<Bytes, Status>> + Send + 'static,
+        D: Decoder<Item = T, Error = Status> + Send + 'static,
+    {
+        let body = StreamBody::new(stream.map(|bytes| bytes.map(Frame::data)));
+        Self::new_request(decoder, body, encoding, max_message_size)
+    }
+
+    /// Create a streaming response yielding `messages`, for tests.
+    ///
+    /// The messages go through the same framing as a real response. The stream ends after the
+    /// first error, whose status is returned instead of the remaining messages, as if the server
+    /// had sent it in the trailers.
+    ///
+    /// ```
+    /// # use tonic::{codec::Streaming, Status};
+    /// # async {
+    /// let mut stream = Streaming::from_messages([Ok(1), Ok(2), Err(Status::aborted("stop"))]);
+    ///
+    /// assert_eq!(stream.message().await.unwrap(), Some(1));
+    /// assert_eq!(stream.message().await.unwrap(), Some(2));
+    /// assert_eq!(stream.message().await.unwrap_err().message(), "stop");
+    /// # };
+    /// ```
+    #[cfg(feature = "test-util")]
+    pub fn from_messages(messages: impl IntoIterator<Item = Result<T, Status>>) -> Self
+    where
+        T: Send + 'static,
+    {
+        Self::from_messages_with_trailers(messages, MetadataMap::new())
+    }
+
+    /// Create a streaming response yielding `messages` and ending with `trailers`, for tests.
+    ///
+    /// Like [`Streaming::from_messages`], but the trailers also carry `trailers`. They are
+    /// returned by [`Streaming::trailers`] if the stream ends successfully, and are the metadata
+    /// of the status otherwise.
+    #[cfg(feature = "test-util")]
+    pub fn from_messages_with_trailers(
+        messages: impl IntoIterator<Item = Result<T, Status>>,
+        trailers: MetadataMap,
+    ) -> Self
+    where
+        T: Send + 'static,
+    {
+        let mut queue = std::collections::VecDeque::new();
+        let mut status = Status::ok("");
+        for message in messages {
+            match message {
+                Ok(message) => queue.push_back(message),
+                Err(err) => {
+                    status = err;
+                    break;
+                }
+            }
+        }
+
+        let mut frames: Vec<_> =
+            std::iter::repeat_with(|| Frame::data(Bytes::from_static(&[0; HEADER_SIZE])))
+                .take(queue.len())
+                .collect();
+        let mut headers = status
+            .to_header_map()
+            .expect("status can be sent as trailers");
+        headers.extend(trailers.into_sanitized_headers());
+        frames.push(Frame::trailers(headers));
+
+        let body = StreamBody::new(tokio_stream::iter(frames.into_iter().map(Ok::<_, Status>)));
+        Self::new_response(QueuedMessages(queue), body, StatusCode::OK, None, None)
+    }
+
     fn new<B, D>(
         decoder: D,
         body: B,
@@ -132,16 +404,66 @@ impl<T> Streaming<T> {
                 state: State::ReadHeader,
                 direction,
                 buf: BytesMut::with_capacity(buffer_size),
+                frames: BytesMut::new(),
                 trailers: None,
+                status_trailers: None,
                 decompress_buf: BytesMut::new(),
                 encoding,
                 max_message_size,
+                lenient_compression_flag: false,
+                in_body_trailers: false,
+                #[cfg(feature = "channel")]
+                data_received: false,
             },
+            progress_observer: None,
+            decode_observer: None,
+            #[cfg(feature = "channel")]
+            idle_timeout: None,
         }
     }
+
+    /// Call `f` with each message yielded by the stream, and once when it ends.
+    pub(crate) fn on_progress<F>(&mut self, mut f: F)
+    where
+        F: FnMut(StreamProgress<'_>) + Send + 'static,
+    {
+        self.progress_observer = Some(match self.progress_observer.take() {
+            Some(mut previous) => Box::new(move |progress| {
+                previous(progress);
+                f(progress);
+            }),
+            None => Box::new(f),
+        });
+    }
+
+    pub(crate) fn with_lenient_compression_flag(mut self) -> Self {
+        self.inner.lenient_compression_flag = true;
+        self
+    }
+
+    /// Accept the trailers in a frame of the body, as sent over HTTP/1.1 by gRPC-Web servers or
+    /// as the terminal metadata of a request stream.
+    ///
+    /// The frame is flagged with [`TERMINAL_METADATA_FLAG`] and holds the trailers as
+    /// `name: value\r\n` lines. Trailers sent as HTTP trailers are still read.
+    pub(crate) fn with_in_body_trailers(mut self) -> Self {
+        self.inner.in_body_trailers = true;
+        self
+    }
+
+    #[cfg(feature = "channel")]
+    pub(crate) fn with_idle_timeout(mut self, duration: std::time::Duration) -> Self {
+        self.idle_timeout = Some(IdleTimeout {
+            duration,
+            sleep: Box::pin(tokio::time::sleep(duration)),
+            waiting: false,
+        });
+        self
+    }
 }
 
 impl StreamingInner {
+    #[allow(clippy::result_large_err)]
     fn decode_chunk(
         &mut self,
         buffer_settings: BufferSettings,
@@ -152,11 +474,32 @@ impl StreamingInner {
             }
 
             let compression_encoding = match self.buf.get_u8() {
+                TERMINAL_METADATA_FLAG if self.in_body_trailers => {
+                    let len = self.buf.get_u32() as usize;
+                    let limit = self
+                        .max_message_size
+                        .unwrap_or(DEFAULT_MAX_RECV_MESSAGE_SIZE);
+                    if len > limit {
+                        return Err(Status::resource_exhausted(format!(
+                            "Error, trailers frame too large: found {} bytes, the limit is: {} bytes",
+                            len, limit
+                        )));
+                    }
+                    self.buf.reserve(len);
+                    self.state = State::ReadTrailers { len };
+                    if !self.take_trailers_frame() {
+                        return Err(Status::internal("protocol error: invalid trailers frame"));
+                    }
+                    return Ok(None);
+                }
                 0 => None,
                 1 => {
                     {
                         if self.encoding.is_some() {
                             self.encoding
+                        } else if self.lenient_compression_flag {
+                            trace!("compressed-flag set without grpc-encoding, decoding as is");
+                            None
                         } else {
                             // https://grpc.github.io/grpc/core/md_doc_compression.html
                             // An ill-constructed message with its Compressed-Flag bit set but lacking a grpc-encoding
@@ -201,6 +544,13 @@ impl StreamingInner {
             }
         }
 
+        if let State::ReadTrailers { .. } = self.state {
+            if !self.take_trailers_frame() {
+                return Err(Status::internal("protocol error: invalid trailers frame"));
+            }
+            return Ok(None);
+        }
+
         if let State::ReadBody { len, compression } = self.state {
             // if we haven't read enough of the message then return and keep
             // reading
@@ -208,7 +558,9 @@ impl StreamingInner {
                 return Ok(None);
             }
 
-            let decode_buf = if let Some(encoding) = compression {
+            if let Some(encoding) = compression {
+                // The scratch buffer is shared by all the frames of the stream. Once the previous
+                // message has been decoded, clearing it keeps its allocation for the next one.
                 self.decompress_buf.clear();
 
                 if let Err(err) = decompress(
@@ -230,6 +582,9 @@ impl StreamingInner {
                     };
                     return Err(Status::internal(message));
                 }
+            }
+
+            let decode_buf = if compression.is_some() {
                 let decompressed_len = self.decompress_buf.len();
                 DecodeBuf::new(&mut self.decompress_buf, decompressed_len)
             } else {
@@ -242,6 +597,57 @@ impl StreamingInner {
         Ok(None)
     }
 
+    // Ends the stream without reading the rest of the body, dropping it resets the HTTP/2 stream.
+    fn abort(&mut self) {
+        self.body = crate::body::empty_body();
+        self.state = State::Error(None);
+    }
+
+    // Splits off the complete frames at the start of the buffer that can be decoded as is: frames
+    // that are uncompressed and within the size limit.
+    fn split_complete_frames(&mut self) {
+        if !matches!(self.state, State::ReadHeader) {
+            return;
+        }
+
+        let limit = self
+            .max_message_size
+            .unwrap_or(DEFAULT_MAX_RECV_MESSAGE_SIZE);
+        let mut end = 0;
+        while let Some(header) = self.buf.get(end..end + HEADER_SIZE) {
+            let len = u32::from_be_bytes(header[1..].try_into().unwrap()) as usize;
+            if header[0] != 0 || len > limit || self.buf.len() - end - HEADER_SIZE < len {
+                break;
+            }
+            end += HEADER_SIZE + len;
+        }
+
+        if end > 0 {
+            self.frames = self.buf.split_to(end);
+        }
+    }
+
+    // Moves the trailers frame to the trailers once it has been received, no message follows it.
+    // Returns false if the frame is malformed.
+    fn take_trailers_frame(&mut self) -> bool {
+        let State::ReadTrailers { len } = self.state else {
+            return true;
+        };
+        if self.buf.len() < len {
+            return true;
+        }
+
+        let frame = self.buf.split_to(len);
+        let Some(trailers) = parse_trailers_frame(&frame) else {
+            return false;
+        };
+        self.trailers
+            .get_or_insert_with(HeaderMap::new)
+            .extend(trailers);
+        self.state = State::ReadHeader;
+        true
+    }
+
     // Returns Some(()) if data was found or None if the loop in `poll_next` should break
     fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<()>, Status>> {
         let chunk = match ready!(Pin::new(&mut self.body).poll_frame(cx)) {
@@ -262,6 +668,10 @@ impl StreamingInner {
             match frame {
                 frame if frame.is_data() => {
                     self.buf.put(frame.into_data().unwrap());
+                    #[cfg(feature = "channel")]
+                    {
+                        self.data_received = true;
+                    }
                     Ok(Some(()))
                 }
                 frame if frame.is_trailers() => {
@@ -289,12 +699,72 @@ impl StreamingInner {
         })
     }
 
+    // Splits the next complete length-prefixed frame off the buffer, without decompressing it.
+    fn poll_raw_frame(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, Status>>> {
+        loop {
+            if let State::Error(status) = &mut self.state {
+                return Poll::Ready(status.take().map(Err));
+            }
+
+            if !self.frames.is_empty() {
+                let len = u32::from_be_bytes(self.frames[1..HEADER_SIZE].try_into().unwrap());
+                let frame = self.frames.split_to(HEADER_SIZE + len as usize).freeze();
+                return Poll::Ready(Some(Ok(frame)));
+            }
+
+            if self.buf.len() >= HEADER_SIZE {
+                let len = u32::from_be_bytes(self.buf[1..HEADER_SIZE].try_into().unwrap()) as usize;
+                let limit = self
+                    .max_message_size
+                    .unwrap_or(DEFAULT_MAX_RECV_MESSAGE_SIZE);
+                if len > limit {
+                    let status = Status::out_of_range(format!(
+                        "Error, decoded message length too large: found {} bytes, the limit is: {} bytes",
+                        len, limit
+                    ));
+                    self.state = State::Error(None);
+                    return Poll::Ready(Some(Err(status)));
+                }
+
+                if self.buf.len() >= HEADER_SIZE + len {
+                    let frame = self.buf.split_to(HEADER_SIZE + len).freeze();
+                    return Poll::Ready(Some(Ok(frame)));
+                }
+            }
+
+            match ready!(self.poll_frame(cx))? {
+                Some(()) => (),
+                None => break,
+            }
+        }
+
+        Poll::Ready(self.response().err().map(Err))
+    }
+
+    // Discards the rest of the body data without decoding it, until the trailers are reached.
+    fn poll_skip_data(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Status>> {
+        loop {
+            if let State::Error(status) = &mut self.state {
+                return Poll::Ready(status.take().map_or(Ok(()), Err));
+            }
+
+            self.frames.clear();
+            self.buf.clear();
+            if ready!(self.poll_frame(cx))?.is_none() {
+                break;
+            }
+        }
+
+        self.state = State::ReadHeader;
+        Poll::Ready(self.response())
+    }
+
     fn response(&mut self) -> Result<(), Status> {
         if let Direction::Response(status) = self.direction {
             if let Err(Some(e)) = crate::status::infer_grpc_status(self.trailers.as_ref(), status) {
                 // If the trailers contain a grpc-status, then we should return that as the error
                 // and otherwise stop the stream (by taking the error state)
-                self.trailers.take();
+                self.status_trailers = self.trailers.take();
                 return Err(e);
             }
         }
@@ -374,24 +844,245 @@ impl<T> Streaming<T> {
         Ok(None)
     }
 
+    /// Fetch the trailing metadata, discarding any remaining body data without decoding it.
+    pub(crate) async fn trailers_skipping_data(&mut self) -> Result<Option<MetadataMap>, Status> {
+        if self.inner.trailers.is_none() {
+            future::poll_fn(|cx| self.inner.poll_skip_data(cx)).await?;
+        }
+
+        Ok(self.inner.trailers.take().map(MetadataMap::from_headers))
+    }
+
+    /// Fetch the trailers exactly as they were received, without extracting the status.
+    ///
+    /// Unlike [`Streaming::trailers`], the trailers are returned even if they carry an error
+    /// `grpc-status`, which makes this suitable for relays that forward trailers verbatim. The
+    /// stream is drained to reach the trailers, so any remaining messages are discarded. Errors
+    /// that occur before the trailers are received are still returned.
+    pub async fn raw_trailers(&mut self) -> Result<Option<HeaderMap>, Status> {
+        if let Some(trailers) = self.inner.trailers.take() {
+            return Ok(Some(trailers));
+        }
+
+        loop {
+            match self.message().await {
+                Ok(Some(_)) => continue,
+                Ok(None) => break,
+                Err(status) => {
+                    return match self.inner.status_trailers.take() {
+                        Some(trailers) => Ok(Some(trailers)),
+                        None => Err(status),
+                    }
+                }
+            }
+        }
+
+        Ok(self
+            .inner
+            .trailers
+            .take()
+            .or_else(|| self.inner.status_trailers.take()))
+    }
+
+    /// Inspect how far the stream is through the frame it is currently receiving.
+    ///
+    /// This is meant for debugging a stalled stream: a stream waiting for the rest of a partially
+    /// received frame is stuck on the network, while an idle one waits for the sender. Reading
+    /// the state leaves the received bytes untouched.
+    pub fn decode_state(&self) -> DecodeState {
+        let (awaiting_frame_header, partial_frame_remaining) = match &self.inner.state {
+            State::ReadHeader => (true, None),
+            State::ReadBody { len, .. } | State::ReadTrailers { len } => {
+                (false, Some(len.saturating_sub(self.inner.buf.len())))
+            }
+            State::Error(_) => (false, None),
+        };
+
+        DecodeState {
+            buffered_bytes: self.inner.frames.len() + self.inner.buf.len(),
+            awaiting_frame_header,
+            partial_frame_remaining,
+        }
+    }
+
+    /// Call `f` with the cost of decoding each message received from now on.
+    ///
+    /// The time spent in the [`Decoder`] is measured separately from the time spent waiting for
+    /// the network, which makes it possible to profile services whose messages are expensive to
+    /// decode. Nothing is measured unless this or [`Streaming::decode_stats`] has been called.
+    pub fn on_decode<F>(&mut self, f: F)
+    where
+        F: Fn(DecodeSample) + Send + Sync + 'static,
+    {
+        self.decode_observer = Some(match self.decode_observer.take() {
+            Some(previous) => Arc::new(move |sample| {
+                previous(sample);
+                f(sample);
+            }),
+            None => Arc::new(f),
+        });
+    }
+
+    /// Aggregate the cost of decoding each message received from now on.
+    ///
+    /// See [`Streaming::on_decode`] to get the cost of each message instead.
+    pub fn decode_stats(&mut self) -> DecodeStats {
+        let stats = DecodeStats::default();
+        let recorder = stats.clone();
+        self.on_decode(move |sample| recorder.record(sample));
+        stats
+    }
+
+    /// Collect the remaining messages of this stream into a `Vec`, failing if there are more
+    /// than `max` of them.
+    ///
+    /// Returns [`Code::ResourceExhausted`] once the sender exceeds `max` messages, and the
+    /// terminal status if the stream ends with an error. On success, the trailers can still be
+    /// fetched with [`Streaming::trailers`].
+    ///
+    /// ```rust
+    /// # use tonic::{Streaming, Status};
+    /// # async fn collect_capped_ex<T>(mut response: Streaming<T>) -> Result<(), Status> {
+    /// let messages = response.collect_capped(100).await?;
+    /// let trailers = response.trailers().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn collect_capped(&mut self, max: usize) -> Result<Vec<T>, Status> {
+        let mut messages = Vec::new();
+
+        while let Some(message) = self.message().await? {
+            if messages.len() == max {
+                return Err(Status::resource_exhausted(format!(
+                    "Stream yielded more than the maximum of {} messages",
+                    max
+                )));
+            }
+            messages.push(message);
+        }
+
+        Ok(messages)
+    }
+
+    /// Drain the stream and return how many messages it yielded.
+    ///
+    /// Fails with the first error of the stream, including an error status in the trailers, so
+    /// that a truncated stream is not mistaken for a complete one.
+    ///
+    /// ```rust
+    /// # use tonic::{Streaming, Status};
+    /// # async fn count_messages_ex<T>(response: Streaming<T>) -> Result<(), Status> {
+    /// let matches = response.count_messages().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn count_messages(mut self) -> Result<usize, Status> {
+        let mut count = 0;
+        while self.message().await?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Pair the messages of this stream with the messages of `other` by position.
+    ///
+    /// The returned stream yields an error as soon as either stream yields one. If one stream
+    /// ends before the other, it yields [`Code::DataLoss`] and ends.
+    ///
+    /// ```rust
+    /// # use tonic::{Streaming, Status};
+    /// # use tokio_stream::StreamExt;
+    /// # async fn zip_ex(keys: Streaming<String>, values: Streaming<u64>) -> Result<(), Status> {
+    /// let mut pairs = std::pin::pin!(keys.zip_streaming(values));
+    /// while let Some((key, value)) = pairs.try_next().await? {
+    ///     println!("{} = {}", key, value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn zip_streaming<U>(
+        self,
+        other: Streaming<U>,
+    ) -> impl Stream<Item = Result<(T, U), Status>> {
+        Zip {
+            left: self,
+            right: other,
+            left_item: None,
+            right_item: None,
+            done: false,
+        }
+    }
+
+    /// Turn this stream into a stream of the raw length-prefixed gRPC frames it receives, without
+    /// decoding them.
+    ///
+    /// Each item is a whole frame, including its compression flag and length prefix. Compressed
+    /// frames are yielded as is. An error status received in the trailers is yielded after the
+    /// last frame, like [`Streaming::message`] would. The frames can be decoded again with
+    /// [`Streaming::from_bytes_stream`].
+    pub fn into_bytes_stream(self) -> impl Stream<Item = Result<Bytes, Status>> {
+        BytesStream { inner: self.inner }
+    }
+
+    #[allow(clippy::result_large_err)]
     fn decode_chunk(&mut self) -> Result<Option<T>, Status> {
-        match self.inner.decode_chunk(self.decoder.buffer_settings())? {
-            Some(mut decode_buf) => match self.decoder.decode(&mut decode_buf)? {
-                Some(msg) => {
-                    self.inner.state = State::ReadHeader;
-                    Ok(Some(msg))
+        if self.inner.frames.is_empty() {
+            self.inner.split_complete_frames();
+        }
+        // The complete frames are decoded in place, without copying them.
+        while !self.inner.frames.is_empty() {
+            let frames = &mut self.inner.frames;
+            let decoded_size =
+                u32::from_be_bytes(frames[1..HEADER_SIZE].try_into().unwrap()) as usize;
+            let start = self.decode_observer.as_ref().map(|_| Instant::now());
+            let decoded = self.decoder.decode_streaming(frames)?;
+
+            if frames.is_empty() {
+                // Release the frames so that the buffer they were split from can be reused.
+                *frames = BytesMut::new();
+            }
+
+            if let Some(item) = decoded {
+                if let (Some(observer), Some(start)) = (&self.decode_observer, start) {
+                    observer(DecodeSample {
+                        decode_nanos: start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX),
+                        decoded_size,
+                    });
                 }
-                None => Ok(None),
-            },
-            None => Ok(None),
+                return Ok(Some(item));
+            }
+        }
+
+        let Some(mut decode_buf) = self.inner.decode_chunk(self.decoder.buffer_settings())? else {
+            return Ok(None);
+        };
+
+        let decoded = match &self.decode_observer {
+            Some(observer) => {
+                let decoded_size = decode_buf.remaining();
+                let start = Instant::now();
+                let decoded = self.decoder.decode(&mut decode_buf)?;
+                let decode_nanos = start.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+                if decoded.is_some() {
+                    observer(DecodeSample {
+                        decode_nanos,
+                        decoded_size,
+                    });
+                }
+                decoded
+            }
+            None => self.decoder.decode(&mut decode_buf)?,
+        };
+
+        if decoded.is_some() {
+            self.inner.state = State::ReadHeader;
         }
+        Ok(decoded)
     }
 }
 
-impl<T> Stream for Streaming<T> {
-    type Item = Result<T, Status>;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+impl<T> Streaming<T> {
+    fn poll_message(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, Status>>> {
         loop {
             // When the stream encounters an error yield that error once and then on subsequent
             // calls to poll_next return Poll::Ready(None) indicating that the stream has been
@@ -400,8 +1091,14 @@ impl<T> Stream for Streaming<T> {
                 return Poll::Ready(status.take().map(Err));
             }
 
-            if let Some(item) = self.decode_chunk()? {
-                return Poll::Ready(Some(Ok(item)));
+            match self.decode_chunk() {
+                Ok(Some(item)) => return Poll::Ready(Some(Ok(item))),
+                Ok(None) => {}
+                Err(status) => {
+                    // The rest of the body can't be decoded.
+                    self.inner.abort();
+                    return Poll::Ready(Some(Err(status)));
+                }
             }
 
             match ready!(self.inner.poll_frame(cx))? {
@@ -417,6 +1114,215 @@ impl<T> Stream for Streaming<T> {
     }
 }
 
+impl<T> Streaming<T> {
+    #[cfg(feature = "channel")]
+    fn poll_message_within_idle_timeout(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<T, Status>>> {
+        use std::future::Future;
+
+        let Some(idle) = &mut self.idle_timeout else {
+            return self.poll_message(cx);
+        };
+
+        // The timer starts when the caller starts waiting for a message, not when the previous
+        // one was handed over.
+        let mut restart = !idle.waiting;
+        idle.waiting = true;
+
+        let poll = self.poll_message(cx);
+        restart |= std::mem::take(&mut self.inner.data_received);
+
+        let Some(idle) = &mut self.idle_timeout else {
+            return poll;
+        };
+
+        if poll.is_ready() {
+            idle.waiting = false;
+            return poll;
+        }
+
+        if restart {
+            let deadline = tokio::time::Instant::now() + idle.duration;
+            idle.sleep.as_mut().reset(deadline);
+        }
+
+        ready!(idle.sleep.as_mut().poll(cx));
+
+        self.idle_timeout = None;
+        self.inner.abort();
+
+        Poll::Ready(Some(Err(Status::deadline_exceeded(
+            "No message received within the stream idle timeout",
+        ))))
+    }
+
+    #[cfg(not(feature = "channel"))]
+    fn poll_message_within_idle_timeout(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<T, Status>>> {
+        self.poll_message(cx)
+    }
+}
+
+// Parses the `name: value\r\n` lines of a trailers frame sent in the body.
+fn parse_trailers_frame(frame: &[u8]) -> Option<HeaderMap> {
+    let mut trailers = HeaderMap::new();
+    for line in frame.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+
+        let colon = line.iter().position(|&b| b == b':')?;
+        let name = HeaderName::from_bytes(line[..colon].trim_ascii()).ok()?;
+        let value = HeaderValue::from_bytes(line[colon + 1..].trim_ascii()).ok()?;
+        trailers.append(name, value);
+    }
+    Some(trailers)
+}
+
+impl<T> Stream for Streaming<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = ready!(self.poll_message_within_idle_timeout(cx));
+
+        match &item {
+            Some(Ok(_)) => {
+                if let Some(observer) = &mut self.progress_observer {
+                    observer(StreamProgress::Message);
+                }
+            }
+            Some(Err(status)) => {
+                if let Some(mut observer) = self.progress_observer.take() {
+                    observer(StreamProgress::End(Some(status)));
+                }
+            }
+            None => {
+                if let Some(mut observer) = self.progress_observer.take() {
+                    observer(StreamProgress::End(None));
+                }
+            }
+        }
+
+        Poll::Ready(item)
+    }
+}
+
+/// Requires building with `--cfg tonic_nightly` on a nightly toolchain.
+#[cfg(tonic_nightly)]
+impl<T> std::async_iter::AsyncIterator for Streaming<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Stream::poll_next(self, cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        Stream::size_hint(self)
+    }
+}
+
+/// Stream returned by [`Streaming::zip_streaming`].
+struct Zip<T, U> {
+    left: Streaming<T>,
+    right: Streaming<U>,
+    // `Some(None)` records that a side has ended while waiting for the other one.
+    left_item: Option<Option<T>>,
+    right_item: Option<Option<U>>,
+    done: bool,
+}
+
+impl<T, U> Unpin for Zip<T, U> {}
+
+impl<T, U> Stream for Zip<T, U> {
+    type Item = Result<(T, U), Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        if this.left_item.is_none() {
+            if let Poll::Ready(item) = Pin::new(&mut this.left).poll_next(cx) {
+                match item.transpose() {
+                    Ok(item) => this.left_item = Some(item),
+                    Err(status) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(status)));
+                    }
+                }
+            }
+        }
+
+        if this.right_item.is_none() {
+            if let Poll::Ready(item) = Pin::new(&mut this.right).poll_next(cx) {
+                match item.transpose() {
+                    Ok(item) => this.right_item = Some(item),
+                    Err(status) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(status)));
+                    }
+                }
+            }
+        }
+
+        let (Some(left), Some(right)) = (&this.left_item, &this.right_item) else {
+            return Poll::Pending;
+        };
+
+        match (left.is_some(), right.is_some()) {
+            (true, true) => {
+                let left = this.left_item.take().flatten().unwrap();
+                let right = this.right_item.take().flatten().unwrap();
+                Poll::Ready(Some(Ok((left, right))))
+            }
+            (false, false) => {
+                this.done = true;
+                Poll::Ready(None)
+            }
+            _ => {
+                this.done = true;
+                Poll::Ready(Some(Err(Status::data_loss(
+                    "Zipped streams ended with a different number of messages",
+                ))))
+            }
+        }
+    }
+}
+
+/// Stream returned by [`Streaming::into_bytes_stream`].
+struct BytesStream {
+    inner: StreamingInner,
+}
+
+impl Stream for BytesStream {
+    type Item = Result<Bytes, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_raw_frame(cx)
+    }
+}
+
+/// Yields the messages of [`Streaming::from_messages`], one per empty frame.
+#[cfg(feature = "test-util")]
+struct QueuedMessages<T>(std::collections::VecDeque<T>);
+
+#[cfg(feature = "test-util")]
+impl<T> Decoder for QueuedMessages<T> {
+    type Item = T;
+    type Error = Status;
+
+    fn decode(&mut self, _: &mut DecodeBuf<'_>) -> Result<Option<T>, Status> {
+        Ok(self.0.pop_front())
+    }
+}
+
 impl<T> fmt::Debug for Streaming<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Streaming").finish()
@@ -425,3 +1331,548 @@ impl<T> fmt::Debug for Streaming<T> {
 
 #[cfg(test)]
 static_assertions::assert_impl_all!(Streaming<()>: Send);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::pin;
+
+    const LEN: usize = 10000;
+
+    #[derive(Debug, Clone, Default)]
+    struct MockDecoder {}
+
+    impl Decoder for MockDecoder {
+        type Item = Vec<u8>;
+        type Error = Status;
+
+        fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+            let out = Vec::from(buf.chunk());
+            buf.advance(LEN);
+            Ok(Some(out))
+        }
+
+        fn buffer_settings(&self) -> BufferSettings {
+            Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn raw_trailers_keep_error_status() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(0);
+        buf.put_u32(LEN as u32);
+        buf.put(&[0u8; LEN][..]);
+
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("grpc-status", "13".parse().unwrap());
+        trailers.insert("x-relay", "verbatim".parse().unwrap());
+
+        let frames = vec![
+            Ok::<_, Status>(http_body::Frame::data(buf.freeze())),
+            Ok(http_body::Frame::trailers(trailers.clone())),
+        ];
+        let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+
+        let mut stream = Streaming::new_response(
+            MockDecoder::default(),
+            body,
+            http::StatusCode::OK,
+            None,
+            None,
+        );
+
+        assert_eq!(stream.raw_trailers().await.unwrap(), Some(trailers));
+    }
+
+    fn response_with_messages(count: usize, status: &'static str) -> Streaming<Vec<u8>> {
+        let mut buf = BytesMut::new();
+        for _ in 0..count {
+            buf.put_u8(0);
+            buf.put_u32(LEN as u32);
+            buf.put(&[0u8; LEN][..]);
+        }
+
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("grpc-status", status.parse().unwrap());
+        trailers.insert("x-done", "yes".parse().unwrap());
+
+        let frames = vec![
+            Ok::<_, Status>(http_body::Frame::data(buf.freeze())),
+            Ok(http_body::Frame::trailers(trailers)),
+        ];
+        let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+
+        Streaming::new_response(
+            MockDecoder::default(),
+            body,
+            http::StatusCode::OK,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn collect_capped_within_limit() {
+        let mut stream = response_with_messages(3, "0");
+
+        let messages = stream.collect_capped(3).await.unwrap();
+        assert_eq!(messages.len(), 3);
+
+        let trailers = stream.trailers().await.unwrap().unwrap();
+        assert_eq!(trailers.get("x-done").unwrap(), "yes");
+    }
+
+    #[tokio::test]
+    async fn collect_capped_exceeded() {
+        let mut stream = response_with_messages(3, "0");
+
+        let status = stream.collect_capped(2).await.unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+    }
+
+    #[tokio::test]
+    async fn collect_capped_propagates_error() {
+        let mut stream = response_with_messages(1, "14");
+
+        let status = stream.collect_capped(10).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn decode_stats_aggregate_samples() {
+        let samples = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut stream = response_with_messages(2, "0");
+        let stats = stream.decode_stats();
+        stream.on_decode({
+            let samples = samples.clone();
+            move |sample| samples.lock().unwrap().push(sample)
+        });
+
+        while stream.message().await.unwrap().is_some() {}
+
+        let samples = samples.lock().unwrap();
+        let sizes: Vec<_> = samples.iter().map(|sample| sample.decoded_size).collect();
+        assert_eq!(sizes, [LEN, LEN]);
+        assert_eq!(stats.messages(), 2);
+        assert_eq!(stats.decoded_bytes(), 2 * LEN as u64);
+        // The decode time may be zero on coarse clocks, but is the sum of the samples.
+        let decode_nanos: u64 = samples.iter().map(|sample| sample.decode_nanos).sum();
+        assert_eq!(stats.decode_nanos(), decode_nanos);
+    }
+
+    #[tokio::test]
+    async fn zip_pairs_messages() {
+        let zipped = response_with_messages(2, "0").zip_streaming(response_with_messages(2, "0"));
+        let pairs: Vec<_> = zipped.collect().await;
+
+        assert_eq!(pairs.len(), 2);
+        assert!(pairs.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test]
+    async fn zip_uneven_lengths() {
+        let zipped = response_with_messages(2, "0").zip_streaming(response_with_messages(1, "0"));
+        let mut zipped = pin!(zipped);
+
+        assert!(zipped.next().await.unwrap().is_ok());
+        let status = zipped.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::DataLoss);
+        assert!(zipped.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn zip_propagates_error() {
+        let zipped = response_with_messages(2, "0").zip_streaming(response_with_messages(0, "14"));
+        let mut zipped = pin!(zipped);
+
+        let status = zipped.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(zipped.next().await.is_none());
+    }
+
+    #[cfg(feature = "prost")]
+    #[test]
+    fn decode_streaming_decodes_complete_frames() {
+        use crate::codec::{Codec, EncodeBuf, Encoder};
+
+        let mut codec = crate::codec::ProstCodec::<String, String>::default();
+        let mut src = BytesMut::new();
+        for message in ["one", "two"] {
+            let mut encoded = BytesMut::new();
+            codec
+                .encoder()
+                .encode(message.to_string(), &mut EncodeBuf::new(&mut encoded))
+                .unwrap();
+            src.put_u8(0);
+            src.put_u32(encoded.len() as u32);
+            src.put(encoded);
+        }
+        // The start of a third frame.
+        src.put_u8(0);
+        src.put_u32(10);
+        src.put(&b"thr"[..]);
+
+        let mut decoder = codec.decoder();
+        let decoded: Vec<_> = DecodeFrames::new(&mut decoder, &mut src)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, ["one", "two"]);
+        assert_eq!(src.len(), HEADER_SIZE + 3);
+    }
+
+    #[test]
+    fn decode_streaming_rejects_compressed_frames() {
+        let mut src = BytesMut::new();
+        src.put_u8(1);
+        src.put_u32(1);
+        src.put_u8(0);
+
+        let mut decoder = MockDecoder::default();
+        let mut frames = DecodeFrames::new(&mut decoder, &mut src);
+        assert_eq!(frames.next().unwrap().unwrap_err().code(), Code::DataLoss);
+        assert!(frames.next().is_none());
+    }
+
+    #[tokio::test]
+    async fn streaming_uses_overridden_decode_streaming() {
+        #[derive(Default)]
+        struct BatchDecoder {
+            streaming_calls: Arc<AtomicU64>,
+        }
+
+        impl Decoder for BatchDecoder {
+            type Item = Vec<u8>;
+            type Error = Status;
+
+            fn decode(
+                &mut self,
+                buf: &mut DecodeBuf<'_>,
+            ) -> Result<Option<Self::Item>, Self::Error> {
+                MockDecoder::default().decode(buf)
+            }
+
+            fn decode_streaming(
+                &mut self,
+                src: &mut BytesMut,
+            ) -> Result<Option<Self::Item>, Self::Error> {
+                self.streaming_calls.fetch_add(1, Ordering::Relaxed);
+                super::decode_next_frame(self, src)
+            }
+        }
+
+        let mut frames = BytesMut::new();
+        for _ in 0..2 {
+            frames.put_u8(0);
+            frames.put_u32(LEN as u32);
+            frames.put(&[0u8; LEN][..]);
+        }
+
+        let decoder = BatchDecoder::default();
+        let streaming_calls = decoder.streaming_calls.clone();
+        let mut stream = Streaming::from_bytes_stream(
+            decoder,
+            tokio_stream::iter([Ok(frames.freeze())]),
+            None,
+            None,
+        );
+
+        for _ in 0..2 {
+            assert_eq!(stream.message().await.unwrap().unwrap().len(), LEN);
+        }
+        assert!(stream.message().await.unwrap().is_none());
+        assert_eq!(streaming_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn streaming_decodes_frames_received_together() {
+        let mut frames = BytesMut::new();
+        for _ in 0..3 {
+            frames.put_u8(0);
+            frames.put_u32(LEN as u32);
+            frames.put(&[0u8; LEN][..]);
+        }
+        // A frame over the size limit follows the ones decoded together.
+        frames.put_u8(0);
+        frames.put_u32(LEN as u32 + 1);
+        let frames = frames.freeze();
+
+        let split = 2 * (HEADER_SIZE + LEN) + 1;
+        let chunks = vec![Ok(frames.slice(..split)), Ok(frames.slice(split..))];
+        let mut stream = Streaming::from_bytes_stream(
+            MockDecoder::default(),
+            tokio_stream::iter(chunks),
+            None,
+            Some(LEN),
+        );
+        let stats = stream.decode_stats();
+
+        for _ in 0..3 {
+            assert_eq!(stream.message().await.unwrap().unwrap().len(), LEN);
+        }
+        let status = stream.message().await.unwrap_err();
+        assert_eq!(status.code(), Code::OutOfRange);
+        assert_eq!(stats.messages(), 3);
+        assert_eq!(stats.decoded_bytes(), 3 * LEN as u64);
+    }
+
+    #[cfg(tonic_nightly)]
+    #[tokio::test]
+    async fn async_iterator_yields_messages() {
+        use std::async_iter::AsyncIterator;
+
+        let mut stream = pin!(response_with_messages(2, "0"));
+        let mut lengths = Vec::new();
+        while let Some(message) =
+            std::future::poll_fn(|cx| AsyncIterator::poll_next(stream.as_mut(), cx)).await
+        {
+            lengths.push(message.unwrap().len());
+        }
+        assert_eq!(lengths, [LEN, LEN]);
+    }
+
+    #[tokio::test]
+    async fn decode_state_tracks_partial_frames() {
+        let mut frames = BytesMut::new();
+        for _ in 0..2 {
+            frames.put_u8(0);
+            frames.put_u32(LEN as u32);
+            frames.put(&[0u8; LEN][..]);
+        }
+        let frames = frames.freeze();
+
+        // A whole frame and the start of the header of the next one, then the rest of the header
+        // and the start of its message, and nothing more.
+        let split = HEADER_SIZE + LEN + 3;
+        let chunks = vec![
+            Ok(frames.slice(..split)),
+            Ok(frames.slice(split..split + 2 + 4)),
+        ];
+        let mut stream = Streaming::from_bytes_stream(
+            MockDecoder::default(),
+            tokio_stream::iter(chunks).chain(tokio_stream::pending()),
+            None,
+            None,
+        );
+
+        let state = stream.decode_state();
+        assert_eq!(state.buffered_bytes, 0);
+        assert!(state.awaiting_frame_header);
+        assert_eq!(state.partial_frame_remaining, None);
+
+        assert_eq!(stream.message().await.unwrap().unwrap().len(), LEN);
+        let state = stream.decode_state();
+        assert_eq!(state.buffered_bytes, 3);
+        assert!(state.awaiting_frame_header);
+        assert_eq!(state.partial_frame_remaining, None);
+
+        let poll = std::future::poll_fn(|cx| {
+            std::task::Poll::Ready(tokio_stream::Stream::poll_next(pin!(&mut stream), cx))
+        })
+        .await;
+        assert!(poll.is_pending());
+
+        let state = stream.decode_state();
+        assert_eq!(state.buffered_bytes, 4);
+        assert!(!state.awaiting_frame_header);
+        assert_eq!(state.partial_frame_remaining, Some(LEN - 4));
+        assert_eq!(stream.decode_state(), state);
+    }
+
+    #[tokio::test]
+    async fn bytes_stream_round_trips_frames() {
+        let frames: Vec<_> = response_with_messages(3, "0")
+            .into_bytes_stream()
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 3);
+        for frame in &frames {
+            let frame = frame.as_ref().unwrap();
+            assert_eq!(frame.len(), HEADER_SIZE + LEN);
+            assert_eq!(&frame[1..HEADER_SIZE], &(LEN as u32).to_be_bytes());
+        }
+
+        let stream = Streaming::from_bytes_stream(
+            MockDecoder::default(),
+            tokio_stream::iter(frames),
+            None,
+            None,
+        );
+        let messages: Vec<_> = stream.collect().await;
+
+        assert_eq!(messages.len(), 3);
+        assert!(messages
+            .iter()
+            .all(|msg| msg.as_ref().unwrap().len() == LEN));
+    }
+
+    #[tokio::test]
+    async fn bytes_stream_propagates_error() {
+        let frames: Vec<_> = response_with_messages(1, "14")
+            .into_bytes_stream()
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].is_ok());
+        assert_eq!(frames[1].as_ref().unwrap_err().code(), Code::Unavailable);
+    }
+
+    #[cfg(feature = "channel")]
+    #[tokio::test]
+    async fn idle_timeout_elapses_on_stalled_stream() {
+        let mut buf = BytesMut::new();
+        buf.put_u8(0);
+        buf.put_u32(LEN as u32);
+        buf.put(&[0u8; LEN][..]);
+
+        let frames =
+            tokio_stream::iter(vec![Ok::<_, Status>(http_body::Frame::data(buf.freeze()))])
+                .chain(tokio_stream::pending());
+        let body = http_body_util::StreamBody::new(frames);
+
+        let mut stream = Streaming::new_response(
+            MockDecoder::default(),
+            body,
+            http::StatusCode::OK,
+            None,
+            None,
+        )
+        .with_idle_timeout(std::time::Duration::from_millis(50));
+
+        assert!(stream.message().await.unwrap().is_some());
+        let status = stream.message().await.unwrap_err();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(stream.message().await.unwrap().is_none());
+    }
+
+    #[cfg(feature = "channel")]
+    #[tokio::test]
+    async fn idle_timeout_restarts_when_bytes_arrive() {
+        use std::time::Duration;
+
+        let mut buf = BytesMut::new();
+        buf.put_u8(0);
+        buf.put_u32(LEN as u32);
+        buf.put(&[0u8; LEN][..]);
+        let buf = buf.freeze();
+
+        // The message trickles in over 120ms, never stalling for the 50ms timeout.
+        let chunks = (0..4).map(move |i| buf.slice(i * buf.len() / 4..(i + 1) * buf.len() / 4));
+        let frames = futures_util::stream::unfold(chunks, |mut chunks| async move {
+            let chunk = chunks.next()?;
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            Some((Ok::<_, Status>(http_body::Frame::data(chunk)), chunks))
+        })
+        .chain(tokio_stream::pending());
+        let body = http_body_util::StreamBody::new(frames);
+
+        let mut stream = Streaming::new_response(
+            MockDecoder::default(),
+            body,
+            http::StatusCode::OK,
+            None,
+            None,
+        )
+        .with_idle_timeout(Duration::from_millis(50));
+
+        assert!(stream.message().await.unwrap().is_some());
+        // The caller taking longer than the timeout before waiting again doesn't count.
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let start = std::time::Instant::now();
+        let status = stream.message().await.unwrap_err();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+
+    fn response_with_trailing_garbage() -> Streaming<Vec<u8>> {
+        let mut buf = BytesMut::new();
+        buf.put_u8(0);
+        buf.put_u32(LEN as u32);
+        buf.put(&[0u8; LEN][..]);
+        buf.put(&b"\xffgarbage"[..]);
+
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("grpc-status", "0".parse().unwrap());
+        trailers.insert("x-done", "yes".parse().unwrap());
+
+        let frames = vec![
+            Ok::<_, Status>(http_body::Frame::data(buf.freeze())),
+            Ok(http_body::Frame::data(bytes::Bytes::from_static(b"more"))),
+            Ok(http_body::Frame::trailers(trailers)),
+        ];
+        let body = http_body_util::StreamBody::new(tokio_stream::iter(frames));
+
+        Streaming::new_response(
+            MockDecoder::default(),
+            body,
+            http::StatusCode::OK,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn trailing_garbage_is_rejected() {
+        let mut stream = response_with_trailing_garbage();
+
+        assert!(stream.message().await.unwrap().is_some());
+        let status = stream.trailers().await.unwrap_err();
+        assert_eq!(status.code(), Code::Internal);
+    }
+
+    #[tokio::test]
+    async fn trailers_skipping_data_ignores_trailing_garbage() {
+        let mut stream = response_with_trailing_garbage();
+
+        assert!(stream.message().await.unwrap().is_some());
+        let trailers = stream.trailers_skipping_data().await.unwrap().unwrap();
+        assert_eq!(trailers.get("x-done").unwrap(), "yes");
+    }
+
+    #[tokio::test]
+    async fn trailers_skipping_data_keeps_error_status() {
+        let mut stream = response_with_messages(1, "14");
+
+        assert!(stream.message().await.unwrap().is_some());
+        let status = stream.trailers_skipping_data().await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn from_messages_ends_with_trailers() {
+        let mut trailers = crate::metadata::MetadataMap::new();
+        trailers.insert("x-trailer", "yes".parse().unwrap());
+
+        let mut stream =
+            Streaming::from_messages_with_trailers([Ok(1u32), Ok(2)], trailers.clone());
+        assert_eq!(stream.message().await.unwrap(), Some(1));
+        assert_eq!(stream.message().await.unwrap(), Some(2));
+        assert_eq!(stream.message().await.unwrap(), None);
+        let received = stream.trailers().await.unwrap().unwrap();
+        assert_eq!(received.get("x-trailer").unwrap(), "yes");
+
+        let mut stream = Streaming::from_messages_with_trailers(
+            [Ok(1u32), Err(Status::aborted("stop")), Ok(2)],
+            trailers,
+        );
+        assert_eq!(stream.message().await.unwrap(), Some(1));
+        let status = stream.message().await.unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+        assert_eq!(status.metadata().get("x-trailer").unwrap(), "yes");
+        assert_eq!(stream.message().await.unwrap(), None);
+    }
+
+    #[cfg(feature = "test-util")]
+    #[tokio::test]
+    async fn count_messages_propagates_errors() {
+        let stream = Streaming::from_messages([Ok(1u32), Ok(2), Ok(3)]);
+        assert_eq!(stream.count_messages().await.unwrap(), 3);
+
+        let stream = Streaming::from_messages([Ok(1u32), Err(Status::aborted("stop"))]);
+        let status = stream.count_messages().await.unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+    }
+}
@@ -1,15 +1,23 @@
 use super::compression::{
-    compress, CompressionEncoding, CompressionSettings, SingleMessageCompressionOverride,
+    compress, CompressionEncoding, CompressionPolicy, CompressionSettings,
+    SingleMessageCompressionOverride,
 };
-use super::{BufferSettings, EncodeBuf, Encoder, DEFAULT_MAX_SEND_MESSAGE_SIZE, HEADER_SIZE};
-use crate::Status;
+use super::{
+    EncodeBuf, Encoder, DEFAULT_MAX_SEND_MESSAGE_SIZE, HEADER_SIZE, TERMINAL_METADATA_FLAG,
+};
+use crate::{extensions::MessageSizeDefaults, metadata::MetadataMap, Status};
 use bytes::{BufMut, Bytes, BytesMut};
 use http::HeaderMap;
 use http_body::{Body, Frame};
 use pin_project::pin_project;
 use std::{
+    fmt,
     pin::Pin,
-    task::{ready, Context, Poll},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{ready, Context, Poll, Waker},
 };
 use tokio_stream::{Stream, StreamExt};
 
@@ -22,6 +30,29 @@ pub fn encode_server<T, U>(
     compression_override: SingleMessageCompressionOverride,
     max_message_size: Option<usize>,
 ) -> EncodeBody<impl Stream<Item = Result<Bytes, Status>>>
+where
+    T: Encoder<Error = Status>,
+    U: Stream<Item = Result<T::Item, Status>>,
+{
+    encode_server_with_policy(
+        encoder,
+        source,
+        compression_encoding,
+        compression_override,
+        CompressionPolicy::default(),
+        max_message_size,
+    )
+}
+
+/// Like [`encode_server`], but decides which messages to compress with `compression_policy`.
+pub fn encode_server_with_policy<T, U>(
+    encoder: T,
+    source: U,
+    compression_encoding: Option<CompressionEncoding>,
+    compression_override: SingleMessageCompressionOverride,
+    compression_policy: CompressionPolicy,
+    max_message_size: Option<usize>,
+) -> EncodeBody<impl Stream<Item = Result<Bytes, Status>>>
 where
     T: Encoder<Error = Status>,
     U: Stream<Item = Result<T::Item, Status>>,
@@ -31,7 +62,9 @@ where
         source.fuse(),
         compression_encoding,
         compression_override,
+        compression_policy,
         max_message_size,
+        None,
     );
 
     EncodeBody::new_server(stream)
@@ -48,15 +81,67 @@ pub fn encode_client<T, U>(
 where
     T: Encoder<Error = Status>,
     U: Stream<Item = T::Item>,
+{
+    encode_client_with_policy(
+        encoder,
+        source,
+        compression_encoding,
+        CompressionPolicy::default(),
+        max_message_size,
+    )
+}
+
+/// Like [`encode_client`], but decides which messages to compress with `compression_policy`.
+pub fn encode_client_with_policy<T, U>(
+    encoder: T,
+    source: U,
+    compression_encoding: Option<CompressionEncoding>,
+    compression_policy: CompressionPolicy,
+    max_message_size: Option<usize>,
+) -> EncodeBody<impl Stream<Item = Result<Bytes, Status>>>
+where
+    T: Encoder<Error = Status>,
+    U: Stream<Item = T::Item>,
+{
+    encode_client_results(
+        encoder,
+        source.map(Ok),
+        compression_encoding,
+        compression_policy,
+        max_message_size,
+        None,
+        None,
+    )
+}
+
+/// Like [encode_client], but an error status in `source` aborts the request stream.
+///
+/// `buffer_size` overrides the initial buffer capacity from the encoder's buffer settings, and
+/// `size_defaults` provides the limit when `max_message_size` is not set.
+pub(crate) fn encode_client_results<T, U>(
+    encoder: T,
+    source: U,
+    compression_encoding: Option<CompressionEncoding>,
+    compression_policy: CompressionPolicy,
+    max_message_size: Option<usize>,
+    buffer_size: Option<usize>,
+    size_defaults: Option<MessageSizeDefaults>,
+) -> EncodeBody<impl Stream<Item = Result<Bytes, Status>>>
+where
+    T: Encoder<Error = Status>,
+    U: Stream<Item = Result<T::Item, Status>>,
 {
     let stream = EncodedBytes::new(
         encoder,
-        source.fuse().map(Ok),
+        source.fuse(),
         compression_encoding,
         SingleMessageCompressionOverride::default(),
+        compression_policy,
         max_message_size,
-    );
-    EncodeBody::new_client(stream)
+        buffer_size,
+    )
+    .with_size_defaults(size_defaults.clone());
+    EncodeBody::new_client(stream, max_message_size).with_size_defaults(size_defaults)
 }
 
 /// Combinator for efficient encoding of messages into reasonably sized buffers.
@@ -75,9 +160,13 @@ where
     source: U,
     encoder: T,
     compression_encoding: Option<CompressionEncoding>,
+    compression_policy: CompressionPolicy,
     max_message_size: Option<usize>,
+    size_defaults: Option<MessageSizeDefaults>,
     buf: BytesMut,
     uncompression_buf: BytesMut,
+    /// The end of the last message, sent after the frame in `buf`.
+    zero_copy: Option<Bytes>,
     error: Option<Status>,
 }
 
@@ -92,10 +181,12 @@ where
         source: U,
         compression_encoding: Option<CompressionEncoding>,
         compression_override: SingleMessageCompressionOverride,
+        compression_policy: CompressionPolicy,
         max_message_size: Option<usize>,
+        buffer_size: Option<usize>,
     ) -> Self {
-        let buffer_settings = encoder.buffer_settings();
-        let buf = BytesMut::with_capacity(buffer_settings.buffer_size);
+        let buffer_size = buffer_size.unwrap_or(encoder.buffer_settings().buffer_size);
+        let buf = BytesMut::with_capacity(buffer_size);
 
         let compression_encoding =
             if compression_override == SingleMessageCompressionOverride::Disable {
@@ -105,7 +196,7 @@ where
             };
 
         let uncompression_buf = if compression_encoding.is_some() {
-            BytesMut::with_capacity(buffer_settings.buffer_size)
+            BytesMut::with_capacity(buffer_size)
         } else {
             BytesMut::new()
         };
@@ -114,12 +205,20 @@ where
             source,
             encoder,
             compression_encoding,
+            compression_policy,
             max_message_size,
+            size_defaults: None,
             buf,
             uncompression_buf,
+            zero_copy: None,
             error: None,
         }
     }
+
+    fn with_size_defaults(mut self, size_defaults: Option<MessageSizeDefaults>) -> Self {
+        self.size_defaults = size_defaults;
+        self
+    }
 }
 
 impl<T, U> Stream for EncodedBytes<T, U>
@@ -134,12 +233,24 @@ where
             mut source,
             encoder,
             compression_encoding,
+            compression_policy,
             max_message_size,
+            size_defaults,
             buf,
             uncompression_buf,
+            zero_copy,
             error,
         } = self.project();
         let buffer_settings = encoder.buffer_settings();
+        let max_message_size = max_message_size.or_else(|| {
+            size_defaults
+                .as_ref()
+                .and_then(MessageSizeDefaults::max_encoding_message_size)
+        });
+
+        if let Some(bytes) = zero_copy.take() {
+            return Poll::Ready(Some(Ok(bytes)));
+        }
 
         if let Some(status) = error.take() {
             return Poll::Ready(Some(Err(status)));
@@ -157,16 +268,22 @@ where
                     return Poll::Ready(Some(Ok(buf.split_to(buf.len()).freeze())));
                 }
                 Poll::Ready(Some(Ok(item))) => {
-                    if let Err(status) = encode_item(
+                    match encode_item(
                         encoder,
                         buf,
                         uncompression_buf,
                         *compression_encoding,
-                        *max_message_size,
-                        buffer_settings,
+                        *compression_policy,
+                        max_message_size,
                         item,
                     ) {
-                        return Poll::Ready(Some(Err(status)));
+                        Ok(Some(bytes)) => {
+                            // The frame ends with bytes that are not in `buf`, send them next.
+                            *zero_copy = Some(bytes);
+                            return Poll::Ready(Some(Ok(buf.split_to(buf.len()).freeze())));
+                        }
+                        Ok(None) => {}
+                        Err(status) => return Poll::Ready(Some(Err(status))),
                     }
 
                     if buf.len() >= buffer_settings.yield_threshold {
@@ -185,18 +302,52 @@ where
     }
 }
 
+/// Encodes `item` into a single frame, with the same bytes [`EncodedBytes`] would yield for it.
+#[allow(clippy::result_large_err)]
+pub(crate) fn encode_frame<T>(
+    encoder: &mut T,
+    item: T::Item,
+    compression_encoding: Option<CompressionEncoding>,
+    compression_policy: CompressionPolicy,
+    max_message_size: Option<usize>,
+    buffer_size: Option<usize>,
+) -> Result<Bytes, Status>
+where
+    T: Encoder<Error = Status>,
+{
+    let buffer_size = buffer_size.unwrap_or(encoder.buffer_settings().buffer_size);
+    let mut buf = BytesMut::with_capacity(buffer_size);
+    let mut uncompression_buf = BytesMut::new();
+
+    let zero_copy = encode_item(
+        encoder,
+        &mut buf,
+        &mut uncompression_buf,
+        compression_encoding,
+        compression_policy,
+        max_message_size,
+        item,
+    )?;
+    if let Some(end) = zero_copy {
+        buf.extend_from_slice(&end);
+    }
+
+    Ok(buf.freeze())
+}
+
 fn encode_item<T>(
     encoder: &mut T,
     buf: &mut BytesMut,
     uncompression_buf: &mut BytesMut,
-    compression_encoding: Option<CompressionEncoding>,
+    mut compression_encoding: Option<CompressionEncoding>,
+    compression_policy: CompressionPolicy,
     max_message_size: Option<usize>,
-    buffer_settings: BufferSettings,
     item: T::Item,
-) -> Result<(), Status>
+) -> Result<Option<Bytes>, Status>
 where
     T: Encoder<Error = Status>,
 {
+    let buffer_settings = encoder.buffer_settings();
     let offset = buf.len();
 
     buf.reserve(HEADER_SIZE);
@@ -204,41 +355,64 @@ where
         buf.advance_mut(HEADER_SIZE);
     }
 
+    let mut zero_copy = None;
+
     if let Some(encoding) = compression_encoding {
         uncompression_buf.clear();
 
-        encoder
-            .encode(item, &mut EncodeBuf::new(uncompression_buf))
+        let end = encoder
+            .encode_zero_copy(item, &mut EncodeBuf::new(uncompression_buf))
             .map_err(|err| Status::internal(format!("Error encoding: {}", err)))?;
+        if let Some(end) = end {
+            uncompression_buf.extend_from_slice(&end);
+        }
 
         let uncompressed_len = uncompression_buf.len();
-
-        compress(
-            CompressionSettings {
-                encoding,
-                buffer_growth_interval: buffer_settings.buffer_size,
-            },
-            uncompression_buf,
-            buf,
-            uncompressed_len,
-        )
-        .map_err(|err| Status::internal(format!("Error compressing: {}", err)))?;
+        let limit = max_message_size.unwrap_or(DEFAULT_MAX_SEND_MESSAGE_SIZE);
+
+        if compression_policy.should_compress(uncompressed_len, limit) {
+            compress(
+                CompressionSettings {
+                    encoding,
+                    buffer_growth_interval: buffer_settings.buffer_size,
+                },
+                uncompression_buf,
+                buf,
+                uncompressed_len,
+            )
+            .map_err(|err| Status::internal(format!("Error compressing: {}", err)))?;
+        } else {
+            buf.extend_from_slice(uncompression_buf);
+            compression_encoding = None;
+        }
     } else {
-        encoder
-            .encode(item, &mut EncodeBuf::new(buf))
-            .map_err(|err| Status::internal(format!("Error encoding: {}", err)))?;
+        zero_copy = encoder
+            .encode_zero_copy(item, &mut EncodeBuf::new(buf))
+            .map_err(|err| Status::internal(format!("Error encoding: {}", err)))?
+            .filter(|end| !end.is_empty());
     }
 
     // now that we know length, we can write the header
-    finish_encoding(compression_encoding, max_message_size, &mut buf[offset..])
+    let zero_copy_len = zero_copy.as_ref().map_or(0, Bytes::len);
+    finish_encoding(
+        compression_encoding,
+        max_message_size,
+        &mut buf[offset..],
+        zero_copy_len,
+    )?;
+
+    Ok(zero_copy)
 }
 
+/// Writes the header of the frame in `buf`, whose message is followed by `extra_len` bytes
+/// that are sent separately.
 fn finish_encoding(
     compression_encoding: Option<CompressionEncoding>,
     max_message_size: Option<usize>,
     buf: &mut [u8],
+    extra_len: usize,
 ) -> Result<(), Status> {
-    let len = buf.len() - HEADER_SIZE;
+    let len = buf.len() - HEADER_SIZE + extra_len;
     let limit = max_message_size.unwrap_or(DEFAULT_MAX_SEND_MESSAGE_SIZE);
     if len > limit {
         return Err(Status::out_of_range(format!(
@@ -261,6 +435,24 @@ fn finish_encoding(
     Ok(())
 }
 
+/// Encodes `metadata` as a terminal metadata frame, see [`TERMINAL_METADATA_FLAG`].
+fn encode_terminal_metadata(metadata: MetadataMap) -> Bytes {
+    let mut buf = BytesMut::with_capacity(HEADER_SIZE);
+    buf.put_u8(TERMINAL_METADATA_FLAG);
+    buf.put_u32(0);
+
+    for (name, value) in &metadata.into_sanitized_headers() {
+        buf.put_slice(name.as_str().as_bytes());
+        buf.put_slice(b": ");
+        buf.put_slice(value.as_bytes());
+        buf.put_slice(b"\r\n");
+    }
+
+    let len = u32::try_from(buf.len() - HEADER_SIZE).expect("metadata is smaller than 4GB");
+    (&mut buf[1..HEADER_SIZE]).put_u32(len);
+    buf.freeze()
+}
+
 #[derive(Debug)]
 enum Role {
     Client,
@@ -281,16 +473,30 @@ struct EncodeState {
     error: Option<Status>,
     role: Role,
     is_end_stream: bool,
+    /// Sent once the client request stream has ended successfully.
+    terminal_frame: Option<TerminalFrame>,
+    /// Only send `terminal_frame` if this is set by the time the request stream ends.
+    terminal_frame_acknowledged: Option<Arc<AtomicBool>>,
+    /// Limits the size of `terminal_frame`, like the size of the messages.
+    max_message_size: Option<usize>,
+    size_defaults: Option<MessageSizeDefaults>,
+    /// Pauses the body while too many of its bytes are held by the transport.
+    inflight: Option<InflightBytes>,
 }
 
 impl<S> EncodeBody<S> {
-    fn new_client(inner: S) -> Self {
+    fn new_client(inner: S, max_message_size: Option<usize>) -> Self {
         Self {
             inner,
             state: EncodeState {
                 error: None,
                 role: Role::Client,
                 is_end_stream: false,
+                terminal_frame: None,
+                terminal_frame_acknowledged: None,
+                max_message_size,
+                size_defaults: None,
+                inflight: None,
             },
         }
     }
@@ -302,12 +508,163 @@ impl<S> EncodeBody<S> {
                 error: None,
                 role: Role::Server,
                 is_end_stream: false,
+                terminal_frame: None,
+                terminal_frame_acknowledged: None,
+                max_message_size: None,
+                size_defaults: None,
+                inflight: None,
             },
         }
     }
+
+    /// End the request stream with a terminal metadata frame carrying `metadata`.
+    pub(crate) fn with_terminal_metadata(mut self, metadata: MetadataMap) -> Self {
+        self.state.terminal_frame =
+            Some(TerminalFrame::Encoded(encode_terminal_metadata(metadata)));
+        self
+    }
+
+    /// End the request stream with a terminal metadata frame carrying the metadata returned by
+    /// `trailers`, which is called once the stream has ended.
+    pub(crate) fn with_outgoing_trailers(mut self, trailers: OutgoingTrailers) -> Self {
+        self.state.terminal_frame = Some(TerminalFrame::Deferred(trailers));
+        self
+    }
+
+    fn with_size_defaults(mut self, size_defaults: Option<MessageSizeDefaults>) -> Self {
+        self.state.size_defaults = size_defaults;
+        self
+    }
+
+    /// Only send the terminal metadata frame if the server acknowledged that it reads it, by
+    /// setting `acknowledged` before the request stream ends.
+    pub(crate) fn with_terminal_frame_acknowledged(
+        mut self,
+        acknowledged: Arc<AtomicBool>,
+    ) -> Self {
+        self.state.terminal_frame_acknowledged = Some(acknowledged);
+        self
+    }
+
+    /// Stop producing data while `max` bytes or more yielded by the body are still alive.
+    pub(crate) fn with_max_inflight_bytes(mut self, max: usize) -> Self {
+        self.state.inflight = Some(InflightBytes::new(max));
+        self
+    }
+}
+
+/// Provides the metadata of the terminal metadata frame once the request stream has ended.
+pub(crate) type OutgoingTrailers = Arc<dyn Fn() -> MetadataMap + Send + Sync>;
+
+/// The terminal metadata frame of a request stream, see [`TERMINAL_METADATA_FLAG`].
+enum TerminalFrame {
+    Encoded(Bytes),
+    Deferred(OutgoingTrailers),
+}
+
+impl TerminalFrame {
+    fn into_bytes(self) -> Bytes {
+        match self {
+            TerminalFrame::Encoded(frame) => frame,
+            TerminalFrame::Deferred(trailers) => encode_terminal_metadata(trailers()),
+        }
+    }
+}
+
+impl fmt::Debug for TerminalFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerminalFrame::Encoded(frame) => f.debug_tuple("Encoded").field(frame).finish(),
+            TerminalFrame::Deferred(_) => f.debug_tuple("Deferred").field(&"Fn").finish(),
+        }
+    }
+}
+
+/// Counts the bytes yielded by an [`EncodeBody`] that the transport has not released yet.
+///
+/// The transport drops the data of a frame once it has written it to the connection, which for
+/// HTTP/2 only happens when the flow control window allows it. The bytes still alive are therefore
+/// an approximation of those sent but not acknowledged by the peer, which are not exposed by the
+/// transport.
+#[derive(Debug)]
+struct InflightBytes {
+    shared: Arc<InflightShared>,
+    max: usize,
+}
+
+#[derive(Debug, Default)]
+struct InflightShared {
+    bytes: AtomicUsize,
+    waker: Mutex<Option<Waker>>,
+}
+
+/// Data yielded by an [`EncodeBody`], counted as in flight until dropped.
+struct CountedBytes {
+    bytes: Bytes,
+    shared: Arc<InflightShared>,
+}
+
+impl InflightBytes {
+    fn new(max: usize) -> Self {
+        Self {
+            shared: Arc::default(),
+            max,
+        }
+    }
+
+    /// Ready once the bytes in flight are below the maximum.
+    fn poll_capacity(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.shared.bytes.load(Ordering::Acquire) < self.max {
+            return Poll::Ready(());
+        }
+
+        // Check again after registering, in case the bytes were released in the meantime.
+        *self.shared.waker.lock().unwrap() = Some(cx.waker().clone());
+        if self.shared.bytes.load(Ordering::Acquire) < self.max {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn track(&self, bytes: Bytes) -> Bytes {
+        self.shared.bytes.fetch_add(bytes.len(), Ordering::AcqRel);
+        Bytes::from_owner(CountedBytes {
+            bytes,
+            shared: self.shared.clone(),
+        })
+    }
+}
+
+impl AsRef<[u8]> for CountedBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for CountedBytes {
+    fn drop(&mut self) {
+        self.shared
+            .bytes
+            .fetch_sub(self.bytes.len(), Ordering::AcqRel);
+        if let Some(waker) = self.shared.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
 }
 
 impl EncodeState {
+    fn take_terminal_frame(&mut self) -> Option<Bytes> {
+        let frame = self.terminal_frame.take()?;
+        if let Some(acknowledged) = &self.terminal_frame_acknowledged {
+            if !acknowledged.load(Ordering::Acquire) {
+                tracing::debug!("server didn't acknowledge terminal metadata, not sending it");
+                return None;
+            }
+        }
+        Some(frame.into_bytes())
+    }
+
     fn trailers(&mut self) -> Option<Result<HeaderMap, Status>> {
         match self.role {
             Role::Client => None,
@@ -344,8 +701,15 @@ where
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
         let self_proj = self.project();
+        if let Some(inflight) = &self_proj.state.inflight {
+            ready!(inflight.poll_capacity(cx));
+        }
+
         match ready!(self_proj.inner.poll_next(cx)) {
-            Some(Ok(d)) => Some(Ok(Frame::data(d))).into(),
+            Some(Ok(d)) => match &self_proj.state.inflight {
+                Some(inflight) => Some(Ok(Frame::data(inflight.track(d)))).into(),
+                None => Some(Ok(Frame::data(d))).into(),
+            },
             Some(Err(status)) => match self_proj.state.role {
                 Role::Client => Some(Err(status)).into(),
                 Role::Server => {
@@ -353,11 +717,167 @@ where
                     Some(Ok(Frame::trailers(status.to_header_map()?))).into()
                 }
             },
-            None => self_proj
-                .state
-                .trailers()
-                .map(|t| t.map(Frame::trailers))
-                .into(),
+            None => match self_proj.state.take_terminal_frame() {
+                Some(frame) => {
+                    let len = frame.len() - HEADER_SIZE;
+                    let state = &self_proj.state;
+                    let limit = state
+                        .max_message_size
+                        .or_else(|| {
+                            state
+                                .size_defaults
+                                .as_ref()
+                                .and_then(MessageSizeDefaults::max_encoding_message_size)
+                        })
+                        .unwrap_or(DEFAULT_MAX_SEND_MESSAGE_SIZE);
+                    if len > limit {
+                        return Some(Err(Status::out_of_range(format!(
+                            "Error, terminal metadata frame too large: found {} bytes, the limit is: {} bytes",
+                            len, limit
+                        ))))
+                        .into();
+                    }
+                    Some(Ok(Frame::data(frame))).into()
+                }
+                None => self_proj
+                    .state
+                    .trailers()
+                    .map(|t| t.map(Frame::trailers))
+                    .into(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::BufferSettings;
+
+    struct UnitEncoder;
+
+    impl Encoder for UnitEncoder {
+        type Item = ();
+        type Error = Status;
+
+        fn encode(&mut self, _: (), _: &mut EncodeBuf<'_>) -> Result<(), Status> {
+            Ok(())
+        }
+
+        fn buffer_settings(&self) -> BufferSettings {
+            BufferSettings::new(1024, 4096)
         }
     }
+
+    fn encoded_bytes(
+        compression_encoding: Option<CompressionEncoding>,
+        buffer_size: Option<usize>,
+    ) -> EncodedBytes<UnitEncoder, impl Stream<Item = Result<(), Status>>> {
+        EncodedBytes::new(
+            UnitEncoder,
+            tokio_stream::empty(),
+            compression_encoding,
+            SingleMessageCompressionOverride::default(),
+            CompressionPolicy::default(),
+            None,
+            buffer_size,
+        )
+    }
+
+    #[test]
+    fn buffer_size_overrides_encoder_settings() {
+        let bytes = encoded_bytes(None, None);
+        assert!(bytes.buf.capacity() >= 1024);
+        assert!(bytes.buf.capacity() < 64 * 1024);
+
+        let bytes = encoded_bytes(None, Some(64 * 1024));
+        assert!(bytes.buf.capacity() >= 64 * 1024);
+        assert_eq!(bytes.uncompression_buf.capacity(), 0);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn buffer_size_applies_to_uncompressed_buffer() {
+        let bytes = encoded_bytes(Some(CompressionEncoding::Gzip), Some(64 * 1024));
+        assert!(bytes.uncompression_buf.capacity() >= 64 * 1024);
+    }
+
+    #[tokio::test]
+    async fn terminal_frame_is_limited_and_negotiated() {
+        use http_body_util::BodyExt;
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-checksum", "0123456789".parse().unwrap());
+        let body = |limit| {
+            encode_client_results(
+                UnitEncoder,
+                tokio_stream::empty(),
+                None,
+                CompressionPolicy::default(),
+                limit,
+                None,
+                None,
+            )
+            .with_terminal_metadata(metadata.clone())
+        };
+
+        let sent = body(None).collect().await.unwrap().to_bytes();
+        assert_eq!(sent[0], TERMINAL_METADATA_FLAG);
+
+        let status = body(Some(8)).collect().await.unwrap_err();
+        assert_eq!(status.code(), crate::Code::OutOfRange);
+
+        let acknowledged = Arc::new(AtomicBool::new(false));
+        let sent = body(None)
+            .with_terminal_frame_acknowledged(acknowledged.clone())
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        assert!(sent.is_empty());
+
+        acknowledged.store(true, Ordering::SeqCst);
+        let sent = body(None)
+            .with_terminal_frame_acknowledged(acknowledged)
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(sent[0], TERMINAL_METADATA_FLAG);
+    }
+
+    #[test]
+    fn pauses_while_too_many_bytes_are_in_flight() {
+        struct Woken(std::sync::atomic::AtomicBool);
+        impl std::task::Wake for Woken {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let woken = Arc::new(Woken(Default::default()));
+        let waker = Waker::from(woken.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let chunks = std::iter::repeat_with(|| Bytes::from(vec![0; 10]))
+            .take(3)
+            .map(Ok::<_, Status>);
+        let mut body =
+            std::pin::pin!(EncodeBody::new_client(tokio_stream::iter(chunks), None)
+                .with_max_inflight_bytes(15));
+        let mut next_chunk = |cx: &mut Context<'_>| match body.as_mut().poll_frame(cx) {
+            Poll::Ready(Some(Ok(frame))) => Some(frame.into_data().unwrap()),
+            Poll::Pending => None,
+            other => panic!("unexpected frame: {:?}", other),
+        };
+
+        let first = next_chunk(&mut cx).unwrap();
+        let _second = next_chunk(&mut cx).unwrap();
+        assert!(next_chunk(&mut cx).is_none());
+        assert!(!woken.0.load(Ordering::SeqCst));
+
+        drop(first);
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert_eq!(next_chunk(&mut cx).unwrap().len(), 10);
+    }
 }
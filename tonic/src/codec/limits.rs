@@ -0,0 +1,41 @@
+use super::{DEFAULT_MAX_RECV_MESSAGE_SIZE, DEFAULT_MAX_SEND_MESSAGE_SIZE};
+
+/// The message size limits of a client that doesn't set its own.
+///
+/// By default, decoded messages are limited to 4MB and encoded messages aren't limited.
+/// [`Grpc::with_default_limits`] changes these defaults for a client, and can be shared by the
+/// clients of an application through a [`GrpcConfig`]. The most specific limit applies:
+///
+/// 1. The limit set on the client itself, for example with
+///    [`Grpc::max_decoding_message_size`].
+/// 2. The limit set with [`Grpc::with_default_limits`].
+/// 3. The built-in default, which [`DefaultLimits::default`] returns.
+///
+/// ```
+/// # use tonic::{client::GrpcConfig, codec::DefaultLimits};
+/// let config = GrpcConfig::default().default_limits(DefaultLimits {
+///     max_decoding_message_size: 16 * 1024 * 1024,
+///     ..Default::default()
+/// });
+/// ```
+///
+/// [`Grpc::with_default_limits`]: crate::client::Grpc::with_default_limits
+/// [`Grpc::max_decoding_message_size`]: crate::client::Grpc::max_decoding_message_size
+/// [`GrpcConfig`]: crate::client::GrpcConfig
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DefaultLimits {
+    /// Limits the maximum size of a decoded message.
+    pub max_decoding_message_size: usize,
+    /// Limits the maximum size of an encoded message.
+    pub max_encoding_message_size: usize,
+}
+
+/// Decoded messages are limited to 4MB, encoded messages aren't limited.
+impl Default for DefaultLimits {
+    fn default() -> Self {
+        Self {
+            max_decoding_message_size: DEFAULT_MAX_RECV_MESSAGE_SIZE,
+            max_encoding_message_size: DEFAULT_MAX_SEND_MESSAGE_SIZE,
+        }
+    }
+}
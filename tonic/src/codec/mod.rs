@@ -7,18 +7,30 @@ mod buffer;
 pub(crate) mod compression;
 mod decode;
 mod encode;
+mod limits;
 #[cfg(feature = "prost")]
 mod prost;
+mod wrapped;
+mod zero_copy;
 
 use crate::Status;
-use std::io;
+use bytes::{Bytes, BytesMut};
+use std::{io, sync::Arc};
 
 pub use self::buffer::{DecodeBuf, EncodeBuf};
-pub use self::compression::{CompressionEncoding, EnabledCompressionEncodings};
-pub use self::decode::Streaming;
-pub use self::encode::{encode_client, encode_server, EncodeBody};
+pub use self::compression::{CompressionEncoding, CompressionPolicy, EnabledCompressionEncodings};
+pub(crate) use self::decode::StreamProgress;
+pub use self::decode::{DecodeFrames, DecodeSample, DecodeState, DecodeStats, Streaming};
+pub use self::encode::{
+    encode_client, encode_client_with_policy, encode_server, encode_server_with_policy, EncodeBody,
+};
+pub(crate) use self::encode::{encode_client_results, encode_frame, OutgoingTrailers};
+pub use self::limits::DefaultLimits;
 #[cfg(feature = "prost")]
 pub use self::prost::ProstCodec;
+pub(crate) use self::wrapped::DecodeTransform;
+pub use self::wrapped::{WrappedCodec, WrappedDecoder, WrappedEncoder};
+pub use self::zero_copy::{IntoBytes, ZeroCopyEncoder};
 
 /// Unless overridden, this is the buffer size used for encoding requests.
 /// This is spent per-rpc, so you may wish to adjust it. The default is
@@ -96,7 +108,28 @@ const HEADER_SIZE: usize =
     // data length
     std::mem::size_of::<u32>();
 
-// The default maximum uncompressed size in bytes for a message. Defaults to 4MB.
+/// The compression flag of the terminal metadata frame, a tonic extension to the gRPC protocol.
+///
+/// Sent by [`Grpc::client_streaming_with_terminal_metadata`] as the last frame of the request
+/// stream, once negotiated with the [`TERMINAL_METADATA_HEADER`] header. The frame has the usual
+/// 5 byte prefix, with this flag instead of the compression flag, followed by the metadata
+/// entries as `name: value\r\n` lines. Binary values are base64 encoded. Like messages, the
+/// frame is subject to the maximum encoding and decoding message sizes.
+///
+/// [`Grpc::client_streaming_with_terminal_metadata`]: crate::client::Grpc::client_streaming_with_terminal_metadata
+pub const TERMINAL_METADATA_FLAG: u8 = 0x80;
+
+/// The header negotiating terminal metadata frames, see [`TERMINAL_METADATA_FLAG`].
+///
+/// Clients offer to send the frame with this request header. Servers that read it, including
+/// tonic servers, acknowledge the offer with this response header. Clients only send the frame
+/// to a server that acknowledged it: in the response headers of the call, received before the
+/// request stream ended, or, on a [`Channel`], on an earlier call sent over the same connection.
+///
+/// [`Channel`]: crate::transport::Channel
+pub const TERMINAL_METADATA_HEADER: &str = "tonic-terminal-metadata";
+
+// The built-in maximum uncompressed size in bytes for a message, 4MB. See `DefaultLimits`.
 const DEFAULT_MAX_RECV_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
 const DEFAULT_MAX_SEND_MESSAGE_SIZE: usize = usize::MAX;
 
@@ -116,6 +149,66 @@ pub trait Codec {
     fn encoder(&mut self) -> Self::Encoder;
     /// Fetch the decoder.
     fn decoder(&mut self) -> Self::Decoder;
+
+    /// The content subtype of the messages of this codec, such as `proto` for
+    /// `application/grpc+proto`.
+    ///
+    /// Clients fail calls whose response declares a different subtype with an `Internal` status,
+    /// which catches servers using another codec before their messages get decoded. Responses
+    /// without a subtype are accepted unless [`Grpc::with_strict_content_subtype`] is enabled.
+    /// Defaults to `None`, which skips the check.
+    ///
+    /// [`Grpc::with_strict_content_subtype`]: crate::client::Grpc::with_strict_content_subtype
+    const CONTENT_SUBTYPE: Option<&'static str> = None;
+
+    /// The compression encoding that suits the messages of this codec best.
+    ///
+    /// Clients compress requests with it when no encoding was chosen with
+    /// [`Grpc::send_compressed`], which always takes precedence. The encoding is only used if it
+    /// was also enabled with [`Grpc::accept_compressed`], so that it is advertised in the
+    /// `grpc-accept-encoding` header of the requests. Defaults to `None`, which leaves requests
+    /// uncompressed.
+    ///
+    /// [`Grpc::send_compressed`]: crate::client::Grpc::send_compressed
+    /// [`Grpc::accept_compressed`]: crate::client::Grpc::accept_compressed
+    fn preferred_compression(&self) -> Option<CompressionEncoding> {
+        None
+    }
+
+    /// Transform the bytes of every message encoded by this codec with `f`.
+    ///
+    /// `f` gets the bytes of a single message, before compression and the gRPC framing are
+    /// applied, which makes this suitable for encrypting or signing messages on top of any
+    /// codec. Use [`Codec::wrap_decoder`] for the inverse transform.
+    ///
+    /// ```rust
+    /// # use tonic::codec::{Codec, ProstCodec};
+    /// # use bytes::Bytes;
+    /// # fn encrypt(message: Bytes) -> Bytes { message }
+    /// # fn decrypt(message: Bytes) -> Bytes { message }
+    /// let codec = ProstCodec::<String, String>::default()
+    ///     .wrap_encoder(encrypt)
+    ///     .wrap_decoder(decrypt);
+    /// ```
+    fn wrap_encoder<F>(self, f: F) -> WrappedCodec<Self>
+    where
+        Self: Sized,
+        F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+    {
+        WrappedCodec::new(self).with_encode(Arc::new(f))
+    }
+
+    /// Transform the bytes of every message received by this codec with `f` before decoding it.
+    ///
+    /// `f` gets the bytes of a single message, after the gRPC framing and compression have been
+    /// removed. See [`Codec::wrap_encoder`].
+    fn wrap_decoder<F>(self, f: F) -> WrappedCodec<Self>
+    where
+        Self: Sized,
+        F: Fn(Bytes) -> Bytes + Send + Sync + 'static,
+    {
+        WrappedCodec::new(self).with_decode(DecodeTransform::Infallible(Arc::new(f)))
+    }
 }
 
 /// Encodes gRPC message types
@@ -131,12 +224,62 @@ pub trait Encoder {
     /// Encodes a message into the provided buffer.
     fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error>;
 
+    /// Encodes a message into the provided buffer, except for its end which is returned.
+    ///
+    /// The message is what is written to `dst` followed by the returned bytes. Unless the message
+    /// is compressed, the returned bytes are sent as is instead of being copied into the encode
+    /// buffer, which saves a copy for messages made mostly of a large binary field.
+    ///
+    /// Defaults to encoding the whole message with [`Encoder::encode`].
+    fn encode_zero_copy(
+        &mut self,
+        item: Self::Item,
+        dst: &mut EncodeBuf<'_>,
+    ) -> Result<Option<Bytes>, Self::Error> {
+        self.encode(item, dst).map(|()| None)
+    }
+
     /// Controls how tonic creates and expands encode buffers.
     fn buffer_settings(&self) -> BufferSettings {
         BufferSettings::default()
     }
 }
 
+/// A type-erased [`Encoder`].
+///
+/// Useful when the concrete encoder is only known at runtime, for example when selecting
+/// a codec from a registry. `BoxEncoder<M>` implements [`Encoder`] itself, so it can be
+/// used as the [`Codec::Encoder`] of a codec.
+pub type BoxEncoder<M> = Box<dyn Encoder<Item = M, Error = Status> + Send + 'static>;
+
+/// A type-erased [`Decoder`].
+///
+/// Useful when the concrete decoder is only known at runtime, for example when selecting
+/// a codec from a registry. `BoxDecoder<M>` implements [`Decoder`] itself, so it can be
+/// used as the [`Codec::Decoder`] of a codec.
+pub type BoxDecoder<M> = Box<dyn Decoder<Item = M, Error = Status> + Send + 'static>;
+
+impl<T: Encoder + ?Sized> Encoder for Box<T> {
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        (**self).encode(item, dst)
+    }
+
+    fn encode_zero_copy(
+        &mut self,
+        item: Self::Item,
+        dst: &mut EncodeBuf<'_>,
+    ) -> Result<Option<Bytes>, Self::Error> {
+        (**self).encode_zero_copy(item, dst)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        (**self).buffer_settings()
+    }
+}
+
 /// Decodes gRPC message types
 pub trait Decoder {
     /// The type that is decoded.
@@ -156,4 +299,36 @@ pub trait Decoder {
     fn buffer_settings(&self) -> BufferSettings {
         BufferSettings::default()
     }
+
+    /// Decode the next complete length-prefixed gRPC frame at the start of `src`.
+    ///
+    /// The frame is removed from `src` and the decoder is handed a view into it rather than a
+    /// copy of the message. Frames for which [`Decoder::decode`] returns `None` are skipped.
+    /// Returns `None` once `src` doesn't start with a complete frame, which is left in `src` so
+    /// that more bytes can be appended to it. Frames are expected to be uncompressed, a
+    /// compressed frame is an error.
+    ///
+    /// [`Streaming`] uses this to decode the uncompressed frames it receives together, decoders
+    /// can override it to decode them in a single pass. [`DecodeFrames`] iterates over the
+    /// frames it decodes.
+    fn decode_streaming(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        decode::decode_next_frame(self, src)
+    }
+}
+
+impl<T: Decoder + ?Sized> Decoder for Box<T> {
+    type Item = T::Item;
+    type Error = T::Error;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        (**self).decode(src)
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        (**self).buffer_settings()
+    }
+
+    fn decode_streaming(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        (**self).decode_streaming(src)
+    }
 }
@@ -11,9 +11,21 @@ pub struct ProstCodec<T, U> {
 }
 
 impl<T, U> ProstCodec<T, U> {
-    /// Configure a ProstCodec with encoder/decoder buffer settings. This is used to control
-    /// how memory is allocated and grows per RPC.
-    pub fn new() -> Self {
+    /// Create a codec encoding `T` and decoding `U`, same as [`ProstCodec::default`].
+    ///
+    /// Naming the message types here helps type inference where the codec is passed to a
+    /// function generic over [`Codec`]:
+    ///
+    /// ```
+    /// # use tonic::codec::{Codec, ProstCodec};
+    /// fn check<C: Codec>(_codec: C) {}
+    ///
+    /// check(ProstCodec::<String, u32>::new());
+    /// ```
+    ///
+    /// Use [`ProstCodec::raw_encoder`] and [`ProstCodec::raw_decoder`] to control how buffers are
+    /// allocated.
+    pub const fn new() -> Self {
         Self { _pd: PhantomData }
     }
 }
@@ -59,6 +71,8 @@ where
     type Encoder = ProstEncoder<T>;
     type Decoder = ProstDecoder<U>;
 
+    const CONTENT_SUBTYPE: Option<&'static str> = Some("proto");
+
     fn encoder(&mut self) -> Self::Encoder {
         ProstEncoder {
             _pd: PhantomData,
@@ -149,15 +163,17 @@ fn from_decode_error(error: prost::DecodeError) -> crate::Status {
 
 #[cfg(test)]
 mod tests {
-    use crate::codec::compression::SingleMessageCompressionOverride;
+    use crate::codec::compression::{CompressionPolicy, SingleMessageCompressionOverride};
     use crate::codec::{
-        encode_server, DecodeBuf, Decoder, EncodeBuf, Encoder, Streaming, HEADER_SIZE,
+        encode_server, encode_server_with_policy, BoxDecoder, BoxEncoder, DecodeBuf, Decoder,
+        EncodeBuf, Encoder, Streaming, HEADER_SIZE, TERMINAL_METADATA_FLAG,
     };
     use crate::Status;
     use bytes::{Buf, BufMut, BytesMut};
     use http_body::Body;
     use http_body_util::BodyExt as _;
     use std::pin::pin;
+    use tokio_stream::StreamExt;
 
     const LEN: usize = 10000;
     // The maximum uncompressed size in bytes for a message. Set to 2MB.
@@ -189,6 +205,84 @@ mod tests {
         assert_eq!(i, 1);
     }
 
+    #[tokio::test]
+    async fn boxed_roundtrip() {
+        let encoder: BoxEncoder<Vec<u8>> = Box::<MockEncoder>::default();
+        let decoder: BoxDecoder<Vec<u8>> = Box::<MockDecoder>::default();
+
+        let msg = vec![0u8; LEN];
+        let source = tokio_stream::iter(std::iter::once(Ok::<_, Status>(msg.clone())));
+
+        let body = encode_server(
+            encoder,
+            source,
+            None,
+            SingleMessageCompressionOverride::default(),
+            None,
+        );
+
+        let mut stream = Streaming::new_request(decoder, body, None, None);
+
+        let output_msg = stream.message().await.unwrap().expect("one message");
+        assert_eq!(output_msg, msg);
+        assert!(stream.message().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn terminal_metadata_is_last_frame() {
+        let mut metadata = crate::metadata::MetadataMap::new();
+        metadata.insert("x-checksum", "abc".parse().unwrap());
+        // Reserved headers aren't sent.
+        metadata.insert("grpc-status", "0".parse().unwrap());
+
+        let source = tokio_stream::iter(vec![vec![0u8; LEN]; 2]);
+        let body = crate::codec::encode_client(MockEncoder::default(), source, None, None)
+            .with_terminal_metadata(metadata);
+
+        let stream = Streaming::new_request(MockDecoder::default(), body, None, None);
+        let frames: Vec<_> = stream.into_bytes_stream().collect().await;
+
+        assert_eq!(frames.len(), 3);
+        assert!(frames[..2]
+            .iter()
+            .all(|frame| frame.as_ref().unwrap()[0] == 0));
+
+        let terminal = frames[2].as_ref().unwrap();
+        assert_eq!(terminal[0], TERMINAL_METADATA_FLAG);
+        assert_eq!(&terminal[HEADER_SIZE..], b"x-checksum: abc\r\n");
+    }
+
+    #[tokio::test]
+    async fn outgoing_trailers_are_computed_once_the_stream_ends() {
+        use std::sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        };
+
+        let sent = Arc::new(AtomicUsize::new(0));
+        let source = {
+            let sent = sent.clone();
+            tokio_stream::iter(vec![vec![0u8; LEN]; 2]).map(move |message| {
+                sent.fetch_add(1, Ordering::SeqCst);
+                message
+            })
+        };
+        let body = crate::codec::encode_client(MockEncoder::default(), source, None, None)
+            .with_outgoing_trailers(Arc::new(move || {
+                let mut metadata = crate::metadata::MetadataMap::new();
+                metadata.insert("x-sent", sent.load(Ordering::SeqCst).into());
+                metadata
+            }));
+
+        let stream = Streaming::new_request(MockDecoder::default(), body, None, None);
+        let frames: Vec<_> = stream.into_bytes_stream().collect().await;
+
+        assert_eq!(frames.len(), 3);
+        let terminal = frames[2].as_ref().unwrap();
+        assert_eq!(terminal[0], TERMINAL_METADATA_FLAG);
+        assert_eq!(&terminal[HEADER_SIZE..], b"x-sent: 2\r\n");
+    }
+
     #[tokio::test]
     async fn decode_max_message_size_exceeded() {
         let decoder = MockDecoder::default();
@@ -274,6 +368,41 @@ mod tests {
         assert!(body.is_end_stream());
     }
 
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn encode_compress_to_fit() {
+        use crate::codec::CompressionEncoding;
+
+        let policy = CompressionPolicy::exceeds_limit_fraction(0.5);
+
+        // Larger than the limit uncompressed, but compression brings it under.
+        let big = vec![0u8; MAX_MESSAGE_SIZE + 1];
+        // Under half of the limit, so it is sent uncompressed.
+        let small = vec![0u8; 1024];
+
+        for (msg, compressed) in [(big, true), (small, false)] {
+            let source = tokio_stream::iter(std::iter::once(Ok::<_, Status>(msg)));
+
+            let mut body = pin!(encode_server_with_policy(
+                MockEncoder::default(),
+                source,
+                Some(CompressionEncoding::Gzip),
+                SingleMessageCompressionOverride::default(),
+                policy,
+                Some(MAX_MESSAGE_SIZE),
+            ));
+
+            let data = body
+                .frame()
+                .await
+                .expect("at least one frame")
+                .expect("no error polling frame")
+                .into_data()
+                .expect("got data");
+            assert_eq!(data[0], compressed as u8);
+        }
+    }
+
     // skip on windows because CI stumbles over our 4GB allocation
     #[cfg(not(target_family = "windows"))]
     #[tokio::test]
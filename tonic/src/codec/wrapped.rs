@@ -0,0 +1,223 @@
+use super::{BufferSettings, Codec, CompressionEncoding, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use crate::Status;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::{fmt, sync::Arc};
+
+type Transform = Arc<dyn Fn(Bytes) -> Bytes + Send + Sync + 'static>;
+
+/// Transforms the bytes of each received message.
+#[derive(Clone)]
+pub(crate) enum DecodeTransform {
+    Infallible(Transform),
+    /// Fails the stream with the returned status.
+    Fallible(Arc<dyn Fn(Bytes) -> Result<Bytes, Status> + Send + Sync>),
+}
+
+/// A [`Codec`] that transforms the bytes of each message of another codec.
+///
+/// Created by [`Codec::wrap_encoder`] and [`Codec::wrap_decoder`]. The transform applies to the
+/// bytes of a single message, inside the gRPC framing: encoded messages are transformed before
+/// they are compressed and length-prefixed, received messages after they are decompressed and
+/// before they are decoded.
+#[derive(Clone)]
+pub struct WrappedCodec<C> {
+    inner: C,
+    encode: Option<Transform>,
+    decode: Option<DecodeTransform>,
+}
+
+impl<C> WrappedCodec<C> {
+    pub(crate) fn new(inner: C) -> Self {
+        Self {
+            inner,
+            encode: None,
+            decode: None,
+        }
+    }
+
+    pub(crate) fn with_encode(mut self, f: Transform) -> Self {
+        self.encode = Some(f);
+        self
+    }
+
+    pub(crate) fn with_decode(mut self, f: DecodeTransform) -> Self {
+        self.decode = Some(f);
+        self
+    }
+
+    /// Get a reference to the wrapped codec.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Consumes `self`, returning the wrapped codec.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Codec> Codec for WrappedCodec<C> {
+    type Encode = C::Encode;
+    type Decode = C::Decode;
+
+    type Encoder = WrappedEncoder<C::Encoder>;
+    type Decoder = WrappedDecoder<C::Decoder>;
+
+    const CONTENT_SUBTYPE: Option<&'static str> = C::CONTENT_SUBTYPE;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        WrappedEncoder {
+            inner: self.inner.encoder(),
+            f: self.encode.clone(),
+        }
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        WrappedDecoder::new(self.inner.decoder(), self.decode.clone())
+    }
+
+    fn preferred_compression(&self) -> Option<CompressionEncoding> {
+        self.inner.preferred_compression()
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for WrappedCodec<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WrappedCodec")
+            .field("inner", &self.inner)
+            .field("encode", &self.encode.is_some())
+            .field("decode", &self.decode.is_some())
+            .finish()
+    }
+}
+
+/// The [`Encoder`] of a [`WrappedCodec`].
+pub struct WrappedEncoder<E> {
+    inner: E,
+    f: Option<Transform>,
+}
+
+impl<E> Encoder for WrappedEncoder<E>
+where
+    E: Encoder<Error = Status>,
+{
+    type Item = E::Item;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        let Some(f) = &self.f else {
+            return self.inner.encode(item, dst);
+        };
+
+        let mut buf = BytesMut::new();
+        self.inner.encode(item, &mut EncodeBuf::new(&mut buf))?;
+        dst.put(f(buf.freeze()));
+        Ok(())
+    }
+
+    fn encode_zero_copy(
+        &mut self,
+        item: Self::Item,
+        dst: &mut EncodeBuf<'_>,
+    ) -> Result<Option<Bytes>, Self::Error> {
+        // The transform needs the whole message.
+        match &self.f {
+            Some(_) => self.encode(item, dst).map(|()| None),
+            None => self.inner.encode_zero_copy(item, dst),
+        }
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.inner.buffer_settings()
+    }
+}
+
+impl<E> fmt::Debug for WrappedEncoder<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WrappedEncoder").finish_non_exhaustive()
+    }
+}
+
+/// The [`Decoder`] of a [`WrappedCodec`].
+pub struct WrappedDecoder<D> {
+    inner: D,
+    f: Option<DecodeTransform>,
+}
+
+impl<D> WrappedDecoder<D> {
+    pub(crate) fn new(inner: D, f: Option<DecodeTransform>) -> Self {
+        Self { inner, f }
+    }
+}
+
+impl<D> Decoder for WrappedDecoder<D>
+where
+    D: Decoder<Error = Status>,
+{
+    type Item = D::Item;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(f) = &self.f else {
+            return self.inner.decode(src);
+        };
+
+        let message = src.copy_to_bytes(src.remaining());
+        let message = match f {
+            DecodeTransform::Infallible(f) => f(message),
+            DecodeTransform::Fallible(f) => f(message)?,
+        };
+        let len = message.len();
+        let mut buf = BytesMut::from(message);
+        self.inner.decode(&mut DecodeBuf::new(&mut buf, len))
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.inner.buffer_settings()
+    }
+}
+
+impl<D> fmt::Debug for WrappedDecoder<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WrappedDecoder").finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, feature = "prost"))]
+mod tests {
+    use super::*;
+    use crate::codec::ProstCodec;
+
+    fn reverse(message: Bytes) -> Bytes {
+        message.iter().rev().copied().collect()
+    }
+
+    fn encode<C: Codec>(codec: &mut C, item: C::Encode) -> BytesMut {
+        let mut buf = BytesMut::new();
+        codec
+            .encoder()
+            .encode(item, &mut EncodeBuf::new(&mut buf))
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn transforms_message_bytes() {
+        let mut plain = ProstCodec::<String, String>::default();
+        let mut wrapped = ProstCodec::<String, String>::default()
+            .wrap_encoder(reverse)
+            .wrap_decoder(reverse);
+
+        let message = "hello".to_string();
+        let expected = reverse(encode(&mut plain, message.clone()).freeze());
+        let mut encoded = encode(&mut wrapped, message.clone());
+        assert_eq!(encoded, expected);
+
+        let len = encoded.len();
+        let decoded = wrapped
+            .decoder()
+            .decode(&mut DecodeBuf::new(&mut encoded, len))
+            .unwrap();
+        assert_eq!(decoded, Some(message));
+    }
+}
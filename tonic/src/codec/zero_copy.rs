@@ -0,0 +1,138 @@
+use super::{BufferSettings, EncodeBuf, Encoder};
+use crate::Status;
+use bytes::{BufMut, Bytes};
+use std::marker::PhantomData;
+
+/// A message that is its own encoding.
+///
+/// Implemented by messages that already hold their encoded bytes, such as chunks of a file
+/// transferred with a custom codec, so that [`ZeroCopyEncoder`] can send them without a copy.
+pub trait IntoBytes {
+    /// Returns the encoded message.
+    fn into_bytes(self) -> Bytes;
+}
+
+impl IntoBytes for Bytes {
+    fn into_bytes(self) -> Bytes {
+        self
+    }
+}
+
+impl IntoBytes for Vec<u8> {
+    fn into_bytes(self) -> Bytes {
+        Bytes::from(self)
+    }
+}
+
+/// An [`Encoder`] that sends the bytes of [`IntoBytes`] messages without copying them.
+///
+/// Only the gRPC frame header is written to the encode buffer, the message itself is sent as
+/// is, unless it is compressed. See [`Encoder::encode_zero_copy`].
+#[derive(Debug, Clone, Default)]
+pub struct ZeroCopyEncoder<M> {
+    _pd: PhantomData<M>,
+    buffer_settings: BufferSettings,
+}
+
+impl<M> ZeroCopyEncoder<M> {
+    /// Get a new encoder with explicit buffer settings
+    pub fn new(buffer_settings: BufferSettings) -> Self {
+        Self {
+            _pd: PhantomData,
+            buffer_settings,
+        }
+    }
+}
+
+impl<M: IntoBytes> Encoder for ZeroCopyEncoder<M> {
+    type Item = M;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        dst.put(item.into_bytes());
+        Ok(())
+    }
+
+    fn encode_zero_copy(
+        &mut self,
+        item: Self::Item,
+        _dst: &mut EncodeBuf<'_>,
+    ) -> Result<Option<Bytes>, Self::Error> {
+        Ok(Some(item.into_bytes()))
+    }
+
+    fn buffer_settings(&self) -> BufferSettings {
+        self.buffer_settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{encode_client, HEADER_SIZE};
+    use http_body_util::BodyExt;
+
+    async fn chunks(
+        messages: Vec<Bytes>,
+        compression: Option<crate::codec::CompressionEncoding>,
+    ) -> Vec<Bytes> {
+        let mut body = std::pin::pin!(encode_client(
+            ZeroCopyEncoder::<Bytes>::default(),
+            tokio_stream::iter(messages),
+            compression,
+            None,
+        ));
+
+        let mut chunks = Vec::new();
+        while let Some(frame) = body.frame().await {
+            chunks.push(frame.unwrap().into_data().unwrap());
+        }
+        chunks
+    }
+
+    #[tokio::test]
+    async fn sends_messages_without_copying() {
+        let messages = vec![Bytes::from(vec![1; 1024]), Bytes::from(vec![2; 16])];
+        let chunks = chunks(messages.clone(), None).await;
+
+        assert_eq!(chunks.len(), 4);
+        for (header, (message, expected)) in chunks
+            .iter()
+            .step_by(2)
+            .zip(chunks.iter().skip(1).step_by(2).zip(&messages))
+        {
+            assert_eq!(header.len(), HEADER_SIZE);
+            assert_eq!(header[0], 0);
+            assert_eq!(&header[1..], &(expected.len() as u32).to_be_bytes());
+            assert_eq!(message.as_ptr(), expected.as_ptr());
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[tokio::test]
+    async fn compressed_messages_are_copied() {
+        use crate::codec::compression::{decompress, CompressionEncoding, CompressionSettings};
+
+        let message = Bytes::from(vec![1; 1024]);
+        let chunks = chunks(vec![message.clone()], Some(CompressionEncoding::Gzip)).await;
+
+        assert_eq!(chunks.len(), 1);
+        let frame = &chunks[0];
+        assert_eq!(frame[0], 1);
+
+        let mut compressed = bytes::BytesMut::from(&frame[HEADER_SIZE..]);
+        let mut decompressed = bytes::BytesMut::new();
+        let len = compressed.len();
+        decompress(
+            CompressionSettings {
+                encoding: CompressionEncoding::Gzip,
+                buffer_growth_interval: 8 * 1024,
+            },
+            &mut compressed,
+            &mut decompressed,
+            len,
+        )
+        .unwrap();
+        assert_eq!(decompressed, message);
+    }
+}
@@ -1,3 +1,8 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, OnceLock,
+};
+
 /// A gRPC Method info extension.
 #[derive(Debug, Clone)]
 pub struct GrpcMethod<'a> {
@@ -21,3 +26,82 @@ impl<'a> GrpcMethod<'a> {
         self.method
     }
 }
+
+/// The HTTP/2 stream ID a call runs on.
+///
+/// Transports that know the stream ID can insert this extension into the responses they return,
+/// it is then available through [`Response::stream_id`].
+///
+/// [`Response::stream_id`]: crate::Response::stream_id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Http2StreamId(u32);
+
+impl Http2StreamId {
+    /// Create a new `Http2StreamId` extension.
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    /// The numeric stream ID.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+/// The message size limits set on the [`Endpoint`] of a [`Channel`].
+///
+/// A [`Grpc`] client that doesn't limit the size of its messages itself inserts this slot into
+/// the extensions of its requests. The slot is shared by the clones of the client, and filled in
+/// by the first channel it is dispatched to, before the request body is encoded and the response
+/// decoded. Services other than [`Channel`] leave it empty, so the built-in limits apply.
+///
+/// [`Endpoint`]: crate::transport::Endpoint
+/// [`Channel`]: crate::transport::Channel
+/// [`Grpc`]: crate::client::Grpc
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MessageSizeDefaults(Arc<OnceLock<MessageSizeLimits>>);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct MessageSizeLimits {
+    pub(crate) max_decoding_message_size: Option<usize>,
+    pub(crate) max_encoding_message_size: Option<usize>,
+}
+
+impl MessageSizeDefaults {
+    #[cfg_attr(not(feature = "channel"), allow(dead_code))]
+    pub(crate) fn set(&self, limits: MessageSizeLimits) {
+        let _ = self.0.set(limits);
+    }
+
+    pub(crate) fn max_decoding_message_size(&self) -> Option<usize> {
+        self.0.get()?.max_decoding_message_size
+    }
+
+    pub(crate) fn max_encoding_message_size(&self) -> Option<usize> {
+        self.0.get()?.max_encoding_message_size
+    }
+}
+
+/// Whether the server handling a call reads terminal metadata frames, see
+/// [`TERMINAL_METADATA_HEADER`].
+///
+/// Inserted into the request extensions of the calls offering the frame, and shared with their
+/// request body, which only sends the frame if this is set by the time the request stream ends.
+/// The client sets it when the response headers of the call acknowledge the offer. A [`Channel`]
+/// also sets it when the call is sent on a connection whose server acknowledged an earlier call,
+/// so that an acknowledgement never carries over to another server.
+///
+/// [`TERMINAL_METADATA_HEADER`]: crate::codec::TERMINAL_METADATA_HEADER
+/// [`Channel`]: crate::transport::Channel
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TerminalMetadataAcknowledged(Arc<AtomicBool>);
+
+impl TerminalMetadataAcknowledged {
+    pub(crate) fn set(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn flag(&self) -> Arc<AtomicBool> {
+        self.0.clone()
+    }
+}
@@ -36,6 +36,16 @@
 //!   Not enabled by default.
 //! - `zstd`: Enables compressing requests, responses, and streams. Depends on [`zstd`].
 //!   Not enabled by default.
+//! - `serde`: Implements [`serde`] serialization for metadata values. Not enabled by default.
+//! - `test-util`: Enables [`Grpc::with_fault_injection`] to test how clients handle failures,
+//!   and [`Streaming::from_messages`] to fake streaming responses.
+//!   Not enabled by default.
+//!
+//! Building with `RUSTFLAGS="--cfg tonic_nightly"` on a nightly toolchain implements the unstable
+//! [`AsyncIterator`] trait for [`Streaming`]. This isn't a Cargo feature so that
+//! `--all-features` keeps building on stable, and it may change along with the unstable trait.
+//!
+//! [`AsyncIterator`]: https://doc.rust-lang.org/nightly/std/async_iter/trait.AsyncIterator.html
 //!
 //! # Structure
 //!
@@ -63,6 +73,7 @@
 //! Currently, both servers and clients can be configured to set the max message encoding and
 //! decoding size. This will ensure that an incoming gRPC message will not exhaust the systems
 //! memory. By default, the decoding message limit is `4MB` and the encoding limit is `usize::MAX`.
+//! The defaults of a client can be changed with [`client::Grpc::with_default_limits`].
 //!
 //! [gRPC]: https://grpc.io
 //! [`tonic`]: https://github.com/hyperium/tonic
@@ -71,6 +82,7 @@
 //! [`hyper`]: https://docs.rs/hyper
 //! [`tower`]: https://docs.rs/tower
 //! [`tonic-build`]: https://docs.rs/tonic-build
+//! [`Grpc::with_fault_injection`]: crate::client::Grpc::with_fault_injection
 //! [`tonic-examples`]: https://github.com/hyperium/tonic/tree/master/examples
 //! [`Codec`]: codec/trait.Codec.html
 //! [`Channel`]: transport/struct.Channel.html
@@ -82,6 +94,7 @@
 //! [`webpki-roots`]: https://docs.rs/webpki-roots
 //! [`flate2`]: https://docs.rs/flate2
 //! [`zstd`]: https://docs.rs/zstd
+//! [`serde`]: https://docs.rs/serde
 
 #![recursion_limit = "256"]
 #![warn(
@@ -98,6 +111,7 @@
 #![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
 #![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
+#![cfg_attr(tonic_nightly, feature(async_iterator))]
 
 pub mod body;
 pub mod client;
@@ -122,7 +136,7 @@ pub use async_trait::async_trait;
 
 #[doc(inline)]
 pub use codec::Streaming;
-pub use extensions::GrpcMethod;
+pub use extensions::{GrpcMethod, Http2StreamId};
 pub use http::Extensions;
 pub use request::{IntoRequest, IntoStreamingRequest, Request};
 pub use response::Response;
@@ -543,6 +543,47 @@ from_integers! {
     from_isize: isize => 20
 }
 
+/// Serializes the value as a string.
+///
+/// Fails if the value contains bytes that are not visible ASCII characters.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MetadataValue<Ascii> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.to_str().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(value)
+    }
+}
+
+/// Deserializes the value from a string.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MetadataValue<Ascii> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        MetadataValue::try_from(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serializes the value as its base64 encoded string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MetadataValue<Binary> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value =
+            std::str::from_utf8(self.as_encoded_bytes()).map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(value)
+    }
+}
+
+/// Deserializes the value from a base64 encoded string.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MetadataValue<Binary> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let decoded = base64::Engine::decode(&crate::util::base64::STANDARD, value)
+            .map_err(serde::de::Error::custom)?;
+        Ok(MetadataValue::from_bytes(&decoded))
+    }
+}
+
 #[cfg(test)]
 mod from_metadata_value_tests {
     use super::*;
@@ -961,3 +1002,36 @@ fn test_invalid_binary_value_hash() {
         assert_ne!(hash(valid), hash(invalid));
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use crate::metadata::{AsciiMetadataValue, BinaryMetadataValue};
+
+    #[test]
+    fn ascii_round_trip() {
+        let value = AsciiMetadataValue::from_static("hello world");
+
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"hello world\"");
+        assert_eq!(
+            serde_json::from_str::<AsciiMetadataValue>(&json).unwrap(),
+            value
+        );
+
+        assert!(serde_json::from_str::<AsciiMetadataValue>("\"bad\\n\"").is_err());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let value = BinaryMetadataValue::from_bytes(b"\x00\x01binary");
+
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"AAFiaW5hcnk\"");
+        assert_eq!(
+            serde_json::from_str::<BinaryMetadataValue>(&json).unwrap(),
+            value
+        );
+
+        assert!(serde_json::from_str::<BinaryMetadataValue>("\"not base64!\"").is_err());
+    }
+}
@@ -14,7 +14,10 @@ use tokio_rustls::rustls::pki_types::CertificateDer;
 use tokio_stream::Stream;
 
 /// A gRPC request and metadata from an RPC call.
-#[derive(Debug)]
+///
+/// Cloning a request copies its metadata and extensions, so a clone can be modified without
+/// affecting the original.
+#[derive(Debug, Clone)]
 pub struct Request<T> {
     metadata: MetadataMap,
     message: T,
@@ -157,7 +160,24 @@ impl<T> Request<T> {
         }
     }
 
-    pub(crate) fn from_http_parts(parts: http::request::Parts, message: T) -> Self {
+    /// Create a gRPC request from the parts of an HTTP request and a message.
+    ///
+    /// This is [`Request::from_http`] for middleware that has already split the HTTP request
+    /// with [`http::Request::into_parts`]. The headers become the metadata and the extensions
+    /// are kept, the method, URI and version are dropped.
+    ///
+    /// ```
+    /// # use tonic::Request;
+    /// let http = http::Request::builder()
+    ///     .header("x-client", "probe")
+    ///     .body(())
+    ///     .unwrap();
+    /// let (parts, ()) = http.into_parts();
+    ///
+    /// let request = Request::from_http_parts(parts, "message");
+    /// assert_eq!(request.metadata().get("x-client").unwrap(), "probe");
+    /// ```
+    pub fn from_http_parts(parts: http::request::Parts, message: T) -> Self {
         Request {
             metadata: MetadataMap::from_headers(parts.headers),
             message,
@@ -296,6 +316,19 @@ impl<T> Request<T> {
             .insert(crate::metadata::GRPC_TIMEOUT_HEADER, value);
     }
 
+    /// Disable compression of the request body.
+    ///
+    /// This sends the messages of this request uncompressed with a `grpc-encoding: identity`
+    /// header, even if the client is configured to compress requests with
+    /// [`Grpc::send_compressed`]. This is useful for payloads that are already compressed.
+    ///
+    /// [`Grpc::send_compressed`]: crate::client::Grpc::send_compressed
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    pub fn disable_compression(&mut self) {
+        self.extensions_mut()
+            .insert(crate::codec::compression::SingleMessageCompressionOverride::Disable);
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &Extensions {
         &self.extensions
@@ -350,6 +383,32 @@ impl<T> Request<T> {
     pub fn extensions_mut(&mut self) -> &mut Extensions {
         &mut self.extensions
     }
+
+    /// Add a typed extension to the request.
+    ///
+    /// Extensions are not sent over the wire. When the request is made through a client, they are
+    /// carried over to the extensions of the `http::Request` that is passed to the inner
+    /// [`GrpcService`], unlike metadata which is sanitized into headers. This allows passing
+    /// per-call values, such as routing hints, to layers of the client stack.
+    ///
+    /// ```
+    /// use tonic::Request;
+    ///
+    /// #[derive(Clone)]
+    /// struct Shard(u32);
+    ///
+    /// let request = Request::new(()).with_extension(Shard(3));
+    /// assert_eq!(request.extensions().get::<Shard>().unwrap().0, 3);
+    /// ```
+    ///
+    /// [`GrpcService`]: crate::client::GrpcService
+    pub fn with_extension<E>(mut self, extension: E) -> Self
+    where
+        E: Clone + Send + Sync + 'static,
+    {
+        self.extensions.insert(extension);
+        self
+    }
 }
 
 impl<T> IntoRequest<T> for T {
@@ -394,7 +453,7 @@ mod sealed {
     pub trait Sealed {}
 }
 
-fn duration_to_grpc_timeout(duration: Duration) -> String {
+pub(crate) fn duration_to_grpc_timeout(duration: Duration) -> String {
     fn try_format<T: Into<u128>>(
         duration: Duration,
         unit: char,
@@ -458,6 +517,37 @@ mod tests {
         assert!(http_request.headers().is_empty());
     }
 
+    #[test]
+    fn extensions_are_preserved() {
+        #[derive(Clone)]
+        struct Shard(u32);
+
+        let http_request = Request::new(1).with_extension(Shard(3)).into_http(
+            Uri::default(),
+            http::Method::POST,
+            http::Version::HTTP_2,
+            SanitizeHeaders::Yes,
+        );
+        assert_eq!(http_request.extensions().get::<Shard>().unwrap().0, 3);
+    }
+
+    #[test]
+    fn clones_have_their_own_metadata() {
+        let mut template = Request::new(1);
+        template
+            .metadata_mut()
+            .insert("x-client", MetadataValue::from_static("probe"));
+
+        let mut r = template.clone();
+        r.metadata_mut()
+            .insert("x-trace-id", MetadataValue::from_static("1"));
+        r.metadata_mut()
+            .insert("x-client", MetadataValue::from_static("changed"));
+
+        assert_eq!(template.metadata().len(), 1);
+        assert_eq!(template.metadata().get("x-client").unwrap(), "probe");
+    }
+
     #[test]
     fn duration_to_grpc_timeout_less_than_second() {
         let timeout = Duration::from_millis(500);
@@ -1,6 +1,7 @@
 use http::Extensions;
+use tokio_stream::{Stream, StreamExt};
 
-use crate::metadata::MetadataMap;
+use crate::{codec::Streaming, metadata::MetadataMap, Http2StreamId, Status};
 
 /// A gRPC response and metadata from an RPC call.
 #[derive(Debug)]
@@ -112,6 +113,20 @@ impl<T> Response<T> {
         &mut self.extensions
     }
 
+    /// Get the HTTP/2 stream ID the call ran on, if the transport provided it.
+    ///
+    /// This is mostly useful to correlate client logs with server-side HTTP/2 access logs. For
+    /// streaming calls, this is the single stream the whole call runs on. The ID is read from the
+    /// [`Http2StreamId`] extension, which the transport has to insert into its responses; tonic's
+    /// own [`Channel`] can't, since hyper doesn't expose stream IDs, and returns `None`.
+    ///
+    /// [`Channel`]: crate::transport::Channel
+    pub fn stream_id(&self) -> Option<u32> {
+        self.extensions
+            .get::<Http2StreamId>()
+            .map(Http2StreamId::get)
+    }
+
     /// Disable compression of the response body.
     ///
     /// This disables compression of the body of this response, even if compression is enabled on
@@ -127,6 +142,38 @@ impl<T> Response<T> {
     }
 }
 
+impl<M> Response<Streaming<M>> {
+    /// Transform the messages of a response stream, keeping the metadata and extensions of the
+    /// response.
+    ///
+    /// `f` is applied to each received message. An error returned by `f` is yielded in place of
+    /// the message and ends the stream, like an error received from the server does.
+    ///
+    /// ```rust
+    /// # use tonic::{codec::Streaming, Response, Status};
+    /// # fn decrypt(message: Vec<u8>) -> Result<String, Status> { unimplemented!() }
+    /// # fn ex(response: Response<Streaming<Vec<u8>>>) {
+    /// let response = response.try_map(decrypt);
+    /// # }
+    /// ```
+    pub fn try_map<N, F>(self, mut f: F) -> Response<impl Stream<Item = Result<N, Status>>>
+    where
+        F: FnMut(M) -> Result<N, Status>,
+    {
+        self.map(|stream| {
+            let mut failed = false;
+            stream.map_while(move |item| {
+                if failed {
+                    return None;
+                }
+                let item = item.and_then(&mut f);
+                failed = item.is_err();
+                Some(item)
+            })
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +191,83 @@ mod tests {
         let http_response = r.into_http();
         assert!(http_response.headers().is_empty());
     }
+
+    #[test]
+    fn into_parts_round_trips() {
+        let mut r = Response::new(1);
+        r.metadata_mut()
+            .insert("x-custom", MetadataValue::from_static("value"));
+        r.extensions_mut().insert("extension");
+
+        let (metadata, message, extensions) = r.into_parts();
+        let r = Response::from_parts(metadata, message, extensions);
+
+        assert_eq!(*r.get_ref(), 1);
+        assert_eq!(r.metadata().get("x-custom").unwrap(), "value");
+        assert_eq!(r.extensions().get::<&str>(), Some(&"extension"));
+    }
+
+    #[test]
+    fn stream_id_from_extension() {
+        let mut r = Response::new(1);
+        assert_eq!(r.stream_id(), None);
+
+        r.extensions_mut().insert(Http2StreamId::new(7));
+        assert_eq!(r.stream_id(), Some(7));
+    }
+
+    #[cfg(feature = "prost")]
+    fn streaming(messages: &[&str]) -> Streaming<String> {
+        use crate::codec::{Codec, EncodeBuf, Encoder, ProstCodec};
+        use bytes::{BufMut, BytesMut};
+
+        let mut codec = ProstCodec::<String, String>::default();
+        let mut frames = BytesMut::new();
+        for message in messages {
+            let mut encoded = BytesMut::new();
+            codec
+                .encoder()
+                .encode(message.to_string(), &mut EncodeBuf::new(&mut encoded))
+                .unwrap();
+            frames.put_u8(0);
+            frames.put_u32(encoded.len() as u32);
+            frames.put(encoded);
+        }
+
+        Streaming::from_bytes_stream(
+            codec.decoder(),
+            tokio_stream::iter([Ok(frames.freeze())]),
+            None,
+            None,
+        )
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn try_map_keeps_metadata() {
+        let mut response = Response::new(streaming(&["one", "three"]));
+        response
+            .metadata_mut()
+            .insert("x-custom", MetadataValue::from_static("value"));
+
+        #[allow(clippy::result_large_err)]
+        let response = response.try_map(|message| Ok(message.len()));
+        assert_eq!(response.metadata().get("x-custom").unwrap(), "value");
+
+        let lengths: Result<Vec<_>, _> = response.into_inner().collect().await;
+        assert_eq!(lengths.unwrap(), [3, 5]);
+    }
+
+    #[cfg(feature = "prost")]
+    #[tokio::test]
+    async fn try_map_error_ends_stream() {
+        #[allow(clippy::result_large_err)]
+        let response = Response::new(streaming(&["one", "two", "three"]))
+            .try_map(|_| Err::<(), _>(Status::data_loss("corrupt")));
+        let mut stream = std::pin::pin!(response.into_inner());
+
+        let status = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), crate::Code::DataLoss);
+        assert!(stream.next().await.is_none());
+    }
 }
@@ -1,10 +1,11 @@
 use crate::codec::compression::{
-    CompressionEncoding, EnabledCompressionEncodings, SingleMessageCompressionOverride,
+    CompressionEncoding, CompressionPolicy, EnabledCompressionEncodings,
+    SingleMessageCompressionOverride,
 };
 use crate::metadata::GRPC_CONTENT_TYPE;
 use crate::{
     body::BoxBody,
-    codec::{encode_server, Codec, Streaming},
+    codec::{encode_server_with_policy, Codec, Streaming, TERMINAL_METADATA_HEADER},
     server::{ClientStreamingService, ServerStreamingService, StreamingService, UnaryService},
     Request, Status,
 };
@@ -36,6 +37,8 @@ pub struct Grpc<T> {
     accept_compression_encodings: EnabledCompressionEncodings,
     /// Which compression encodings might the server use for responses.
     send_compression_encodings: EnabledCompressionEncodings,
+    /// Which response messages get compressed.
+    compression_policy: CompressionPolicy,
     /// Limits the maximum size of a decoded message.
     max_decoding_message_size: Option<usize>,
     /// Limits the maximum size of an encoded message.
@@ -52,6 +55,7 @@ where
             codec,
             accept_compression_encodings: EnabledCompressionEncodings::default(),
             send_compression_encodings: EnabledCompressionEncodings::default(),
+            compression_policy: CompressionPolicy::default(),
             max_decoding_message_size: None,
             max_encoding_message_size: None,
         }
@@ -120,6 +124,15 @@ where
         self
     }
 
+    /// Set which response messages are compressed when compression is in use.
+    ///
+    /// Defaults to [`CompressionPolicy::Always`]. See [`CompressionPolicy::ExceedsLimitFraction`]
+    /// for only compressing messages that are close to [`Grpc::max_encoding_message_size`].
+    pub fn compression_policy(mut self, policy: CompressionPolicy) -> Self {
+        self.compression_policy = policy;
+        self
+    }
+
     /// Limits the maximum size of a decoded message.
     ///
     /// # Example
@@ -318,6 +331,7 @@ where
             self.send_compression_encodings,
         );
 
+        let terminal_metadata = req.headers().contains_key(TERMINAL_METADATA_HEADER);
         let request = t!(self.map_request_streaming(req));
 
         let response = service
@@ -327,12 +341,13 @@ where
 
         let compression_override = compression_override_from_response(&response);
 
-        self.map_response(
+        let response = self.map_response(
             response,
             accept_encoding,
             compression_override,
             self.max_encoding_message_size,
-        )
+        );
+        acknowledge_terminal_metadata(response, terminal_metadata)
     }
 
     /// Handle a bi-directional streaming gRPC request.
@@ -352,16 +367,18 @@ where
             self.send_compression_encodings,
         );
 
+        let terminal_metadata = req.headers().contains_key(TERMINAL_METADATA_HEADER);
         let request = t!(self.map_request_streaming(req));
 
         let response = service.call(request).await;
 
-        self.map_response(
+        let response = self.map_response(
             response,
             accept_encoding,
             SingleMessageCompressionOverride::default(),
             self.max_encoding_message_size,
-        )
+        );
+        acknowledge_terminal_metadata(response, terminal_metadata)
     }
 
     async fn map_request_unary<B>(
@@ -406,14 +423,20 @@ where
         B::Error: Into<crate::Error> + Send,
     {
         let encoding = self.request_encoding_if_supported(&request)?;
+        let terminal_metadata = request.headers().contains_key(TERMINAL_METADATA_HEADER);
 
         let request = request.map(|body| {
-            Streaming::new_request(
+            let streaming = Streaming::new_request(
                 self.codec.decoder(),
                 body,
                 encoding,
                 self.max_decoding_message_size,
-            )
+            );
+            if terminal_metadata {
+                streaming.with_in_body_trailers()
+            } else {
+                streaming
+            }
         });
 
         Ok(Request::from_http(request))
@@ -447,11 +470,12 @@ where
             );
         }
 
-        let body = encode_server(
+        let body = encode_server_with_policy(
             self.codec.encoder(),
             body,
             accept_encoding,
             compression_override,
+            self.compression_policy,
             max_message_size,
         );
 
@@ -485,10 +509,27 @@ impl<T: fmt::Debug> fmt::Debug for Grpc<T> {
             &self.send_compression_encodings,
         );
 
+        f.field("compression_policy", &self.compression_policy);
+
         f.finish()
     }
 }
 
+/// Tells the client that the request's terminal metadata frame is read, see
+/// [`TERMINAL_METADATA_HEADER`].
+fn acknowledge_terminal_metadata(
+    mut response: http::Response<BoxBody>,
+    offered: bool,
+) -> http::Response<BoxBody> {
+    if offered {
+        response.headers_mut().insert(
+            TERMINAL_METADATA_HEADER,
+            http::HeaderValue::from_static("1"),
+        );
+    }
+    response
+}
+
 fn compression_override_from_response<B, E>(
     res: &Result<crate::Response<B>, E>,
 ) -> SingleMessageCompressionOverride {
@@ -45,6 +45,7 @@ use tower_service::Service;
 /// [tower-example]: https://github.com/hyperium/tonic/tree/master/examples/src/tower
 pub trait Interceptor {
     /// Intercept a request before it is sent, optionally cancelling it.
+    #[allow(clippy::result_large_err)]
     fn call(&mut self, request: crate::Request<()>) -> Result<crate::Request<()>, Status>;
 }
 
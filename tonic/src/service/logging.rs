@@ -0,0 +1,552 @@
+//! Structured access logging of gRPC calls.
+//!
+//! See [`LoggingLayer`] for more details.
+
+use crate::{
+    body::{boxed, BoxBody},
+    Code,
+};
+use bytes::{Buf, Bytes};
+use http::{header::HeaderName, HeaderMap};
+use http_body::{Body, Frame, SizeHint};
+use pin_project::pin_project;
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+    time::Instant,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::Level;
+
+const GRPC_STATUS_HEADER: &str = "grpc-status";
+
+/// A [`Layer`] that logs every gRPC call with structured fields.
+///
+/// When a call completes, a `TRACE` event with the following fields is emitted:
+///
+/// - `grpc.method`: the path of the method, e.g. `/helloworld.Greeter/SayHello`.
+/// - `grpc.code`: the status code of the call, absent if the call was cancelled before a status
+///   was received.
+/// - `duration`: the time from the start of the call until its status was received.
+/// - `request.messages` and `request.bytes`: the number of request messages and the size of the
+///   request body.
+/// - `response.messages` and `response.bytes`: the same for the response.
+/// - `metadata`: the request metadata, with the values of [redacted](LoggingLayer::redact_metadata)
+///   keys hidden.
+/// - `error`: the error returned by the inner service, if any.
+///
+/// Messages are counted as they are streamed, so streaming calls are supported. The layer can be
+/// used both on the server and the client side, since it only relies on the HTTP representation
+/// of a call. When `TRACE` events are disabled for this module at the time of a call, the call is
+/// passed through untouched.
+///
+/// ```no_run
+/// # #[cfg(feature = "transport")]
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// use tonic::{client::Grpc, service::LoggingLayer, transport::{Channel, Server}};
+/// use tower::ServiceBuilder;
+///
+/// let layer = LoggingLayer::new().redact_metadata("x-api-key");
+///
+/// // On the server side.
+/// Server::builder()
+///     .layer(layer.clone())
+///     .add_routes(Default::default())
+///     .serve("[::1]:50051".parse()?)
+///     .await?;
+///
+/// // On the client side.
+/// let channel = Channel::from_static("http://[::1]:50051").connect().await?;
+/// let mut client = Grpc::new(ServiceBuilder::new().layer(layer).service(channel));
+/// client.ready().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoggingLayer {
+    redacted: Arc<Vec<HeaderName>>,
+}
+
+impl LoggingLayer {
+    /// Create a new `LoggingLayer`.
+    ///
+    /// The `authorization` metadata key is redacted by default.
+    pub fn new() -> Self {
+        Self {
+            redacted: Arc::new(vec![http::header::AUTHORIZATION]),
+        }
+    }
+
+    /// Hide the values of the metadata `key` in the logs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not a valid lowercase metadata key.
+    pub fn redact_metadata(mut self, key: &'static str) -> Self {
+        Arc::make_mut(&mut self.redacted).push(HeaderName::from_static(key));
+        self
+    }
+}
+
+impl Default for LoggingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for LoggingLayer {
+    type Service = Logging<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Logging {
+            inner,
+            redacted: self.redacted.clone(),
+        }
+    }
+}
+
+/// A service that logs every gRPC call, created by [`LoggingLayer`].
+///
+/// See [`LoggingLayer`] for more details.
+#[derive(Debug, Clone)]
+pub struct Logging<S> {
+    inner: S,
+    redacted: Arc<Vec<HeaderName>>,
+}
+
+impl<S, ResBody> Service<http::Request<BoxBody>> for Logging<S>
+where
+    S: Service<http::Request<BoxBody>, Response = http::Response<ResBody>>,
+    S::Error: fmt::Display,
+{
+    type Response = http::Response<LoggingBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
+        if !tracing::enabled!(Level::TRACE) {
+            return ResponseFuture {
+                inner: self.inner.call(request),
+                record: None,
+            };
+        }
+
+        let request_counts = Arc::new(SharedCounts::default());
+        let record = Record {
+            method: request.uri().path().to_owned(),
+            metadata: format_metadata(request.headers(), &self.redacted),
+            start: Instant::now(),
+            request: request_counts.clone(),
+            response: FrameCounter::default(),
+            code: None,
+            error: None,
+        };
+
+        let request = request.map(|body| {
+            boxed(RequestBody {
+                inner: body,
+                counter: FrameCounter::default(),
+                shared: request_counts,
+            })
+        });
+
+        ResponseFuture {
+            inner: self.inner.call(request),
+            record: Some(record),
+        }
+    }
+}
+
+/// Response future for [`Logging`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    record: Option<Record>,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: fmt::Display,
+{
+    type Output = Result<http::Response<LoggingBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        match ready!(this.inner.poll(cx)) {
+            Ok(response) => {
+                let mut record = this.record.take();
+                if let Some(record) = &mut record {
+                    // Trailers-only responses carry the status in the headers.
+                    record.code = grpc_status(response.headers());
+                }
+
+                Poll::Ready(Ok(response.map(|inner| LoggingBody { inner, record })))
+            }
+            Err(err) => {
+                if let Some(mut record) = this.record.take() {
+                    record.error = Some(err.to_string());
+                }
+
+                Poll::Ready(Err(err))
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish_non_exhaustive()
+    }
+}
+
+/// Response body for [`Logging`].
+///
+/// The call is logged once the body has ended or is dropped.
+#[pin_project]
+pub struct LoggingBody<B> {
+    #[pin]
+    inner: B,
+    record: Option<Record>,
+}
+
+impl<B> Body for LoggingBody<B>
+where
+    B: Body<Data = Bytes>,
+    B::Error: fmt::Display,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+
+        if let Some(record) = this.record {
+            match &frame {
+                Some(Ok(frame)) => {
+                    if let Some(data) = frame.data_ref() {
+                        record.response.observe(data.chunk());
+                    } else if let Some(trailers) = frame.trailers_ref() {
+                        record.code = grpc_status(trailers);
+                        *this.record = None;
+                    }
+                }
+                Some(Err(err)) => {
+                    record.error = Some(err.to_string());
+                    *this.record = None;
+                }
+                None => *this.record = None,
+            }
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B> fmt::Debug for LoggingBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggingBody").finish_non_exhaustive()
+    }
+}
+
+/// Counts the request messages on their way to the inner service.
+#[pin_project]
+struct RequestBody {
+    #[pin]
+    inner: BoxBody,
+    counter: FrameCounter,
+    shared: Arc<SharedCounts>,
+}
+
+impl Body for RequestBody {
+    type Data = Bytes;
+    type Error = crate::Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+
+        if let Some(data) = frame.as_ref().and_then(|f| f.as_ref().ok()?.data_ref()) {
+            this.counter.observe(data.chunk());
+            this.shared
+                .messages
+                .store(this.counter.messages, Ordering::Relaxed);
+            this.shared
+                .bytes
+                .store(this.counter.bytes, Ordering::Relaxed);
+        }
+
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[derive(Debug, Default)]
+struct SharedCounts {
+    messages: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// Counts the length-prefixed messages of a gRPC body, which may be split across data frames
+/// arbitrarily.
+#[derive(Debug, Default)]
+struct FrameCounter {
+    messages: u64,
+    bytes: u64,
+    header: [u8; 5],
+    header_len: usize,
+    remaining: usize,
+}
+
+impl FrameCounter {
+    fn observe(&mut self, mut data: &[u8]) {
+        self.bytes += data.len() as u64;
+
+        while !data.is_empty() {
+            if self.remaining > 0 {
+                let len = self.remaining.min(data.len());
+                self.remaining -= len;
+                data = &data[len..];
+                continue;
+            }
+
+            let len = (self.header.len() - self.header_len).min(data.len());
+            self.header[self.header_len..self.header_len + len].copy_from_slice(&data[..len]);
+            self.header_len += len;
+            data = &data[len..];
+
+            if self.header_len == self.header.len() {
+                let [_, length @ ..] = self.header;
+                self.messages += 1;
+                self.remaining = u32::from_be_bytes(length) as usize;
+                self.header_len = 0;
+            }
+        }
+    }
+}
+
+/// The fields of a call, logged when dropped.
+struct Record {
+    method: String,
+    metadata: String,
+    start: Instant,
+    request: Arc<SharedCounts>,
+    response: FrameCounter,
+    code: Option<Code>,
+    error: Option<String>,
+}
+
+impl Drop for Record {
+    fn drop(&mut self) {
+        tracing::trace!(
+            grpc.method = %self.method,
+            grpc.code = self.code.map(tracing::field::debug),
+            duration = ?self.start.elapsed(),
+            request.messages = self.request.messages.load(Ordering::Relaxed),
+            request.bytes = self.request.bytes.load(Ordering::Relaxed),
+            response.messages = self.response.messages,
+            response.bytes = self.response.bytes,
+            metadata = %self.metadata,
+            error = self.error.as_deref(),
+            "gRPC call completed",
+        );
+    }
+}
+
+fn grpc_status(headers: &HeaderMap) -> Option<Code> {
+    headers
+        .get(GRPC_STATUS_HEADER)
+        .map(|value| Code::from_bytes(value.as_bytes()))
+}
+
+fn format_metadata(headers: &HeaderMap, redacted: &[HeaderName]) -> String {
+    struct Metadata<'a>(&'a HeaderMap, &'a [HeaderName]);
+
+    impl fmt::Debug for Metadata<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mut map = f.debug_map();
+            for (name, value) in self.0 {
+                if self.1.contains(name) {
+                    map.entry(name, &"[redacted]");
+                } else {
+                    map.entry(name, value);
+                }
+            }
+            map.finish()
+        }
+    }
+
+    format!("{:?}", Metadata(headers, redacted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        body::{empty_body, from_bytes},
+        Status,
+    };
+    use http_body_util::BodyExt;
+    use std::{convert::Infallible, sync::Mutex};
+    use tower::ServiceExt;
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    #[test]
+    fn counts_messages_split_across_chunks() {
+        let mut frames = Vec::new();
+        for message in [&b"abc"[..], b"", b"defgh"] {
+            frames.push(0);
+            frames.extend_from_slice(&(message.len() as u32).to_be_bytes());
+            frames.extend_from_slice(message);
+        }
+
+        for chunk_size in [1, 2, 4, frames.len()] {
+            let mut counter = FrameCounter::default();
+            for chunk in frames.chunks(chunk_size) {
+                counter.observe(chunk);
+            }
+            assert_eq!(counter.messages, 3);
+            assert_eq!(counter.bytes, frames.len() as u64);
+            assert_eq!(counter.remaining, 0);
+        }
+    }
+
+    /// Records the fields of the events it receives.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let value = format!("{:?}", value);
+            self.0.lock().unwrap().push((field.name().into(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn logs_call_fields() {
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let svc = tower::service_fn(|request: http::Request<BoxBody>| async move {
+            // Consume the request, then answer with a single message and a status.
+            request.into_body().collect().await.unwrap();
+
+            let body = http_body_util::StreamBody::new(tokio_stream::iter([
+                Ok::<_, Status>(Frame::data(Bytes::from_static(b"\0\0\0\0\x02hi"))),
+                Ok(Frame::trailers(
+                    Status::not_found("").to_header_map().unwrap(),
+                )),
+            ]));
+            Ok::<_, Infallible>(http::Response::new(body))
+        });
+        let svc = LoggingLayer::new().redact_metadata("x-secret").layer(svc);
+
+        let request = http::Request::builder()
+            .uri("/test.Test/Call")
+            .header("authorization", "Bearer token")
+            .header("x-secret", "hunter2")
+            .header("x-visible", "shown")
+            .body(from_bytes(Bytes::from_static(b"\0\0\0\0\0\0\0\0\0\x01a")))
+            .unwrap();
+
+        let response = svc.oneshot(request).await.unwrap();
+        response.into_body().collect().await.unwrap();
+
+        let fields = recorder.0.lock().unwrap().clone();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        assert_eq!(field("message").unwrap(), "gRPC call completed");
+        assert_eq!(field("grpc.method").unwrap(), "/test.Test/Call");
+        assert_eq!(field("grpc.code").unwrap(), "NotFound");
+        assert_eq!(field("request.messages").unwrap(), "2");
+        assert_eq!(field("request.bytes").unwrap(), "11");
+        assert_eq!(field("response.messages").unwrap(), "1");
+        assert_eq!(field("response.bytes").unwrap(), "7");
+        assert!(field("error").is_none());
+
+        let metadata = field("metadata").unwrap();
+        assert!(metadata.contains(r#""authorization": "[redacted]""#));
+        assert!(metadata.contains(r#""x-secret": "[redacted]""#));
+        assert!(metadata.contains(r#""x-visible": "shown""#));
+        assert!(!metadata.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn passes_through_when_disabled() {
+        let svc = tower::service_fn(|_: http::Request<BoxBody>| async {
+            Ok::<_, Infallible>(http::Response::new(empty_body()))
+        });
+
+        let response = LoggingLayer::new()
+            .layer(svc)
+            .oneshot(http::Request::new(empty_body()))
+            .await
+            .unwrap();
+        assert!(response.body().record.is_none());
+    }
+}
@@ -1,12 +1,15 @@
 //! Utilities for using Tower services with Tonic.
 
 pub mod interceptor;
+pub mod logging;
 #[cfg(feature = "router")]
 pub(crate) mod router;
 
 #[doc(inline)]
 pub use self::interceptor::{interceptor, Interceptor};
 #[doc(inline)]
+pub use self::logging::LoggingLayer;
+#[doc(inline)]
 #[cfg(feature = "router")]
 pub use self::router::{Routes, RoutesBuilder};
 #[cfg(feature = "router")]
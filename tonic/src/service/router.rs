@@ -2,21 +2,79 @@ use crate::{
     body::{boxed, BoxBody},
     metadata::GRPC_CONTENT_TYPE,
     server::NamedService,
+    Status,
 };
 use http::{HeaderName, HeaderValue, Request, Response};
 use std::{
+    any::Any,
     convert::Infallible,
     fmt,
     future::Future,
+    marker::PhantomData,
+    panic::{catch_unwind, AssertUnwindSafe},
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
 };
-use tower::{Service, ServiceExt};
+use tower::{Layer, Service, ServiceExt};
 
 /// A [`Service`] router.
 #[derive(Debug, Clone)]
 pub struct Routes {
     router: axum::Router,
+    guard: Guard,
+}
+
+/// The raw interceptor and panic handler of [`Routes`], applied to the requests it routes.
+#[derive(Debug, Clone, Default)]
+struct Guard {
+    raw_interceptor: Option<BoxRawInterceptor>,
+    panic_handler: Option<PanicHandler>,
+}
+
+/// Turns the payload of a panic into the status of the request, see [`Routes::handle_error`].
+#[derive(Clone)]
+struct PanicHandler(Arc<dyn Fn(Box<dyn Any + Send>) -> Status + Send + Sync>);
+
+impl fmt::Debug for PanicHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PanicHandler").finish()
+    }
+}
+
+/// Runs on each raw request before it is dispatched, see [`Routes::with_raw_interceptor`].
+trait RawInterceptor: Send + Sync + 'static {
+    #[allow(clippy::result_large_err)]
+    fn call(&mut self, request: &Request<BoxBody>) -> Result<(), Status>;
+
+    fn clone_box(&self) -> BoxRawInterceptor;
+}
+
+impl<F> RawInterceptor for F
+where
+    F: FnMut(&Request<BoxBody>) -> Result<(), Status> + Clone + Send + Sync + 'static,
+{
+    fn call(&mut self, request: &Request<BoxBody>) -> Result<(), Status> {
+        self(request)
+    }
+
+    fn clone_box(&self) -> BoxRawInterceptor {
+        BoxRawInterceptor(Box::new(self.clone()))
+    }
+}
+
+struct BoxRawInterceptor(Box<dyn RawInterceptor>);
+
+impl Clone for BoxRawInterceptor {
+    fn clone(&self) -> Self {
+        self.0.clone_box()
+    }
+}
+
+impl fmt::Debug for BoxRawInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawInterceptor").finish()
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -52,6 +110,7 @@ impl Default for Routes {
     fn default() -> Self {
         Self {
             router: axum::Router::new().fallback(unimplemented),
+            guard: Guard::default(),
         }
     }
 }
@@ -100,9 +159,35 @@ impl Routes {
     pub fn prepare(self) -> Self {
         Self {
             router: self.router.with_state(()),
+            guard: self.guard,
         }
     }
 
+    /// Run `f` on each request before it is dispatched to a service, see
+    /// [`Router::with_raw_interceptor`].
+    ///
+    /// [`Router::with_raw_interceptor`]: crate::transport::server::Router::with_raw_interceptor
+    #[cfg(feature = "server")]
+    pub(crate) fn with_raw_interceptor<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&Request<BoxBody>) -> Result<(), Status> + Clone + Send + Sync + 'static,
+    {
+        self.guard.raw_interceptor = Some(BoxRawInterceptor(Box::new(f)));
+        self
+    }
+
+    /// Turn panics of the services into a status with `f`, see [`Router::handle_error`].
+    ///
+    /// [`Router::handle_error`]: crate::transport::server::Router::handle_error
+    #[cfg(feature = "server")]
+    pub(crate) fn handle_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Box<dyn Any + Send>) -> Status + Send + Sync + 'static,
+    {
+        self.guard.panic_handler = Some(PanicHandler(Arc::new(f)));
+        self
+    }
+
     /// Convert this `Routes` into an [`axum::Router`].
     #[deprecated(since = "0.12.2", note = "Use `Routes::into_axum_router` instead.")]
     pub fn into_router(self) -> axum::Router {
@@ -110,14 +195,23 @@ impl Routes {
     }
 
     /// Convert this `Routes` into an [`axum::Router`].
+    ///
+    /// The raw interceptor and the panic handler, if any, are applied to the routes with a
+    /// layer. They don't apply to routes added to the returned router afterwards.
     pub fn into_axum_router(self) -> axum::Router {
-        self.router
+        if self.guard.raw_interceptor.is_none() && self.guard.panic_handler.is_none() {
+            return self.router;
+        }
+        self.router.layer(self.guard)
     }
 }
 
 impl From<axum::Router> for Routes {
     fn from(router: axum::Router) -> Self {
-        Self { router }
+        Self {
+            router,
+            guard: Guard::default(),
+        }
     }
 }
 
@@ -144,23 +238,135 @@ impl Service<Request<BoxBody>> for Routes {
     }
 
     fn call(&mut self, req: Request<BoxBody>) -> Self::Future {
-        RoutesFuture(self.router.call(req))
+        self.guard.call(&mut self.router, req)
     }
 }
 
-pub struct RoutesFuture(axum::routing::future::RouteFuture<Infallible>);
+impl Guard {
+    fn call<S, E>(&mut self, inner: &mut S, req: Request<BoxBody>) -> RoutesFuture<E>
+    where
+        S: Service<
+            Request<BoxBody>,
+            Error = Infallible,
+            Future = axum::routing::future::RouteFuture<Infallible>,
+        >,
+    {
+        if let Some(BoxRawInterceptor(interceptor)) = &mut self.raw_interceptor {
+            if let Err(status) = interceptor.call(&req) {
+                return RoutesFuture::rejected(status);
+            }
+        }
 
-impl fmt::Debug for RoutesFuture {
+        let Some(PanicHandler(handler)) = &self.panic_handler else {
+            return RoutesFuture::route(inner.call(req), None);
+        };
+
+        match catch_unwind(AssertUnwindSafe(|| inner.call(req))) {
+            Ok(future) => RoutesFuture::route(future, self.panic_handler.clone()),
+            Err(panic) => RoutesFuture::rejected(handler(panic)),
+        }
+    }
+}
+
+impl Layer<axum::routing::Route> for Guard {
+    type Service = Guarded;
+
+    fn layer(&self, inner: axum::routing::Route) -> Self::Service {
+        Guarded {
+            inner,
+            guard: self.clone(),
+        }
+    }
+}
+
+/// A route of the router returned by [`Routes::into_axum_router`], see [`Guard`].
+#[derive(Clone)]
+struct Guarded {
+    inner: axum::routing::Route,
+    guard: Guard,
+}
+
+impl Service<Request<axum::body::Body>> for Guarded {
+    type Response = Response<BoxBody>;
+    type Error = Infallible;
+    type Future = RoutesFuture<Infallible>;
+
+    #[inline]
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<axum::body::Body>) -> Self::Future {
+        self.guard.call(&mut self.inner, req.map(boxed))
+    }
+}
+
+pub struct RoutesFuture<E = crate::Error> {
+    kind: Kind,
+    panic_handler: Option<PanicHandler>,
+    _error: PhantomData<fn() -> E>,
+}
+
+impl<E> RoutesFuture<E> {
+    fn route(
+        future: axum::routing::future::RouteFuture<Infallible>,
+        panic_handler: Option<PanicHandler>,
+    ) -> Self {
+        Self {
+            kind: Kind::Route(future),
+            panic_handler,
+            _error: PhantomData,
+        }
+    }
+
+    fn rejected(status: Status) -> Self {
+        Self {
+            kind: Kind::Rejected(Some(status.into_http())),
+            panic_handler: None,
+            _error: PhantomData,
+        }
+    }
+}
+
+enum Kind {
+    Route(axum::routing::future::RouteFuture<Infallible>),
+    Rejected(Option<Response<BoxBody>>),
+}
+
+impl<E> fmt::Debug for RoutesFuture<E> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("RoutesFuture").finish()
     }
 }
 
-impl Future for RoutesFuture {
-    type Output = Result<Response<BoxBody>, crate::Error>;
+impl<E> Future for RoutesFuture<E> {
+    type Output = Result<Response<BoxBody>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let future = match &mut this.kind {
+            Kind::Route(future) => future,
+            Kind::Rejected(response) => {
+                return Poll::Ready(Ok(response.take().expect("polled after completion")))
+            }
+        };
+
+        let result = match &this.panic_handler {
+            Some(PanicHandler(handler)) => {
+                match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut *future).poll(cx))) {
+                    Ok(poll) => ready!(poll),
+                    Err(panic) => {
+                        let response = handler(panic).into_http();
+                        // The service can't be polled again after panicking.
+                        this.kind = Kind::Rejected(None);
+                        return Poll::Ready(Ok(response));
+                    }
+                }
+            }
+            None => ready!(Pin::new(future).poll(cx)),
+        };
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        match ready!(Pin::new(&mut self.as_mut().0).poll(cx)) {
+        match result {
             Ok(res) => Ok(res.map(boxed)).into(),
             // NOTE: This pattern is not needed from Rust 1.82.
             // See https://github.com/rust-lang/rust/pull/122792.
@@ -169,3 +375,111 @@ impl Future for RoutesFuture {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::empty_body;
+
+    async fn grpc_status<S, B>(routes: &mut S, request: Request<BoxBody>) -> String
+    where
+        S: Service<Request<BoxBody>, Response = Response<B>>,
+        S::Error: fmt::Debug,
+    {
+        let response = routes.call(request).await.unwrap();
+        response.headers()["grpc-status"]
+            .to_str()
+            .unwrap()
+            .to_owned()
+    }
+
+    // Rejects the requests with an `x-blocked` header.
+    fn blocking_routes() -> Routes {
+        #[allow(clippy::result_large_err)]
+        Routes::default().with_raw_interceptor(|request: &Request<BoxBody>| {
+            match request.headers().get("x-blocked") {
+                Some(_) => Err(Status::permission_denied("blocked")),
+                None => Ok(()),
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn raw_interceptor_rejects_requests() {
+        let mut routes = blocking_routes().prepare();
+
+        let request = Request::builder()
+            .header("x-blocked", "yes")
+            .body(empty_body())
+            .unwrap();
+        assert_eq!(grpc_status(&mut routes, request).await, "7");
+
+        // Accepted requests reach the router, which has no services.
+        let request = Request::new(empty_body());
+        assert_eq!(grpc_status(&mut routes, request).await, "12");
+    }
+
+    #[derive(Clone)]
+    struct Panicking;
+
+    impl NamedService for Panicking {
+        const NAME: &'static str = "test.Panicking";
+    }
+
+    impl Service<Request<BoxBody>> for Panicking {
+        type Response = Response<BoxBody>;
+        type Error = Infallible;
+        type Future = Pin<Box<dyn Future<Output = Result<Response<BoxBody>, Infallible>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Request<BoxBody>) -> Self::Future {
+            Box::pin(async { panic!("handler failed") })
+        }
+    }
+
+    #[tokio::test]
+    async fn panics_are_turned_into_status() {
+        let mut routes = Routes::new(Panicking)
+            .handle_error(|panic| {
+                let message = panic.downcast_ref::<&str>().copied().unwrap_or_default();
+                Status::internal(format!("panicked: {message}"))
+            })
+            .prepare();
+
+        let request = Request::builder()
+            .uri("/test.Panicking/Call")
+            .body(empty_body())
+            .unwrap();
+        let response = routes.call(request).await.unwrap();
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), crate::Code::Internal);
+        assert_eq!(status.message(), "panicked: handler failed");
+    }
+
+    #[tokio::test]
+    async fn axum_router_keeps_raw_interceptor() {
+        let mut router = blocking_routes().into_axum_router();
+
+        let request = Request::builder()
+            .header("x-blocked", "yes")
+            .body(empty_body())
+            .unwrap();
+        assert_eq!(grpc_status(&mut router, request).await, "7");
+    }
+
+    #[tokio::test]
+    async fn axum_router_keeps_panic_handler() {
+        let mut router = Routes::new(Panicking)
+            .handle_error(|_| Status::internal("panicked"))
+            .into_axum_router();
+
+        let request = Request::builder()
+            .uri("/test.Panicking/Call")
+            .body(empty_body())
+            .unwrap();
+        assert_eq!(grpc_status(&mut router, request).await, "13");
+    }
+}
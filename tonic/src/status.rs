@@ -1,16 +1,18 @@
-use crate::metadata::MetadataMap;
+use crate::metadata::{Ascii, Binary, KeyAndValueRef, MetadataKey, MetadataMap, MetadataValue};
 use crate::{body::BoxBody, metadata::GRPC_CONTENT_TYPE};
 use base64::Engine as _;
 use bytes::Bytes;
 use http::header::{HeaderMap, HeaderValue};
 use percent_encoding::{percent_decode, percent_encode, AsciiSet, CONTROLS};
-use std::{borrow::Cow, error::Error, fmt, sync::Arc};
+use std::{borrow::Cow, error::Error, fmt, sync::Arc, time::Duration};
 use tracing::{debug, trace, warn};
 
+// Non-ASCII bytes are always percent-encoded, which keeps UTF-8 messages intact.
 const ENCODING_SET: &AsciiSet = &CONTROLS
     .add(b' ')
     .add(b'"')
     .add(b'#')
+    .add(b'%')
     .add(b'<')
     .add(b'>')
     .add(b'`')
@@ -21,6 +23,7 @@ const ENCODING_SET: &AsciiSet = &CONTROLS
 const GRPC_STATUS_HEADER_CODE: &str = "grpc-status";
 const GRPC_STATUS_MESSAGE_HEADER: &str = "grpc-message";
 const GRPC_STATUS_DETAILS_HEADER: &str = "grpc-status-details-bin";
+const GRPC_RETRY_AFTER_HEADER: &str = "grpc-retry-after";
 
 /// A gRPC status describing the result of an RPC call.
 ///
@@ -38,6 +41,8 @@ const GRPC_STATUS_DETAILS_HEADER: &str = "grpc-status-details-bin";
 pub struct Status {
     /// The gRPC status code, found in the `grpc-status` header.
     code: Code,
+    /// The numeric `grpc-status` value received from the peer, if it is not a known [`Code`].
+    raw_code: Option<i32>,
     /// A relevant error message, found in the `grpc-message` header.
     message: String,
     /// Binary opaque details, found in the `grpc-status-details-bin` header.
@@ -122,6 +127,9 @@ impl Code {
     /// If you only need description in `println`, `format`, `log` and other
     /// formatting contexts, you may want to use `Display` impl for `Code`
     /// instead.
+    ///
+    /// The description is meant for humans and may change, use [`Code::to_grpc_name`] to
+    /// identify the code in a stable way.
     pub fn description(&self) -> &'static str {
         match self {
             Code::Ok => "The operation completed successfully",
@@ -147,6 +155,36 @@ impl Code {
             Code::Unauthenticated => "The request does not have valid authentication credentials",
         }
     }
+
+    /// Get the canonical name of this `Code`, as in the `google.rpc.Code` proto enum.
+    ///
+    /// These names are the same in every gRPC implementation, which makes them suitable to
+    /// identify the code in logs or serialized errors.
+    ///
+    /// ```
+    /// assert_eq!(tonic::Code::DeadlineExceeded.to_grpc_name(), "DEADLINE_EXCEEDED");
+    /// ```
+    pub fn to_grpc_name(&self) -> &'static str {
+        match self {
+            Code::Ok => "OK",
+            Code::Cancelled => "CANCELLED",
+            Code::Unknown => "UNKNOWN",
+            Code::InvalidArgument => "INVALID_ARGUMENT",
+            Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Code::NotFound => "NOT_FOUND",
+            Code::AlreadyExists => "ALREADY_EXISTS",
+            Code::PermissionDenied => "PERMISSION_DENIED",
+            Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            Code::FailedPrecondition => "FAILED_PRECONDITION",
+            Code::Aborted => "ABORTED",
+            Code::OutOfRange => "OUT_OF_RANGE",
+            Code::Unimplemented => "UNIMPLEMENTED",
+            Code::Internal => "INTERNAL",
+            Code::Unavailable => "UNAVAILABLE",
+            Code::DataLoss => "DATA_LOSS",
+            Code::Unauthenticated => "UNAUTHENTICATED",
+        }
+    }
 }
 
 impl std::fmt::Display for Code {
@@ -162,6 +200,7 @@ impl Status {
     pub fn new(code: Code, message: impl Into<String>) -> Status {
         Status {
             code,
+            raw_code: None,
             message: message.into(),
             details: Bytes::new(),
             metadata: MetadataMap::new(),
@@ -204,6 +243,41 @@ impl Status {
         Status::new(Code::DeadlineExceeded, message)
     }
 
+    /// Deadline expired before operation could complete, and the call may be
+    /// retried after `retry_after`.
+    ///
+    /// The delay is sent in the `grpc-retry-after` metadata entry, as seconds in
+    /// decimal notation, and is read back with [`Status::parse_retry_after`].
+    /// When a call fails with such a status, the retry policy of a [`Channel`]
+    /// waits for this delay instead of its backoff before the next attempt.
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use tonic::Status;
+    /// let status = Status::deadline_exceeded_with_retry(Duration::from_millis(1500));
+    ///
+    /// assert_eq!(status.metadata().get("grpc-retry-after").unwrap(), "1.5");
+    /// assert_eq!(status.parse_retry_after(), Some(Duration::from_millis(1500)));
+    /// ```
+    ///
+    /// [`Channel`]: crate::transport::Channel
+    pub fn deadline_exceeded_with_retry(retry_after: Duration) -> Status {
+        let mut status =
+            Status::deadline_exceeded(format!("Deadline exceeded, retry after {:?}", retry_after));
+        status.set_retry_after(retry_after);
+        status
+    }
+
+    /// Set the `grpc-retry-after` metadata entry read by [`Status::parse_retry_after`].
+    pub(crate) fn set_retry_after(&mut self, retry_after: Duration) {
+        let value = retry_after
+            .as_secs_f64()
+            .to_string()
+            .parse()
+            .expect("a decimal number is a valid metadata value");
+        self.metadata.insert(GRPC_RETRY_AFTER_HEADER, value);
+    }
+
     /// Some requested entity (e.g., file or directory) was not found.
     pub fn not_found(message: impl Into<String>) -> Status {
         Status::new(Code::NotFound, message)
@@ -340,7 +414,7 @@ impl Status {
             Err(err) => err,
         };
 
-        #[cfg(feature = "server")]
+        #[cfg(any(feature = "server", feature = "channel"))]
         let err = match err.downcast::<h2::Error>() {
             Ok(h2) => {
                 return Ok(Status::from_h2_error(h2));
@@ -357,7 +431,7 @@ impl Status {
     }
 
     // FIXME: bubble this into `transport` and expose generic http2 reasons.
-    #[cfg(feature = "server")]
+    #[cfg(any(feature = "server", feature = "channel"))]
     fn from_h2_error(err: Box<h2::Error>) -> Status {
         let code = Self::code_from_h2(&err);
 
@@ -366,8 +440,13 @@ impl Status {
         status
     }
 
-    #[cfg(feature = "server")]
+    #[cfg(any(feature = "server", feature = "channel"))]
     fn code_from_h2(err: &h2::Error) -> Code {
+        // Streams the peer refused by going away were never processed and are safe to retry.
+        if err.is_go_away() && err.is_remote() {
+            return Code::Unavailable;
+        }
+
         // See https://github.com/grpc/grpc/blob/3977c30/doc/PROTOCOL-HTTP2.md#errors
         match err.reason() {
             Some(h2::Reason::NO_ERROR)
@@ -418,7 +497,7 @@ impl Status {
             return Some(Status::cancelled(err.to_string()));
         }
 
-        #[cfg(feature = "server")]
+        #[cfg(any(feature = "server", feature = "channel"))]
         if let Some(h2_err) = err.source().and_then(|e| e.downcast_ref::<h2::Error>()) {
             let code = Status::code_from_h2(h2_err);
             let status = Self::new(code, format!("h2 protocol error: {}", err));
@@ -437,18 +516,54 @@ impl Status {
         Status::from_error(err)
     }
 
+    /// Create a `Status` from an I/O error, with a code depending on its kind.
+    ///
+    /// | [`io::ErrorKind`] | [`Code`] |
+    /// |---|---|
+    /// | `NotFound` | `NotFound` |
+    /// | `PermissionDenied` | `PermissionDenied` |
+    /// | `AlreadyExists` | `AlreadyExists` |
+    /// | `InvalidInput` | `InvalidArgument` |
+    /// | `InvalidData` | `DataLoss` |
+    /// | `TimedOut` | `DeadlineExceeded` |
+    /// | `UnexpectedEof` | `OutOfRange` |
+    /// | `ConnectionAborted` | `Aborted` |
+    /// | `ConnectionRefused`, `ConnectionReset`, `NotConnected`, `AddrInUse`, `AddrNotAvailable` | `Unavailable` |
+    /// | `BrokenPipe`, `WouldBlock`, `WriteZero`, `Interrupted` | `Internal` |
+    /// | any other kind | `Unknown` |
+    ///
+    /// The message is the one of `err`. Since I/O errors can't be cloned, the source of the status
+    /// is a new I/O error with the same kind and message. Converting with `Status::from` keeps
+    /// `err` itself as the source.
+    ///
+    /// [`io::ErrorKind`]: std::io::ErrorKind
+    pub fn from_io_error(err: &std::io::Error) -> Status {
+        let mut status = Status::new(io_error_code(err.kind()), err.to_string());
+        status.set_source(Arc::new(std::io::Error::new(err.kind(), err.to_string())));
+        status
+    }
+
     /// Extract a `Status` from a hyper `HeaderMap`.
     pub fn from_header_map(header_map: &HeaderMap) -> Option<Status> {
         header_map.get(GRPC_STATUS_HEADER_CODE).map(|code| {
+            let raw_code = std::str::from_utf8(code.as_bytes())
+                .ok()
+                .and_then(|code| code.parse::<i32>().ok());
             let code = Code::from_bytes(code.as_ref());
-            let error_message = header_map
+            let message = header_map
                 .get(GRPC_STATUS_MESSAGE_HEADER)
                 .map(|header| {
                     percent_decode(header.as_bytes())
                         .decode_utf8()
                         .map(|cow| cow.to_string())
+                        .unwrap_or_else(|err| {
+                            // The spec forbids discarding a message that fails to decode, so
+                            // fall back to its raw percent-encoded form.
+                            warn!("Error deserializing status message header: {}", err);
+                            String::from_utf8_lossy(header.as_bytes()).into_owned()
+                        })
                 })
-                .unwrap_or_else(|| Ok(String::new()));
+                .unwrap_or_default();
 
             let details = header_map
                 .get(GRPC_STATUS_DETAILS_HEADER)
@@ -465,24 +580,13 @@ impl Status {
             other_headers.remove(GRPC_STATUS_MESSAGE_HEADER);
             other_headers.remove(GRPC_STATUS_DETAILS_HEADER);
 
-            match error_message {
-                Ok(message) => Status {
-                    code,
-                    message,
-                    details,
-                    metadata: MetadataMap::from_headers(other_headers),
-                    source: None,
-                },
-                Err(err) => {
-                    warn!("Error deserializing status message header: {}", err);
-                    Status {
-                        code: Code::Unknown,
-                        message: format!("Error deserializing status message header: {}", err),
-                        details,
-                        metadata: MetadataMap::from_headers(other_headers),
-                        source: None,
-                    }
-                }
+            Status {
+                code,
+                raw_code: raw_code.filter(|raw_code| *raw_code != code as i32),
+                message,
+                details,
+                metadata: MetadataMap::from_headers(other_headers),
+                source: None,
             }
         })
     }
@@ -492,11 +596,67 @@ impl Status {
         self.code
     }
 
+    /// Get the numeric gRPC status code of this `Status`.
+    ///
+    /// This is the value of [`Status::code`], unless the `Status` was received from a peer that
+    /// sent a numeric `grpc-status` outside the known code range. Such codes are reported as
+    /// [`Code::Unknown`], and this returns the value that was actually sent.
+    pub fn raw_code(&self) -> i32 {
+        self.raw_code.unwrap_or(self.code as i32)
+    }
+
     /// Get the text error message of this `Status`.
     pub fn message(&self) -> &str {
         &self.message
     }
 
+    /// Returns `true` if the call failed because the server sent an HTTP/2 `GOAWAY`.
+    ///
+    /// A server going away, for example because it is shutting down, fails the calls it did not
+    /// process with an `Unavailable` status instead of leaving them hanging. Those calls never
+    /// reached the application, so they can be retried on a fresh connection. A [`Channel`]
+    /// reconnects after a `GOAWAY`, and a balanced channel routes the retry to another endpoint
+    /// while the affected one reconnects. Calls that were already being processed are not
+    /// affected and complete normally.
+    ///
+    /// This inspects the [source](Error::source) chain, so it only returns `true` for statuses
+    /// created by tonic from transport errors.
+    ///
+    /// [`Channel`]: crate::transport::Channel
+    pub fn is_goaway(&self) -> bool {
+        #[cfg(any(feature = "server", feature = "channel"))]
+        {
+            let mut source = self
+                .source
+                .as_deref()
+                .map(|err| err as &(dyn Error + 'static));
+            while let Some(err) = source {
+                if let Some(h2_err) = err.downcast_ref::<h2::Error>() {
+                    if h2_err.is_go_away() && h2_err.is_remote() {
+                        return true;
+                    }
+                }
+                source = err.source();
+            }
+        }
+
+        false
+    }
+
+    /// Get the delay the server asked to wait before retrying the call, if any.
+    ///
+    /// This reads the `grpc-retry-after` metadata entry set by
+    /// [`Status::deadline_exceeded_with_retry`] and by servers rejecting calls over their
+    /// [rate limit], whatever the code of the status.
+    /// Entries that aren't a non-negative number of seconds are ignored.
+    ///
+    /// [rate limit]: crate::transport::Server::with_rate_limit
+    pub fn parse_retry_after(&self) -> Option<Duration> {
+        let value = self.metadata.get(GRPC_RETRY_AFTER_HEADER)?.to_str().ok()?;
+        let secs = value.parse::<f64>().ok()?;
+        Duration::try_from_secs_f64(secs).ok()
+    }
+
     /// Get the opaque error details of this `Status`.
     pub fn details(&self) -> &[u8] {
         &self.details
@@ -512,6 +672,41 @@ impl Status {
         &mut self.metadata
     }
 
+    /// Add an ASCII metadata entry, keeping any existing values of `key`.
+    ///
+    /// Unlike inserting into [`Status::metadata_mut`], this allows keys that carry several values.
+    ///
+    /// ```
+    /// # use tonic::{metadata::MetadataKey, Status};
+    /// let mut status = Status::unavailable("overloaded");
+    /// status
+    ///     .append_metadata(MetadataKey::from_static("x-route"), "a".parse().unwrap())
+    ///     .append_metadata(MetadataKey::from_static("x-route"), "b".parse().unwrap());
+    ///
+    /// assert_eq!(status.metadata().get_all("x-route").iter().count(), 2);
+    /// ```
+    pub fn append_metadata(
+        &mut self,
+        key: MetadataKey<Ascii>,
+        value: MetadataValue<Ascii>,
+    ) -> &mut Status {
+        self.metadata.append(key, value);
+        self
+    }
+
+    /// Add a binary metadata entry, keeping any existing values of `key`.
+    ///
+    /// See [`Status::append_metadata`].
+    pub fn append_binary_metadata(
+        &mut self,
+        key: MetadataKey<Binary>,
+        value: MetadataValue<Binary>,
+    ) -> &mut Status {
+        self.metadata.append_bin(key, value);
+        self
+    }
+
+    #[allow(clippy::result_large_err)]
     pub(crate) fn to_header_map(&self) -> Result<HeaderMap, Self> {
         let mut header_map = HeaderMap::with_capacity(3 + self.metadata.len());
         self.add_header(&mut header_map)?;
@@ -519,10 +714,16 @@ impl Status {
     }
 
     /// Add headers from this `Status` into `header_map`.
+    #[allow(clippy::result_large_err)]
     pub fn add_header(&self, header_map: &mut HeaderMap) -> Result<(), Self> {
         header_map.extend(self.metadata.clone().into_sanitized_headers());
 
-        header_map.insert(GRPC_STATUS_HEADER_CODE, self.code.to_header_value());
+        // Forward codes outside the known range as they were received.
+        let code = match self.raw_code {
+            Some(raw_code) => HeaderValue::from(raw_code),
+            None => self.code.to_header_value(),
+        };
+        header_map.insert(GRPC_STATUS_HEADER_CODE, code);
 
         if !self.message.is_empty() {
             let to_write = Bytes::copy_from_slice(
@@ -566,6 +767,7 @@ impl Status {
     ) -> Status {
         Status {
             code,
+            raw_code: None,
             message: message.into(),
             details,
             metadata,
@@ -590,13 +792,14 @@ impl Status {
     }
 }
 
-fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
+pub(crate) fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
     let mut source = Some(err);
 
     while let Some(err) = source {
         if let Some(status) = err.downcast_ref::<Status>() {
             return Some(Status {
                 code: status.code,
+                raw_code: status.raw_code,
                 message: status.message.clone(),
                 details: status.details.clone(),
                 metadata: status.metadata.clone(),
@@ -640,6 +843,10 @@ impl fmt::Debug for Status {
 
         builder.field("code", &self.code);
 
+        if let Some(raw_code) = self.raw_code {
+            builder.field("raw_code", &raw_code);
+        }
+
         if !self.message.is_empty() {
             builder.field("message", &self.message);
         }
@@ -666,7 +873,7 @@ fn invalid_header_value_byte<Error: fmt::Display>(err: Error) -> Status {
     )
 }
 
-#[cfg(feature = "server")]
+#[cfg(any(feature = "server", feature = "channel"))]
 impl From<h2::Error> for Status {
     fn from(err: h2::Error) -> Self {
         Status::from_h2_error(Box::new(err))
@@ -681,42 +888,71 @@ impl From<Status> for h2::Error {
 }
 
 impl From<std::io::Error> for Status {
+    /// Converts with the mapping of [`Status::from_io_error`], keeping `err` as the source.
     fn from(err: std::io::Error) -> Self {
-        use std::io::ErrorKind;
-        let code = match err.kind() {
-            ErrorKind::BrokenPipe
-            | ErrorKind::WouldBlock
-            | ErrorKind::WriteZero
-            | ErrorKind::Interrupted => Code::Internal,
-            ErrorKind::ConnectionRefused
-            | ErrorKind::ConnectionReset
-            | ErrorKind::NotConnected
-            | ErrorKind::AddrInUse
-            | ErrorKind::AddrNotAvailable => Code::Unavailable,
-            ErrorKind::AlreadyExists => Code::AlreadyExists,
-            ErrorKind::ConnectionAborted => Code::Aborted,
-            ErrorKind::InvalidData => Code::DataLoss,
-            ErrorKind::InvalidInput => Code::InvalidArgument,
-            ErrorKind::NotFound => Code::NotFound,
-            ErrorKind::PermissionDenied => Code::PermissionDenied,
-            ErrorKind::TimedOut => Code::DeadlineExceeded,
-            ErrorKind::UnexpectedEof => Code::OutOfRange,
-            _ => Code::Unknown,
-        };
-        Status::new(code, err.to_string())
+        let mut status = Status::new(io_error_code(err.kind()), err.to_string());
+        status.set_source(Arc::new(err));
+        status
     }
 }
 
+fn io_error_code(kind: std::io::ErrorKind) -> Code {
+    use std::io::ErrorKind;
+    match kind {
+        ErrorKind::BrokenPipe
+        | ErrorKind::WouldBlock
+        | ErrorKind::WriteZero
+        | ErrorKind::Interrupted => Code::Internal,
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::NotConnected
+        | ErrorKind::AddrInUse
+        | ErrorKind::AddrNotAvailable => Code::Unavailable,
+        ErrorKind::AlreadyExists => Code::AlreadyExists,
+        ErrorKind::ConnectionAborted => Code::Aborted,
+        ErrorKind::InvalidData => Code::DataLoss,
+        ErrorKind::InvalidInput => Code::InvalidArgument,
+        ErrorKind::NotFound => Code::NotFound,
+        ErrorKind::PermissionDenied => Code::PermissionDenied,
+        ErrorKind::TimedOut => Code::DeadlineExceeded,
+        ErrorKind::UnexpectedEof => Code::OutOfRange,
+        _ => Code::Unknown,
+    }
+}
+
+/// Formats the code, message, details and every metadata entry.
+///
+/// Binary values, including the details, are not printed. They are replaced with the length of
+/// their base64 encoding so that logs are not cluttered with binary data.
 impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let details_len = base64::encoded_len(self.details.len(), false).unwrap_or(usize::MAX);
+
         write!(
             f,
-            "status: {:?}, message: {:?}, details: {:?}, metadata: {:?}",
+            "status: {:?}, message: {:?}, details: <{} bytes base64>, metadata: {{",
             self.code(),
             self.message(),
-            self.details(),
-            self.metadata(),
-        )
+            details_len,
+        )?;
+
+        for (i, entry) in self.metadata.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            match entry {
+                KeyAndValueRef::Ascii(key, value) => write!(f, "{:?}: {:?}", key, value)?,
+                KeyAndValueRef::Binary(key, value) => write!(
+                    f,
+                    "{:?}: <{} bytes base64>",
+                    key,
+                    value.as_encoded_bytes().len()
+                )?,
+            }
+        }
+
+        f.write_str("}")
     }
 }
 
@@ -729,6 +965,7 @@ impl Error for Status {
 ///
 /// Take the `Status` value from `trailers` if it is available, else from `status_code`.
 ///
+#[allow(clippy::result_large_err)]
 pub(crate) fn infer_grpc_status(
     trailers: Option<&HeaderMap>,
     status_code: http::StatusCode,
@@ -877,6 +1114,7 @@ impl From<Code> for i32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::metadata::MetadataValue;
     use crate::Error;
 
     #[derive(Debug)]
@@ -938,6 +1176,46 @@ mod tests {
         assert_eq!(source.reason(), Some(h2::Reason::CANCEL));
     }
 
+    #[test]
+    #[cfg(any(feature = "server", feature = "channel"))]
+    fn from_error_h2_reset_is_not_goaway() {
+        let found = Status::from_error(Box::new(h2::Error::from(h2::Reason::NO_ERROR)));
+
+        assert_eq!(found.code(), Code::Internal);
+        assert!(!found.is_goaway());
+        assert!(!Status::unavailable("").is_goaway());
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "server")]
+    async fn from_error_h2_goaway() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (io, _) = listener.accept().await.unwrap();
+            let mut connection = h2::server::handshake(io).await.unwrap();
+            // Go away before processing any stream, as a server shutting down.
+            connection.abrupt_shutdown(h2::Reason::NO_ERROR);
+            while let Some(Ok(_)) = connection.accept().await {}
+        });
+
+        let io = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (mut client, connection) = h2::client::handshake(io).await.unwrap();
+        let request = http::Request::get("http://example.com/test.Test/Call")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        tokio::spawn(connection);
+        let err = response.await.unwrap_err();
+        assert!(err.is_go_away() && err.is_remote(), "{err:?}");
+
+        let found = Status::from_error(Box::new(err));
+        assert_eq!(found.code(), Code::Unavailable);
+        assert!(found.is_goaway());
+
+        server.await.unwrap();
+    }
+
     #[test]
     #[cfg(feature = "server")]
     fn to_h2_error() {
@@ -963,6 +1241,22 @@ mod tests {
         assert_eq!(Code::from(-1), Code::Unknown);
     }
 
+    #[test]
+    fn code_grpc_names() {
+        // The proto enum names are the upper snake case variant names.
+        for i in 0..=(Code::Unauthenticated as i32) {
+            let code = Code::from(i);
+            let mut expected = String::new();
+            for (index, c) in format!("{:?}", code).char_indices() {
+                if c.is_uppercase() && index > 0 {
+                    expected.push('_');
+                }
+                expected.push(c.to_ascii_uppercase());
+            }
+            assert_eq!(code.to_grpc_name(), expected);
+        }
+    }
+
     #[test]
     fn constructors() {
         assert_eq!(Status::ok("").code(), Code::Ok);
@@ -1008,6 +1302,167 @@ mod tests {
 
         assert_eq!(status.details(), DETAILS);
     }
+
+    #[test]
+    fn message_round_trips_through_headers() {
+        for message in [
+            "naïve café ☕",
+            "100% done",
+            "literal %41 escape",
+            "line\nbreak",
+        ] {
+            let status = Status::new(Code::Internal, message);
+            let header_map = status.to_header_map().unwrap();
+
+            assert!(header_map[super::GRPC_STATUS_MESSAGE_HEADER]
+                .as_bytes()
+                .is_ascii());
+
+            let status = Status::from_header_map(&header_map).unwrap();
+            assert_eq!(status.code(), Code::Internal);
+            assert_eq!(status.message(), message);
+        }
+    }
+
+    #[test]
+    fn invalid_message_keeps_code() {
+        let mut header_map = HeaderMap::new();
+        header_map.insert(super::GRPC_STATUS_HEADER_CODE, "5".parse().unwrap());
+        header_map.insert(
+            super::GRPC_STATUS_MESSAGE_HEADER,
+            "bad %FF".parse().unwrap(),
+        );
+
+        let status = Status::from_header_map(&header_map).unwrap();
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "bad %FF");
+    }
+
+    #[test]
+    fn unknown_code_keeps_raw_value() {
+        for (header, raw_code) in [("99", 99), ("-1", -1)] {
+            let mut header_map = HeaderMap::new();
+            header_map.insert(super::GRPC_STATUS_HEADER_CODE, header.parse().unwrap());
+
+            let status = Status::from_header_map(&header_map).unwrap();
+            assert_eq!(status.code(), Code::Unknown);
+            assert_eq!(status.raw_code(), raw_code);
+        }
+
+        let mut header_map = HeaderMap::new();
+        header_map.insert(super::GRPC_STATUS_HEADER_CODE, "5".parse().unwrap());
+        let status = Status::from_header_map(&header_map).unwrap();
+        assert_eq!(status.raw_code(), Code::NotFound as i32);
+        assert_eq!(Status::unavailable("").raw_code(), 14);
+    }
+
+    #[test]
+    fn raw_code_round_trips_through_headers() {
+        let mut header_map = HeaderMap::new();
+        header_map.insert(super::GRPC_STATUS_HEADER_CODE, "99".parse().unwrap());
+        let status = Status::from_header_map(&header_map).unwrap();
+
+        let header_map = status.to_header_map().unwrap();
+        assert_eq!(header_map[super::GRPC_STATUS_HEADER_CODE], "99");
+
+        let status = Status::from_header_map(&header_map).unwrap();
+        assert_eq!(status.code(), Code::Unknown);
+        assert_eq!(status.raw_code(), 99);
+    }
+
+    #[test]
+    fn display_includes_metadata() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("retry-after", "5".parse().unwrap());
+        metadata.insert_bin("trace-bin", MetadataValue::from_bytes(&[0; 10]));
+
+        let status = Status::with_details_and_metadata(
+            Code::Unavailable,
+            "try again",
+            Bytes::from_static(&[1, 2, 3]),
+            metadata,
+        );
+
+        assert_eq!(
+            status.to_string(),
+            "status: Unavailable, message: \"try again\", details: <4 bytes base64>, \
+             metadata: {\"retry-after\": \"5\", \"trace-bin\": <14 bytes base64>}"
+        );
+    }
+
+    #[test]
+    fn from_io_error_maps_kinds() {
+        use std::io::{Error, ErrorKind};
+
+        let cases = [
+            (ErrorKind::NotFound, Code::NotFound),
+            (ErrorKind::PermissionDenied, Code::PermissionDenied),
+            (ErrorKind::AlreadyExists, Code::AlreadyExists),
+            (ErrorKind::InvalidInput, Code::InvalidArgument),
+            (ErrorKind::InvalidData, Code::DataLoss),
+            (ErrorKind::TimedOut, Code::DeadlineExceeded),
+            (ErrorKind::UnexpectedEof, Code::OutOfRange),
+            (ErrorKind::ConnectionAborted, Code::Aborted),
+            (ErrorKind::ConnectionRefused, Code::Unavailable),
+            (ErrorKind::ConnectionReset, Code::Unavailable),
+            (ErrorKind::BrokenPipe, Code::Internal),
+            (ErrorKind::Other, Code::Unknown),
+        ];
+
+        for (kind, code) in cases {
+            let err = Error::new(kind, "disk on fire");
+            let status = Status::from_io_error(&err);
+            assert_eq!(status.code(), code, "{kind:?}");
+            assert_eq!(status.message(), "disk on fire");
+
+            let source = std::error::Error::source(&status).unwrap();
+            let source = source.downcast_ref::<Error>().unwrap();
+            assert_eq!(source.kind(), kind);
+            assert_eq!(Status::from(err).code(), code);
+        }
+    }
+
+    #[test]
+    fn retry_after_round_trips() {
+        let status = Status::deadline_exceeded_with_retry(Duration::from_secs(2));
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert_eq!(status.metadata().get("grpc-retry-after").unwrap(), "2");
+
+        let status = Status::from_header_map(&status.to_header_map().unwrap()).unwrap();
+        assert_eq!(status.parse_retry_after(), Some(Duration::from_secs(2)));
+
+        let mut status = Status::unavailable("");
+        assert_eq!(status.parse_retry_after(), None);
+        for invalid in ["-1", "soon", "NaN"] {
+            status
+                .metadata_mut()
+                .insert("grpc-retry-after", invalid.parse().unwrap());
+            assert_eq!(status.parse_retry_after(), None);
+        }
+    }
+
+    #[test]
+    fn append_metadata_keeps_existing_values() {
+        let mut status = Status::unavailable("");
+        status
+            .metadata_mut()
+            .insert("x-route", "a".parse().unwrap());
+        status
+            .append_metadata(MetadataKey::from_static("x-route"), "b".parse().unwrap())
+            .append_binary_metadata(
+                MetadataKey::from_static("x-route-bin"),
+                MetadataValue::from_bytes(b"c"),
+            )
+            .append_binary_metadata(
+                MetadataKey::from_static("x-route-bin"),
+                MetadataValue::from_bytes(b"d"),
+            );
+
+        let header_map = status.to_header_map().unwrap();
+        let values = |key| header_map.get_all(key).iter().collect::<Vec<_>>();
+        assert_eq!(values("x-route"), ["a", "b"]);
+        assert_eq!(values("x-route-bin"), ["Yw", "ZA"]);
+    }
 }
 
 /// Error returned if a request didn't complete within the configured timeout.
@@ -1,15 +1,15 @@
 #[cfg(feature = "tls")]
 use super::service::TlsConnector;
 use super::service::{self, Executor, SharedExec};
-use super::Channel;
 #[cfg(feature = "tls")]
 use super::ClientTlsConfig;
-use crate::transport::Error;
+use super::{Channel, ServiceConfig};
+use crate::{extensions::MessageSizeLimits, transport::Error};
 use bytes::Bytes;
 use http::{uri::Uri, HeaderValue};
 use hyper::rt;
 use hyper_util::client::legacy::connect::HttpConnector;
-use std::{fmt, future::Future, pin::Pin, str::FromStr, time::Duration};
+use std::{fmt, future::Future, pin::Pin, str::FromStr, sync::Arc, time::Duration};
 use tower_service::Service;
 
 /// Channel builder.
@@ -18,6 +18,7 @@ use tower_service::Service;
 #[derive(Clone)]
 pub struct Endpoint {
     pub(crate) uri: Uri,
+    pub(crate) name: Option<Arc<str>>,
     pub(crate) origin: Option<Uri>,
     pub(crate) user_agent: Option<HeaderValue>,
     pub(crate) timeout: Option<Duration>,
@@ -36,7 +37,10 @@ pub struct Endpoint {
     pub(crate) http2_max_header_list_size: Option<u32>,
     pub(crate) connect_timeout: Option<Duration>,
     pub(crate) http2_adaptive_window: Option<bool>,
+    pub(crate) idle_timeout: Option<Duration>,
     pub(crate) executor: SharedExec,
+    pub(crate) service_config: Option<Arc<ServiceConfig>>,
+    pub(crate) message_size_limits: MessageSizeLimits,
 }
 
 impl Endpoint {
@@ -107,6 +111,29 @@ impl Endpoint {
             .map_err(|_| Error::new_invalid_user_agent())
     }
 
+    /// Label the channels created from this endpoint with `name`.
+    ///
+    /// Once resolved, the addresses of the downstream services don't tell them apart. The name,
+    /// typically that of the service reached through the endpoint, is shown in the `Debug`
+    /// output of the [`Channel`] and recorded as the `name` field of a `grpc.channel` span
+    /// around each of its requests, its background worker and its connection tasks, so that they
+    /// can be told apart in logs and traces.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// let endpoint = Endpoint::from_static("http://10.0.0.1:50051").named("users");
+    ///
+    /// assert_eq!(endpoint.name(), Some("users"));
+    /// ```
+    ///
+    /// [`Channel`]: super::Channel
+    pub fn named(self, name: impl Into<String>) -> Self {
+        Endpoint {
+            name: Some(name.into().into()),
+            ..self
+        }
+    }
+
     /// Set a custom origin.
     ///
     /// Override the `origin`, mainly useful when you are reaching a Server/LoadBalancer
@@ -151,6 +178,11 @@ impl Endpoint {
 
     /// Apply a timeout to connecting to the uri.
     ///
+    /// The timeout covers establishing the connection, that is the TCP connection and the TLS
+    /// handshake, or whatever a custom connector does. A connection that isn't established in
+    /// time fails with a transport error. This is separate from the [request timeout], so that
+    /// failing to connect can be told apart from a slow call.
+    ///
     /// Defaults to no timeout.
     ///
     /// ```
@@ -159,6 +191,8 @@ impl Endpoint {
     /// # let mut builder = Endpoint::from_static("https://example.com");
     /// builder.connect_timeout(Duration::from_secs(5));
     /// ```
+    ///
+    /// [request timeout]: Endpoint::timeout
     pub fn connect_timeout(self, dur: Duration) -> Self {
         Endpoint {
             connect_timeout: Some(dur),
@@ -181,6 +215,66 @@ impl Endpoint {
         }
     }
 
+    /// Apply per-method timeouts and retry policies from a [`ServiceConfig`].
+    ///
+    /// The config is only used by channels connected from this endpoint, balanced channels
+    /// ignore it.
+    ///
+    /// ```
+    /// # use tonic::transport::{Endpoint, channel::{MethodConfig, ServiceConfig}};
+    /// # use std::time::Duration;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.service_config(
+    ///     ServiceConfig::new().method_config(MethodConfig::new().timeout(Duration::from_secs(5))),
+    /// );
+    /// ```
+    pub fn service_config(self, config: ServiceConfig) -> Self {
+        Endpoint {
+            service_config: Some(Arc::new(config)),
+            ..self
+        }
+    }
+
+    /// Limits the maximum size of a decoded message for every client using channels
+    /// connected from this endpoint.
+    ///
+    /// Clients that set their own limit, with [`Grpc::max_decoding_message_size`] or
+    /// [`Grpc::with_default_limits`], keep it. This only replaces the built-in default of
+    /// `4MB` and, like the [`ServiceConfig`], is ignored by balanced channels.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.max_decoding_message_size(16 * 1024 * 1024);
+    /// ```
+    ///
+    /// [`Grpc::max_decoding_message_size`]: crate::client::Grpc::max_decoding_message_size
+    /// [`Grpc::with_default_limits`]: crate::client::Grpc::with_default_limits
+    pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+        self.message_size_limits.max_decoding_message_size = Some(limit);
+        self
+    }
+
+    /// Limits the maximum size of an encoded message for every client using channels
+    /// connected from this endpoint.
+    ///
+    /// Clients that set their own limit, with [`Grpc::max_encoding_message_size`] or
+    /// [`Grpc::with_default_limits`], keep it. This only replaces the built-in default of
+    /// `usize::MAX` and, like the [`ServiceConfig`], is ignored by balanced channels.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.max_encoding_message_size(16 * 1024 * 1024);
+    /// ```
+    ///
+    /// [`Grpc::max_encoding_message_size`]: crate::client::Grpc::max_encoding_message_size
+    /// [`Grpc::with_default_limits`]: crate::client::Grpc::with_default_limits
+    pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+        self.message_size_limits.max_encoding_message_size = Some(limit);
+        self
+    }
+
     /// Apply a concurrency limit to each request.
     ///
     /// ```
@@ -280,6 +374,26 @@ impl Endpoint {
         }
     }
 
+    /// Close connections that have had no open stream for `duration`.
+    ///
+    /// The channel connects again on the next call. HTTP/2 keep-alive pings don't count as
+    /// activity, so an idle connection is closed even with [`Endpoint::keep_alive_while_idle`].
+    ///
+    /// Defaults to keeping idle connections open.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # use std::time::Duration;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.idle_timeout(Duration::from_secs(300));
+    /// ```
+    pub fn idle_timeout(self, duration: Duration) -> Self {
+        Endpoint {
+            idle_timeout: Some(duration),
+            ..self
+        }
+    }
+
     /// Set http2 KEEP_ALIVE_WHILE_IDLE. Uses `hyper`'s default otherwise.
     pub fn keep_alive_while_idle(self, enabled: bool) -> Self {
         Endpoint {
@@ -322,6 +436,7 @@ impl Endpoint {
             c,
             #[cfg(feature = "tls")]
             self.tls.clone(),
+            self.connect_timeout,
         )
     }
 
@@ -369,14 +484,7 @@ impl Endpoint {
         crate::Error: From<C::Error> + Send,
     {
         let connector = self.connector(connector);
-
-        if let Some(connect_timeout) = self.connect_timeout {
-            let mut connector = hyper_timeout::TimeoutConnector::new(connector);
-            connector.set_connect_timeout(Some(connect_timeout));
-            Channel::connect(connector, self.clone()).await
-        } else {
-            Channel::connect(connector, self.clone()).await
-        }
+        Channel::connect(connector, self.clone()).await
     }
 
     /// Connect with a custom connector lazily.
@@ -384,6 +492,8 @@ impl Endpoint {
     /// This allows you to build a [Channel](struct.Channel.html) that uses a non-HTTP transport
     /// connect to it lazily.
     ///
+    /// The [`connect_timeout`](Endpoint::connect_timeout) will still be applied.
+    ///
     /// See the `uds` example for an example on how to use this function to build channel that
     /// uses a Unix socket transport.
     pub fn connect_with_connector_lazy<C>(&self, connector: C) -> Channel
@@ -394,13 +504,7 @@ impl Endpoint {
         crate::Error: From<C::Error> + Send,
     {
         let connector = self.connector(connector);
-        if let Some(connect_timeout) = self.connect_timeout {
-            let mut connector = hyper_timeout::TimeoutConnector::new(connector);
-            connector.set_connect_timeout(Some(connect_timeout));
-            Channel::new(connector, self.clone())
-        } else {
-            Channel::new(connector, self.clone())
-        }
+        Channel::new(connector, self.clone())
     }
 
     /// Get the endpoint uri.
@@ -415,12 +519,18 @@ impl Endpoint {
     pub fn uri(&self) -> &Uri {
         &self.uri
     }
+
+    /// Get the name set with [`Endpoint::named`].
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
 }
 
 impl From<Uri> for Endpoint {
     fn from(uri: Uri) -> Self {
         Self {
             uri,
+            name: None,
             origin: None,
             user_agent: None,
             concurrency_limit: None,
@@ -439,7 +549,10 @@ impl From<Uri> for Endpoint {
             http2_max_header_list_size: None,
             connect_timeout: None,
             http2_adaptive_window: None,
+            idle_timeout: None,
             executor: SharedExec::tokio(),
+            service_config: None,
+            message_size_limits: MessageSizeLimits::default(),
         }
     }
 }
@@ -1,16 +1,25 @@
 //! Client implementation and builder.
 
 mod endpoint;
+mod resolver;
 pub(crate) mod service;
+mod service_config;
 #[cfg(feature = "tls")]
 mod tls;
 
 pub use endpoint::Endpoint;
+pub use resolver::{NameResolver, ResolveFuture};
+pub use service_config::{Jitter, MethodConfig, RetryPolicy, ServiceConfig};
 #[cfg(feature = "tls")]
-pub use tls::ClientTlsConfig;
+pub use tls::{ClientTlsConfig, TlsSessionInfo};
 
-use self::service::{Connection, DynamicServiceStream, Executor, SharedExec};
-use crate::body::BoxBody;
+use self::service::{
+    method_config, Connection, DynamicServiceStream, Executor, SharedExec, WarmUp,
+};
+use crate::{
+    body::{empty_body, BoxBody},
+    extensions::{MessageSizeDefaults, MessageSizeLimits},
+};
 use bytes::Bytes;
 use http::{
     uri::{InvalidUri, Uri},
@@ -19,10 +28,12 @@ use http::{
 use hyper_util::client::legacy::connect::Connection as HyperConnection;
 use std::{
     fmt,
-    future::Future,
+    future::{self, Future},
     hash::Hash,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::sync::mpsc::{channel, Sender};
 
@@ -66,13 +77,22 @@ const DEFAULT_BUFFER_SIZE: usize = 1024;
 #[derive(Clone)]
 pub struct Channel {
     svc: Buffer<Svc, Request<BoxBody>>,
+    name: Option<Arc<str>>,
+    service_config: Option<Arc<ServiceConfig>>,
+    message_size_limits: MessageSizeLimits,
 }
 
 /// A future that resolves to an HTTP response.
 ///
 /// This is returned by the `Service::call` on [`Channel`].
 pub struct ResponseFuture {
-    inner: buffer::future::ResponseFuture<<Svc as Service<Request<BoxBody>>>::Future>,
+    inner: ResponseFutureInner,
+    span: tracing::Span,
+}
+
+enum ResponseFutureInner {
+    Buffered(buffer::future::ResponseFuture<<Svc as Service<Request<BoxBody>>>::Future>),
+    MethodConfig(BoxFuture<'static, Result<Response<BoxBody>, crate::Error>>),
 }
 
 impl Channel {
@@ -145,6 +165,70 @@ impl Channel {
         (Self::balance(list, DEFAULT_BUFFER_SIZE, executor), tx)
     }
 
+    /// Balance across the endpoints a [`NameResolver`] resolves `name` to.
+    ///
+    /// The name is resolved right away and then every `refresh_interval`, endpoints that appear
+    /// are added to the [`Channel`] and endpoints that disappear are removed. Endpoints are told
+    /// apart by their URI, so changing the configuration of an endpoint whose URI stays the same
+    /// has no effect. If resolving fails, the current endpoints are kept until it succeeds again.
+    ///
+    /// Resolving stops once every clone of the [`Channel`] has been dropped.
+    pub fn balance_resolver(
+        name: impl Into<String>,
+        resolver: impl NameResolver,
+        refresh_interval: Duration,
+    ) -> Self {
+        let (channel, tx) = Self::balance_channel(DEFAULT_BUFFER_SIZE);
+        let executor = SharedExec::tokio();
+        executor.execute(Box::pin(resolver::run(
+            name.into(),
+            resolver,
+            refresh_interval,
+            tx,
+        )));
+
+        channel
+    }
+
+    /// Label this channel with `name`, see [`Endpoint::named`].
+    ///
+    /// Channels balancing across several endpoints are not named after any of them, this names
+    /// them as a whole. The background tasks of the channel are already running and keep the
+    /// name of their endpoint, only the requests are recorded under this name.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into().into());
+        self
+    }
+
+    /// Get the name of this channel, see [`Endpoint::named`].
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Establish the connection ahead of the first call.
+    ///
+    /// Channels created with [`Endpoint::connect_lazy`] connect when the first call is made, so
+    /// the TCP connection, TLS handshake and HTTP/2 settings exchange add to its latency. This
+    /// connects the channel beforehand, without sending anything to the server, which is useful
+    /// in benchmarks and during startup. Connection errors are returned. A balanced channel only
+    /// connects to one of its endpoints.
+    ///
+    /// ```rust
+    /// # use tonic::transport::Channel;
+    /// # async fn ex() -> Result<(), tonic::transport::Error> {
+    /// let mut channel = Channel::from_static("http://[::1]:50051").connect_lazy();
+    /// channel.warm_up().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn warm_up(&mut self) -> Result<(), super::Error> {
+        let mut request = Request::new(empty_body());
+        request.extensions_mut().insert(WarmUp);
+
+        future::poll_fn(|cx| Service::poll_ready(self, cx)).await?;
+        self.call(request).await.map(drop)
+    }
+
     pub(crate) fn new<C>(connector: C, endpoint: Endpoint) -> Self
     where
         C: Service<Uri> + Send + 'static,
@@ -153,13 +237,24 @@ impl Channel {
         C::Response: rt::Read + rt::Write + HyperConnection + Unpin + Send + 'static,
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
-        let executor = endpoint.executor.clone();
+        let name = endpoint.name.clone();
+        let executor = endpoint
+            .executor
+            .clone()
+            .in_span(channel_span(name.as_deref()));
+        let service_config = endpoint.service_config.clone();
+        let message_size_limits = endpoint.message_size_limits;
 
         let svc = Connection::lazy(connector, endpoint);
         let (svc, worker) = Buffer::pair(Either::A(svc), buffer_size);
         executor.execute(worker);
 
-        Channel { svc }
+        Channel {
+            svc,
+            name,
+            service_config,
+            message_size_limits,
+        }
     }
 
     pub(crate) async fn connect<C>(connector: C, endpoint: Endpoint) -> Result<Self, super::Error>
@@ -170,7 +265,13 @@ impl Channel {
         C::Response: rt::Read + rt::Write + HyperConnection + Unpin + Send + 'static,
     {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
-        let executor = endpoint.executor.clone();
+        let name = endpoint.name.clone();
+        let executor = endpoint
+            .executor
+            .clone()
+            .in_span(channel_span(name.as_deref()));
+        let service_config = endpoint.service_config.clone();
+        let message_size_limits = endpoint.message_size_limits;
 
         let svc = Connection::connect(connector, endpoint)
             .await
@@ -178,7 +279,12 @@ impl Channel {
         let (svc, worker) = Buffer::pair(Either::A(svc), buffer_size);
         executor.execute(worker);
 
-        Ok(Channel { svc })
+        Ok(Channel {
+            svc,
+            name,
+            service_config,
+            message_size_limits,
+        })
     }
 
     pub(crate) fn balance<D, E>(discover: D, buffer_size: usize, executor: E) -> Self
@@ -194,7 +300,20 @@ impl Channel {
         let (svc, worker) = Buffer::pair(Either::B(svc), buffer_size);
         executor.execute(Box::pin(worker));
 
-        Channel { svc }
+        Channel {
+            svc,
+            name: None,
+            service_config: None,
+            message_size_limits: MessageSizeLimits::default(),
+        }
+    }
+}
+
+/// The `grpc.channel` span of a channel named with [`Endpoint::named`].
+pub(crate) fn channel_span(name: Option<&str>) -> tracing::Span {
+    match name {
+        Some(name) => tracing::debug_span!("grpc.channel", name = %name),
+        None => tracing::Span::none(),
     }
 }
 
@@ -208,25 +327,49 @@ impl Service<http::Request<BoxBody>> for Channel {
     }
 
     fn call(&mut self, request: http::Request<BoxBody>) -> Self::Future {
-        let inner = Service::call(&mut self.svc, request);
-
-        ResponseFuture { inner }
+        if let Some(size_defaults) = request.extensions().get::<MessageSizeDefaults>() {
+            size_defaults.set(self.message_size_limits);
+        }
+
+        let method_config = self
+            .service_config
+            .as_ref()
+            .and_then(|config| config.lookup(request.uri().path()));
+
+        let inner = match method_config {
+            Some(config) => ResponseFutureInner::MethodConfig(Box::pin(method_config::call(
+                self.svc.clone(),
+                request,
+                config.timeout,
+                config.retry_policy.clone(),
+            ))),
+            None => ResponseFutureInner::Buffered(Service::call(&mut self.svc, request)),
+        };
+
+        let span = channel_span(self.name.as_deref());
+
+        ResponseFuture { inner, span }
     }
 }
 
 impl Future for ResponseFuture {
     type Output = Result<Response<BoxBody>, super::Error>;
 
-    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Pin::new(&mut self.inner)
-            .poll(cx)
-            .map_err(super::Error::from_source)
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let _guard = this.span.enter();
+
+        match &mut this.inner {
+            ResponseFutureInner::Buffered(inner) => Pin::new(inner).poll(cx),
+            ResponseFutureInner::MethodConfig(inner) => inner.as_mut().poll(cx),
+        }
+        .map_err(super::Error::from_source)
     }
 }
 
 impl fmt::Debug for Channel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Channel").finish()
+        f.debug_struct("Channel").field("name", &self.name).finish()
     }
 }
 
@@ -235,3 +378,107 @@ impl fmt::Debug for ResponseFuture {
         f.debug_struct("ResponseFuture").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        fmt,
+        sync::{Arc, Mutex},
+    };
+    use tokio::net::TcpListener;
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    #[tokio::test]
+    async fn endpoint_name_is_passed_to_channels() {
+        let channel = Endpoint::from_static("http://127.0.0.1:1")
+            .named("users")
+            .connect_lazy();
+        assert_eq!(channel.name(), Some("users"));
+        assert_eq!(format!("{channel:?}"), r#"Channel { name: Some("users") }"#);
+
+        let channel = channel.named("accounts");
+        assert_eq!(channel.name(), Some("accounts"));
+
+        let channel = Endpoint::from_static("http://127.0.0.1:1").connect_lazy();
+        assert_eq!(channel.name(), None);
+    }
+
+    /// Records the `name` of the `grpc.channel` spans that are entered.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        names: Arc<Mutex<HashMap<u64, Option<String>>>>,
+        entered: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct Name(Option<String>);
+
+    impl Visit for Name {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "name" {
+                self.0 = Some(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            let mut names = self.names.lock().unwrap();
+            let id = names.len() as u64 + 1;
+            let mut name = Name(None);
+            if attrs.metadata().name() == "grpc.channel" {
+                attrs.record(&mut name);
+            }
+            names.insert(id, name.0);
+            span::Id::from_u64(id)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, id: &span::Id) {
+            if let Some(name) = &self.names.lock().unwrap()[&id.into_u64()] {
+                self.entered.lock().unwrap().push(name.clone());
+            }
+        }
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn background_tasks_run_in_the_channel_span() {
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await
+        });
+
+        let _channel = Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .named("users")
+            .connect()
+            .await
+            .unwrap();
+        // Let the worker and the connection task run, no request is made.
+        tokio::task::yield_now().await;
+
+        let entered = recorder.entered.lock().unwrap().clone();
+        assert!(!entered.is_empty());
+        assert!(entered.iter().all(|name| name == "users"), "{entered:?}");
+    }
+}
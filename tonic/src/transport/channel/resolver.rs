@@ -0,0 +1,148 @@
+use super::Endpoint;
+use http::Uri;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    time::Duration,
+};
+use tokio::sync::mpsc::Sender;
+use tower::discover::Change;
+
+/// The future returned by [`NameResolver::resolve`].
+pub type ResolveFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Vec<Endpoint>, crate::Error>> + Send + 'a>>;
+
+/// Resolves a service name to the endpoints serving it, for service discovery backends such as
+/// Consul or the Kubernetes Endpoints API.
+///
+/// Used by [`Channel::balance_resolver`], which resolves the name periodically and balances
+/// requests across the resolved endpoints.
+///
+/// ```rust
+/// # use tonic::transport::{channel::ResolveFuture, Endpoint, NameResolver};
+/// struct Static(Vec<&'static str>);
+///
+/// impl NameResolver for Static {
+///     fn resolve<'a>(&'a self, _name: &'a str) -> ResolveFuture<'a> {
+///         let endpoints = self.0.iter().map(|uri| Endpoint::from_static(uri)).collect();
+///         Box::pin(async move { Ok(endpoints) })
+///     }
+/// }
+/// ```
+///
+/// [`Channel::balance_resolver`]: super::Channel::balance_resolver
+pub trait NameResolver: Send + Sync + 'static {
+    /// Returns the endpoints currently serving `name`.
+    fn resolve<'a>(&'a self, name: &'a str) -> ResolveFuture<'a>;
+}
+
+/// Resolves `name` every `interval` and reports the differences to the previously resolved
+/// endpoints through `changes`, until the receiving channel is dropped.
+pub(crate) async fn run<R: NameResolver>(
+    name: String,
+    resolver: R,
+    interval: Duration,
+    changes: Sender<Change<Uri, Endpoint>>,
+) {
+    let mut current = HashSet::new();
+
+    loop {
+        match resolver.resolve(&name).await {
+            Ok(endpoints) => {
+                let mut resolved: HashMap<_, _> = endpoints
+                    .into_iter()
+                    .map(|endpoint| (endpoint.uri.clone(), endpoint))
+                    .collect();
+
+                let removed: Vec<_> = current
+                    .iter()
+                    .filter(|uri| !resolved.contains_key(*uri))
+                    .cloned()
+                    .collect();
+                for uri in removed {
+                    current.remove(&uri);
+                    if changes.send(Change::Remove(uri)).await.is_err() {
+                        return;
+                    }
+                }
+
+                resolved.retain(|uri, _| !current.contains(uri));
+                for (uri, endpoint) in resolved {
+                    current.insert(uri.clone());
+                    if changes.send(Change::Insert(uri, endpoint)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            // Keep the current endpoints until the name resolves again.
+            Err(err) => tracing::debug!("failed to resolve {}: {}", name, err),
+        }
+
+        if tokio::time::timeout(interval, changes.closed())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::VecDeque, sync::Mutex};
+
+    struct Scripted(Mutex<VecDeque<Result<Vec<&'static str>, &'static str>>>);
+
+    impl NameResolver for Scripted {
+        fn resolve<'a>(&'a self, name: &'a str) -> ResolveFuture<'a> {
+            assert_eq!(name, "test");
+            let result = match self.0.lock().unwrap().pop_front() {
+                Some(Ok(uris)) => Ok(uris.into_iter().map(Endpoint::from_static).collect()),
+                Some(Err(err)) => Err(err.into()),
+                None => Ok(vec![Endpoint::from_static("http://c")]),
+            };
+            Box::pin(async move { result })
+        }
+    }
+
+    fn describe(change: Change<Uri, Endpoint>) -> String {
+        match change {
+            Change::Insert(uri, _) => format!("+{}", uri.host().unwrap()),
+            Change::Remove(uri) => format!("-{}", uri.host().unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_endpoint_changes() {
+        let resolver = Scripted(Mutex::new(VecDeque::from([
+            Ok(vec!["http://a", "http://b"]),
+            Err("unavailable"),
+            Ok(vec!["http://b", "http://c"]),
+        ])));
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let task = tokio::spawn(run(
+            "test".to_string(),
+            resolver,
+            Duration::from_millis(1),
+            tx,
+        ));
+
+        let mut first = vec![
+            describe(rx.recv().await.unwrap()),
+            describe(rx.recv().await.unwrap()),
+        ];
+        first.sort();
+        assert_eq!(first, ["+a", "+b"]);
+
+        // The failed resolution keeps the endpoints, the next one only reports the difference.
+        assert_eq!(describe(rx.recv().await.unwrap()), "-a");
+        assert_eq!(describe(rx.recv().await.unwrap()), "+c");
+
+        // Dropping the receiver stops the resolver.
+        drop(rx);
+        task.await.unwrap();
+    }
+}
@@ -1,15 +1,28 @@
 use super::{AddOrigin, Reconnect, SharedExec, UserAgent};
 use crate::{
-    body::{boxed, BoxBody},
-    transport::{channel::BoxFuture, service::GrpcTimeout, Endpoint},
+    body::{boxed, empty_body, BoxBody},
+    codec::TERMINAL_METADATA_HEADER,
+    extensions::TerminalMetadataAcknowledged,
+    transport::{
+        channel::{channel_span, BoxFuture},
+        service::{GrpcTimeout, IdleTracker, TrackedBody},
+        Endpoint,
+    },
 };
 use http::{Request, Response, Uri};
 use hyper::rt;
 use hyper::{client::conn::http2::Builder, rt::Executor};
-use hyper_util::rt::TokioTimer;
+use hyper_util::{client::legacy::connect::Connection as HyperConnection, rt::TokioTimer};
 use std::{
     fmt,
+    future::{self, Future},
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
 use tower::load::Load;
 use tower::{
@@ -19,6 +32,7 @@ use tower::{
     ServiceBuilder, ServiceExt,
 };
 use tower_service::Service;
+use tracing::{Instrument, Span};
 
 pub(crate) struct Connection {
     inner: BoxService<Request<BoxBody>, Response<BoxBody>, crate::Error>,
@@ -30,9 +44,12 @@ impl Connection {
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
         C::Future: Send,
-        C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
+        C::Response: rt::Read + rt::Write + HyperConnection + Unpin + Send + 'static,
     {
-        let mut settings: Builder<SharedExec> = Builder::new(endpoint.executor.clone())
+        let span = channel_span(endpoint.name.as_deref());
+        let executor = endpoint.executor.clone().in_span(span.clone());
+
+        let mut settings: Builder<SharedExec> = Builder::new(executor.clone())
             .initial_stream_window_size(endpoint.init_stream_window_size)
             .initial_connection_window_size(endpoint.init_connection_window_size)
             .keep_alive_interval(endpoint.http2_keep_alive_interval)
@@ -68,7 +85,7 @@ impl Connection {
             .into_inner();
 
         let make_service =
-            MakeSendRequestService::new(connector, endpoint.executor.clone(), settings);
+            MakeSendRequestService::new(connector, executor, settings, endpoint.idle_timeout, span);
 
         let conn = Reconnect::new(make_service, endpoint.uri.clone(), is_lazy);
 
@@ -82,7 +99,7 @@ impl Connection {
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
         C::Future: Unpin + Send,
-        C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
+        C::Response: rt::Read + rt::Write + HyperConnection + Unpin + Send + 'static,
     {
         Self::new(connector, endpoint, false).ready_oneshot().await
     }
@@ -92,7 +109,7 @@ impl Connection {
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::Error> + Send,
         C::Future: Send,
-        C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
+        C::Response: rt::Read + rt::Write + HyperConnection + Unpin + Send + 'static,
     {
         Self::new(connector, endpoint, true)
     }
@@ -126,14 +143,21 @@ impl fmt::Debug for Connection {
     }
 }
 
+/// Marks the request of [`Channel::warm_up`], which is answered by the connection once it is
+/// established instead of being sent.
+///
+/// [`Channel::warm_up`]: crate::transport::Channel::warm_up
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WarmUp;
+
 struct SendRequest {
     inner: hyper::client::conn::http2::SendRequest<BoxBody>,
-}
-
-impl From<hyper::client::conn::http2::SendRequest<BoxBody>> for SendRequest {
-    fn from(inner: hyper::client::conn::http2::SendRequest<BoxBody>) -> Self {
-        Self { inner }
-    }
+    /// Connection details added to every response, such as the outcome of the TLS handshake.
+    extras: http::Extensions,
+    /// Tracks the open streams when the connection is closed after being idle.
+    idle: Option<IdleTracker>,
+    /// Set once the server acknowledged terminal metadata frames on this connection.
+    terminal_metadata_acknowledged: Arc<AtomicBool>,
 }
 
 impl tower::Service<Request<BoxBody>> for SendRequest {
@@ -146,9 +170,42 @@ impl tower::Service<Request<BoxBody>> for SendRequest {
     }
 
     fn call(&mut self, req: Request<BoxBody>) -> Self::Future {
+        if req.extensions().get::<WarmUp>().is_some() {
+            // The connection is established, which is all a warm-up request asks for.
+            return Box::pin(future::ready(Ok(Response::new(empty_body()))));
+        }
+
+        // The server behind this connection already read terminal metadata frames, the call can
+        // send one without waiting for its own response headers.
+        let acknowledged = req
+            .extensions()
+            .get::<TerminalMetadataAcknowledged>()
+            .map(|call| {
+                if self.terminal_metadata_acknowledged.load(Ordering::Acquire) {
+                    call.set();
+                }
+                self.terminal_metadata_acknowledged.clone()
+            });
+
         let fut = self.inner.send_request(req);
+        let extras = self.extras.clone();
+        let stream = self.idle.as_ref().map(IdleTracker::stream);
 
-        Box::pin(async move { fut.await.map_err(Into::into).map(|res| res.map(boxed)) })
+        Box::pin(async move {
+            let mut res = fut.await?;
+            if let Some(acknowledged) = acknowledged {
+                if res.headers().contains_key(TERMINAL_METADATA_HEADER) {
+                    acknowledged.store(true, Ordering::Release);
+                }
+            }
+            if !extras.is_empty() {
+                res.extensions_mut().extend(extras);
+            }
+            Ok(res.map(|body| match stream {
+                Some(stream) => boxed(TrackedBody::new(boxed(body), stream)),
+                None => boxed(body),
+            }))
+        })
     }
 }
 
@@ -156,14 +213,24 @@ struct MakeSendRequestService<C> {
     connector: C,
     executor: SharedExec,
     settings: Builder<SharedExec>,
+    idle_timeout: Option<Duration>,
+    span: Span,
 }
 
 impl<C> MakeSendRequestService<C> {
-    fn new(connector: C, executor: SharedExec, settings: Builder<SharedExec>) -> Self {
+    fn new(
+        connector: C,
+        executor: SharedExec,
+        settings: Builder<SharedExec>,
+        idle_timeout: Option<Duration>,
+        span: Span,
+    ) -> Self {
         Self {
             connector,
             executor,
             settings,
+            idle_timeout,
+            span,
         }
     }
 }
@@ -173,7 +240,7 @@ where
     C: Service<Uri> + Send + 'static,
     C::Error: Into<crate::Error> + Send,
     C::Future: Send,
-    C::Response: rt::Read + rt::Write + Unpin + Send,
+    C::Response: rt::Read + rt::Write + HyperConnection + Unpin + Send,
 {
     type Response = SendRequest;
     type Error = crate::Error;
@@ -187,21 +254,56 @@ where
         let fut = self.connector.call(req);
         let builder = self.settings.clone();
         let executor = self.executor.clone();
+        let idle_timeout = self.idle_timeout;
 
-        Box::pin(async move {
+        // The worker of a balanced channel is not named after any endpoint, connecting is logged
+        // in the span of the endpoint itself.
+        let connect = async move {
             let io = fut.await.map_err(Into::into)?;
-            let (send_request, conn) = builder.handshake(io).await?;
+            let mut extras = http::Extensions::new();
+            io.connected().get_extras(&mut extras);
 
-            Executor::<BoxFuture<'static, ()>>::execute(
-                &executor,
-                Box::pin(async move {
-                    if let Err(e) = conn.await {
-                        tracing::debug!("connection task error: {:?}", e);
+            let (send_request, conn) = builder.handshake(io).await?;
+            let idle = idle_timeout.map(|_| IdleTracker::new());
+
+            let task = {
+                let idle = idle.clone();
+                async move {
+                    let mut conn = pin!(conn);
+                    let mut expired = pin!(async {
+                        match (idle, idle_timeout) {
+                            (Some(idle), Some(timeout)) => idle.expired(timeout).await,
+                            _ => future::pending().await,
+                        }
+                    });
+
+                    let result = future::poll_fn(|cx| {
+                        if let Poll::Ready(result) = conn.as_mut().poll(cx) {
+                            return Poll::Ready(Some(result));
+                        }
+                        expired.as_mut().poll(cx).map(|()| None)
+                    })
+                    .await;
+
+                    match result {
+                        Some(Err(e)) => tracing::debug!("connection task error: {:?}", e),
+                        Some(Ok(())) => {}
+                        // Dropping the connection closes it, the next call reconnects.
+                        None => tracing::debug!("closing idle connection"),
                     }
-                }) as _,
-            );
+                }
+            };
 
-            Ok(SendRequest::from(send_request))
-        })
+            Executor::<BoxFuture<'static, ()>>::execute(&executor, Box::pin(task) as _);
+
+            Ok(SendRequest {
+                inner: send_request,
+                extras,
+                idle,
+                terminal_metadata_acknowledged: Arc::default(),
+            })
+        };
+
+        Box::pin(connect.instrument(self.span.clone()))
     }
 }
@@ -4,9 +4,11 @@ use super::TlsConnector;
 use crate::transport::channel::BoxFuture;
 use crate::ConnectError;
 use http::Uri;
-#[cfg(feature = "tls")]
-use std::fmt;
-use std::task::{Context, Poll};
+use std::{
+    fmt,
+    task::{Context, Poll},
+    time::Duration,
+};
 
 use hyper::rt;
 
@@ -18,14 +20,21 @@ pub(crate) struct Connector<C> {
     inner: C,
     #[cfg(feature = "tls")]
     tls: Option<TlsConnector>,
+    /// Limits the time to establish the connection, TLS handshake included.
+    timeout: Option<Duration>,
 }
 
 impl<C> Connector<C> {
-    pub(crate) fn new(inner: C, #[cfg(feature = "tls")] tls: Option<TlsConnector>) -> Self {
+    pub(crate) fn new(
+        inner: C,
+        #[cfg(feature = "tls")] tls: Option<TlsConnector>,
+        timeout: Option<Duration>,
+    ) -> Self {
         Self {
             inner,
             #[cfg(feature = "tls")]
             tls,
+            timeout,
         }
     }
 }
@@ -54,9 +63,10 @@ where
         #[cfg(feature = "tls")]
         let is_https = uri.scheme_str() == Some("https");
         let connect = self.inner.call(uri);
+        let timeout = self.timeout;
 
         Box::pin(async move {
-            async {
+            let connect = async {
                 let io = connect.await?;
 
                 #[cfg(feature = "tls")]
@@ -70,13 +80,31 @@ where
                 }
 
                 Ok::<_, crate::Error>(BoxedIo::new(io))
+            };
+
+            match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, connect)
+                    .await
+                    .unwrap_or_else(|_| Err(ConnectTimedOut.into())),
+                None => connect.await,
             }
-            .await
             .map_err(ConnectError)
         })
     }
 }
 
+/// Error returned when the connection could not be established within the connect timeout.
+#[derive(Debug)]
+struct ConnectTimedOut;
+
+impl fmt::Display for ConnectTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "connection timed out")
+    }
+}
+
+impl std::error::Error for ConnectTimedOut {}
+
 /// Error returned when trying to connect to an HTTPS endpoint without TLS enabled.
 #[cfg(feature = "tls")]
 #[derive(Debug)]
@@ -92,3 +120,30 @@ impl fmt::Display for HttpsUriWithoutTlsSupport {
 // std::error::Error only requires a type to impl Debug and Display
 #[cfg(feature = "tls")]
 impl std::error::Error for HttpsUriWithoutTlsSupport {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connect_timeout_covers_whole_connection() {
+        // Never finishes connecting.
+        let inner = tower::service_fn(|_: Uri| {
+            std::future::pending::<
+                Result<hyper_util::rt::TokioIo<tokio::io::DuplexStream>, crate::Error>,
+            >()
+        });
+
+        let mut connector = Connector::new(
+            inner,
+            #[cfg(feature = "tls")]
+            None,
+            Some(Duration::from_millis(10)),
+        );
+        let err = match connector.call(Uri::from_static("http://[::1]:50051")).await {
+            Ok(_) => panic!("connection should time out"),
+            Err(err) => err,
+        };
+        assert_eq!(err.0.to_string(), "connection timed out");
+    }
+}
@@ -1,6 +1,7 @@
 use crate::transport::channel::BoxFuture;
 use hyper_util::rt::TokioExecutor;
 use std::{future::Future, sync::Arc};
+use tracing::{Instrument, Span};
 
 pub(crate) use hyper::rt::Executor;
 
@@ -22,6 +23,25 @@ impl SharedExec {
     pub(crate) fn tokio() -> Self {
         Self::new(TokioExecutor::new())
     }
+
+    /// Run the futures spawned on this executor inside `span`.
+    pub(crate) fn in_span(self, span: Span) -> Self {
+        if span.is_none() {
+            return self;
+        }
+        Self::new(InSpan { inner: self, span })
+    }
+}
+
+struct InSpan {
+    inner: SharedExec,
+    span: Span,
+}
+
+impl Executor<BoxFuture<'static, ()>> for InSpan {
+    fn execute(&self, fut: BoxFuture<'static, ()>) {
+        self.inner.execute(fut.instrument(self.span.clone()))
+    }
 }
 
 impl<F> Executor<F> for SharedExec
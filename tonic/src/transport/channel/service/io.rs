@@ -5,6 +5,9 @@ use std::task::{Context, Poll};
 use hyper::rt;
 use hyper_util::client::legacy::connect::{Connected as HyperConnected, Connection};
 
+#[cfg(feature = "tls")]
+use crate::transport::channel::TlsSessionInfo;
+
 pub(in crate::transport) trait Io:
     rt::Read + rt::Write + Send + 'static
 {
@@ -12,17 +15,41 @@ pub(in crate::transport) trait Io:
 
 impl<T> Io for T where T: rt::Read + rt::Write + Send + 'static {}
 
-pub(crate) struct BoxedIo(Pin<Box<dyn Io>>);
+pub(crate) struct BoxedIo {
+    io: Pin<Box<dyn Io>>,
+    #[cfg(feature = "tls")]
+    tls_session: Option<TlsSessionInfo>,
+}
 
 impl BoxedIo {
     pub(in crate::transport) fn new<I: Io>(io: I) -> Self {
-        BoxedIo(Box::pin(io))
+        BoxedIo {
+            io: Box::pin(io),
+            #[cfg(feature = "tls")]
+            tls_session: None,
+        }
+    }
+
+    /// Attach the outcome of the TLS handshake, which is added to every response received on
+    /// this connection.
+    #[cfg(feature = "tls")]
+    pub(in crate::transport) fn with_tls_session(mut self, tls_session: TlsSessionInfo) -> Self {
+        self.tls_session = Some(tls_session);
+        self
     }
 }
 
 impl Connection for BoxedIo {
     fn connected(&self) -> HyperConnected {
-        HyperConnected::new()
+        let connected = HyperConnected::new();
+
+        #[cfg(feature = "tls")]
+        let connected = match self.tls_session {
+            Some(tls_session) => connected.extra(tls_session),
+            None => connected,
+        };
+
+        connected
     }
 }
 
@@ -32,7 +59,7 @@ impl rt::Read for BoxedIo {
         cx: &mut Context<'_>,
         buf: rt::ReadBufCursor<'_>,
     ) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.0).poll_read(cx, buf)
+        self.io.as_mut().poll_read(cx, buf)
     }
 }
 
@@ -42,15 +69,15 @@ impl rt::Write for BoxedIo {
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        Pin::new(&mut self.0).poll_write(cx, buf)
+        self.io.as_mut().poll_write(cx, buf)
     }
 
     fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.0).poll_flush(cx)
+        self.io.as_mut().poll_flush(cx)
     }
 
     fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.0).poll_shutdown(cx)
+        self.io.as_mut().poll_shutdown(cx)
     }
 
     fn poll_write_vectored(
@@ -58,10 +85,10 @@ impl rt::Write for BoxedIo {
         cx: &mut Context<'_>,
         bufs: &[IoSlice<'_>],
     ) -> Poll<Result<usize, io::Error>> {
-        Pin::new(&mut self.0).poll_write_vectored(cx, bufs)
+        self.io.as_mut().poll_write_vectored(cx, bufs)
     }
 
     fn is_write_vectored(&self) -> bool {
-        self.0.is_write_vectored()
+        self.io.is_write_vectored()
     }
 }
@@ -0,0 +1,561 @@
+use crate::{
+    body::{boxed, BoxBody},
+    metadata::GRPC_TIMEOUT_HEADER,
+    request::duration_to_grpc_timeout,
+    status::find_status_in_source_chain,
+    transport::{channel::RetryPolicy, service::grpc_timeout::try_parse_grpc_timeout},
+    Code, Status,
+};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, Request, Response};
+use http_body::{Body, Frame, SizeHint};
+use std::{
+    future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tower_service::Service;
+
+/// The bytes of a request stream kept to send it again, longer request streams are not retried.
+const MAX_REPLAY_BYTES: usize = 256 * 1024;
+
+/// Send `request` through `svc`, applying the timeout and retry policy of its method config.
+pub(crate) async fn call<S>(
+    mut svc: S,
+    mut request: Request<BoxBody>,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+) -> Result<Response<BoxBody>, crate::Error>
+where
+    S: Service<Request<BoxBody>, Response = Response<BoxBody>, Error = crate::Error>,
+{
+    let request_timeout = try_parse_grpc_timeout(request.headers()).unwrap_or_else(|e| {
+        tracing::trace!("Error parsing `grpc-timeout` header {:?}", e);
+        None
+    });
+
+    // Use the shorter of the two durations, if either are set
+    let deadline = match (request_timeout, timeout) {
+        (Some(header), Some(config)) => Some(header.min(config)),
+        (header, config) => header.or(config),
+    }
+    .map(|timeout| Instant::now() + timeout);
+
+    let Some(policy) = retry_policy else {
+        set_remaining_timeout(request.headers_mut(), deadline)?;
+        return send(&mut svc, request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let replay = Replay::default();
+    let mut body = replay.record(body);
+
+    let mut backoff = policy.backoff();
+    let mut attempt = 1;
+
+    loop {
+        let mut request = Request::new(body);
+        *request.method_mut() = parts.method.clone();
+        *request.uri_mut() = parts.uri.clone();
+        *request.version_mut() = parts.version;
+        *request.headers_mut() = parts.headers.clone();
+        *request.extensions_mut() = parts.extensions.clone();
+        set_remaining_timeout(request.headers_mut(), deadline)?;
+
+        let result = send(&mut svc, request).await;
+
+        // A status in the response headers means the server answered without sending any
+        // message (a trailers-only response), so the call can still be retried.
+        let status = match &result {
+            Ok(response) => Status::from_header_map(response.headers()),
+            Err(err) => find_status_in_source_chain(&**err),
+        };
+        let code = status.as_ref().map(Status::code);
+
+        let retryable = code
+            .is_some_and(|code| code != Code::Ok && policy.retryable_status_codes.contains(&code));
+        if !retryable || attempt >= policy.max_attempts {
+            return result;
+        }
+
+        let Some(replayed) = replay.replayed().await else {
+            tracing::debug!(
+                attempt,
+                ?code,
+                "request stream can't be sent again, not retrying"
+            );
+            return result;
+        };
+        body = replayed;
+
+        // The server may ask to wait for a given delay instead, up to the maximum backoff.
+        let delay = status
+            .as_ref()
+            .and_then(Status::parse_retry_after)
+            .map(|delay| delay.min(policy.max_backoff))
+            .unwrap_or_else(|| backoff.next_delay());
+
+        // The attempts share the deadline of the call, there is no point in waiting for the
+        // delay if the next attempt could not start before it.
+        if deadline.is_some_and(|deadline| Instant::now() + delay >= deadline) {
+            tracing::debug!(attempt, ?code, "deadline reached before the next attempt");
+            return Err(deadline_exceeded().into());
+        }
+
+        tracing::debug!(attempt, ?code, ?delay, "retrying call");
+        tokio::time::sleep(delay).await;
+
+        attempt += 1;
+    }
+}
+
+/// Records the request stream of the first attempt, so that it can be sent again.
+///
+/// The request stream is not buffered ahead of the first attempt, which would never start for
+/// streams that only end once the server has answered. Instead, the frames are kept as they are
+/// sent, and a retry is only possible if the stream has ended by then, without exceeding
+/// [`MAX_REPLAY_BYTES`].
+#[derive(Clone, Default)]
+struct Replay(Arc<Mutex<Recording>>);
+
+#[derive(Default)]
+struct Recording {
+    state: RecordingState,
+    data: Vec<Bytes>,
+    len: usize,
+    trailers: Option<HeaderMap>,
+    /// The rest of the request stream, dropped by the first attempt before its end.
+    rest: Option<BoxBody>,
+}
+
+#[derive(Default, PartialEq)]
+enum RecordingState {
+    #[default]
+    Recording,
+    Complete,
+    Abandoned,
+}
+
+impl Replay {
+    /// Wrap the request stream of the first attempt to record it.
+    fn record(&self, body: BoxBody) -> BoxBody {
+        if body.is_end_stream() {
+            self.observe(None, true);
+        }
+
+        BoxBody::new(RecordedBody {
+            inner: Some(body),
+            replay: self.clone(),
+        })
+    }
+
+    /// The request stream to send again, if it has been recorded entirely.
+    async fn replayed(&self) -> Option<BoxBody> {
+        let rest = {
+            let mut recording = self.0.lock().unwrap();
+            match recording.state {
+                RecordingState::Recording => recording.rest.take(),
+                _ => None,
+            }
+        };
+
+        // Only read what the request stream has ready, waiting for more could wait for the
+        // client to end a stream it only ends once it got a response.
+        if let Some(mut rest) = rest {
+            future::poll_fn(|cx| loop {
+                let Poll::Ready(frame) = Pin::new(&mut rest).poll_frame(cx) else {
+                    return Poll::Ready(());
+                };
+                let end = frame.is_none() || rest.is_end_stream();
+                if !self.observe(frame.as_ref(), end) {
+                    return Poll::Ready(());
+                }
+            })
+            .await;
+        }
+
+        let recording = self.0.lock().unwrap();
+        if recording.state != RecordingState::Complete {
+            return None;
+        }
+
+        let frames: Vec<_> = recording
+            .data
+            .iter()
+            .cloned()
+            .map(Frame::data)
+            .chain(recording.trailers.clone().map(Frame::trailers))
+            .map(Ok::<_, Status>)
+            .collect();
+        Some(boxed(http_body_util::StreamBody::new(tokio_stream::iter(
+            frames,
+        ))))
+    }
+
+    /// Record a frame of the request stream, returns whether the recording goes on.
+    fn observe(&self, frame: Option<&Result<Frame<Bytes>, Status>>, end: bool) -> bool {
+        let mut recording = self.0.lock().unwrap();
+        if recording.state != RecordingState::Recording {
+            return false;
+        }
+
+        match frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    recording.len += data.len();
+                    recording.data.push(data.clone());
+                } else if let Some(trailers) = frame.trailers_ref() {
+                    recording.trailers = Some(trailers.clone());
+                }
+            }
+            Some(Err(_)) => recording.state = RecordingState::Abandoned,
+            None => {}
+        }
+
+        if recording.len > MAX_REPLAY_BYTES {
+            recording.state = RecordingState::Abandoned;
+        } else if end && recording.state == RecordingState::Recording {
+            recording.state = RecordingState::Complete;
+        }
+
+        if recording.state == RecordingState::Abandoned {
+            recording.data = Vec::new();
+            recording.rest = None;
+        }
+        recording.state == RecordingState::Recording
+    }
+}
+
+/// The request stream of the first attempt, see [`Replay`].
+struct RecordedBody {
+    inner: Option<BoxBody>,
+    replay: Replay,
+}
+
+impl Body for RecordedBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let Some(inner) = &mut this.inner else {
+            return Poll::Ready(None);
+        };
+
+        let frame = ready!(Pin::new(&mut *inner).poll_frame(cx));
+        let end = frame.is_none() || inner.is_end_stream();
+        this.replay.observe(frame.as_ref(), end);
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            Some(inner) => inner.is_end_stream(),
+            None => true,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner
+            .as_ref()
+            .map_or_else(|| SizeHint::with_exact(0), Body::size_hint)
+    }
+}
+
+impl Drop for RecordedBody {
+    fn drop(&mut self) {
+        // Keep the rest of the stream if the attempt ended before reading it, to record it
+        // for the next attempt.
+        if let Some(inner) = self.inner.take() {
+            let mut recording = self.replay.0.lock().unwrap();
+            if recording.state == RecordingState::Recording {
+                recording.rest = Some(inner);
+            }
+        }
+    }
+}
+
+async fn send<S>(svc: &mut S, request: Request<BoxBody>) -> Result<Response<BoxBody>, crate::Error>
+where
+    S: Service<Request<BoxBody>, Response = Response<BoxBody>, Error = crate::Error>,
+{
+    future::poll_fn(|cx| svc.poll_ready(cx)).await?;
+    svc.call(request).await
+}
+
+fn set_remaining_timeout(
+    headers: &mut HeaderMap,
+    deadline: Option<Instant>,
+) -> Result<(), crate::Error> {
+    if let Some(deadline) = deadline {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(deadline_exceeded().into());
+        }
+
+        let value = HeaderValue::try_from(duration_to_grpc_timeout(remaining))
+            .expect("grpc-timeout is a valid header value");
+        headers.insert(GRPC_TIMEOUT_HEADER, value);
+    }
+
+    Ok(())
+}
+
+fn deadline_exceeded() -> Status {
+    Status::deadline_exceeded("Deadline expired before the call could be attempted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        body::{empty_body, from_bytes},
+        transport::channel::Jitter,
+    };
+    use http_body_util::BodyExt;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn trailers_only(code: Code) -> Response<BoxBody> {
+        let mut response = Response::new(empty_body());
+        Status::new(code, "")
+            .add_header(response.headers_mut())
+            .unwrap();
+        response
+    }
+
+    fn failing_service(
+        failures: usize,
+        code: Code,
+    ) -> (
+        impl Service<Request<BoxBody>, Response = Response<BoxBody>, Error = crate::Error>,
+        Arc<AtomicUsize>,
+    ) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        let svc = tower::service_fn(move |request: Request<BoxBody>| {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let body = request.into_body().collect().await?.to_bytes();
+                assert_eq!(body, "request");
+
+                Ok::<_, crate::Error>(if attempt < failures {
+                    trailers_only(code)
+                } else {
+                    Response::new(empty_body())
+                })
+            }
+        });
+
+        (svc, calls)
+    }
+
+    fn request() -> Request<BoxBody> {
+        Request::new(from_bytes(bytes::Bytes::from_static(b"request")))
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new(3).initial_backoff(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let (svc, calls) = failing_service(2, Code::Unavailable);
+
+        let response = call(svc, request(), None, Some(policy())).await.unwrap();
+
+        assert!(Status::from_header_map(response.headers()).is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_attempts() {
+        let (svc, calls) = failing_service(5, Code::Unavailable);
+
+        let response = call(svc, request(), None, Some(policy())).await.unwrap();
+
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_codes() {
+        let (svc, calls) = failing_service(1, Code::InvalidArgument);
+
+        call(svc, request(), None, Some(policy())).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn sets_shorter_timeout() {
+        let svc = tower::service_fn(|request: Request<BoxBody>| async move {
+            let timeout = try_parse_grpc_timeout(request.headers()).unwrap().unwrap();
+            assert!(timeout <= Duration::from_secs(1));
+            assert!(timeout > Duration::from_millis(900));
+            Ok::<_, crate::Error>(Response::new(empty_body()))
+        });
+
+        let mut request = request();
+        request
+            .headers_mut()
+            .insert(GRPC_TIMEOUT_HEADER, HeaderValue::from_static("10S"));
+
+        call(svc, request, Some(Duration::from_secs(1)), None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn attempts_share_the_deadline() {
+        let timeouts = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let svc = {
+            let timeouts = timeouts.clone();
+            tower::service_fn(move |request: Request<BoxBody>| {
+                let timeout = try_parse_grpc_timeout(request.headers()).unwrap().unwrap();
+                timeouts.lock().unwrap().push(timeout);
+                async { Ok::<_, crate::Error>(trailers_only(Code::Unavailable)) }
+            })
+        };
+
+        let policy = RetryPolicy::new(5)
+            .initial_backoff(Duration::from_millis(40))
+            .backoff_multiplier(1.0)
+            .jitter(Jitter::None);
+        let err = call(
+            svc,
+            request(),
+            Some(Duration::from_millis(100)),
+            Some(policy),
+        )
+        .await
+        .unwrap_err();
+
+        let status = find_status_in_source_chain(&*err).unwrap();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+
+        // Attempts start after 0, 40 and 80 milliseconds, a fourth one would be past the deadline.
+        let timeouts = timeouts.lock().unwrap();
+        assert_eq!(
+            *timeouts,
+            [100, 60, 20].map(Duration::from_millis),
+            "each attempt gets the time left"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_for_retry_after() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = {
+            let calls = calls.clone();
+            tower::service_fn(move |_: Request<BoxBody>| {
+                let attempt = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let mut response = Response::new(empty_body());
+                    if attempt == 0 {
+                        Status::deadline_exceeded_with_retry(Duration::from_millis(50))
+                            .add_header(response.headers_mut())
+                            .unwrap();
+                    }
+                    Ok::<_, crate::Error>(response)
+                }
+            })
+        };
+
+        let policy = policy().retryable_status_codes([Code::DeadlineExceeded]);
+        let start = Instant::now();
+        call(svc, request(), None, Some(policy)).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(start.elapsed(), Duration::from_millis(50));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn caps_retry_after_to_max_backoff() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = {
+            let calls = calls.clone();
+            tower::service_fn(move |_: Request<BoxBody>| {
+                let attempt = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let mut response = Response::new(empty_body());
+                    if attempt == 0 {
+                        Status::deadline_exceeded_with_retry(Duration::from_secs(3600))
+                            .add_header(response.headers_mut())
+                            .unwrap();
+                    }
+                    Ok::<_, crate::Error>(response)
+                }
+            })
+        };
+
+        let policy = policy()
+            .retryable_status_codes([Code::DeadlineExceeded])
+            .max_backoff(Duration::from_millis(10));
+        let start = Instant::now();
+        call(svc, request(), None, Some(policy)).await.unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(start.elapsed(), Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    async fn does_not_wait_for_unfinished_request_streams() {
+        let (svc, calls) = {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let counter = calls.clone();
+            let svc = tower::service_fn(move |_: Request<BoxBody>| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<_, crate::Error>(trailers_only(Code::Unavailable)) }
+            });
+            (svc, calls)
+        };
+
+        // A request stream that only ends once the client is done, which is never.
+        let frames =
+            tokio_stream::iter([Ok::<_, Status>(Frame::data(Bytes::from_static(b"request")))]);
+        let body = http_body_util::StreamBody::new(tokio_stream::StreamExt::chain(
+            frames,
+            tokio_stream::pending(),
+        ));
+        let request = Request::new(boxed(body));
+
+        let response = call(svc, request, None, Some(policy())).await.unwrap();
+
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_long_request_streams() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = {
+            let calls = calls.clone();
+            tower::service_fn(move |request: Request<BoxBody>| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    request.into_body().collect().await?;
+                    Ok::<_, crate::Error>(trailers_only(Code::Unavailable))
+                }
+            })
+        };
+
+        let body = from_bytes(Bytes::from(vec![0; MAX_REPLAY_BYTES + 1]));
+        call(svc, Request::new(body), None, Some(policy()))
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -8,7 +8,9 @@ mod reconnect;
 use self::reconnect::Reconnect;
 
 mod connection;
-pub(super) use self::connection::Connection;
+pub(super) use self::connection::{Connection, WarmUp};
+
+pub(super) mod method_config;
 
 mod discover;
 pub(super) use self::discover::DynamicServiceStream;
@@ -6,13 +6,15 @@ use hyper_util::rt::TokioIo;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::{
     rustls::{
+        client::Resumption,
         pki_types::{ServerName, TrustAnchor},
-        ClientConfig, RootCertStore,
+        ClientConfig, HandshakeKind, RootCertStore,
     },
     TlsConnector as RustlsConnector,
 };
 
 use super::io::BoxedIo;
+use crate::transport::channel::TlsSessionInfo;
 use crate::transport::service::tls::{add_certs_from_pem, load_identity, TlsError, ALPN_H2};
 use crate::transport::tls::{Certificate, Identity};
 
@@ -24,12 +26,15 @@ pub(crate) struct TlsConnector {
 }
 
 impl TlsConnector {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         ca_certs: Vec<Certificate>,
         trust_anchors: Vec<TrustAnchor<'static>>,
         identity: Option<Identity>,
         domain: &str,
         assume_http2: bool,
+        alpn_protocols: Option<Vec<Vec<u8>>>,
+        session_resumption: Option<bool>,
         #[cfg(feature = "tls-native-roots")] with_native_roots: bool,
         #[cfg(feature = "tls-webpki-roots")] with_webpki_roots: bool,
     ) -> Result<Self, crate::Error> {
@@ -67,7 +72,28 @@ impl TlsConnector {
             None => builder.with_no_client_auth(),
         };
 
-        config.alpn_protocols.push(ALPN_H2.into());
+        config.alpn_protocols = match alpn_protocols {
+            Some(alpn_protocols) => {
+                if !alpn_protocols.iter().any(|protocol| protocol == ALPN_H2) {
+                    return Err(TlsError::H2NotInAlpnProtocols.into());
+                }
+                for protocol in alpn_protocols.iter().filter(|p| *p != ALPN_H2) {
+                    tracing::warn!(
+                        "offering custom ALPN protocol {:?}",
+                        String::from_utf8_lossy(protocol)
+                    );
+                }
+                alpn_protocols
+            }
+            None => vec![ALPN_H2.into()],
+        };
+
+        match session_resumption {
+            Some(true) => config.resumption = Resumption::default(),
+            Some(false) => config.resumption = Resumption::disabled(),
+            None => {}
+        }
+
         Ok(Self {
             config: Arc::new(config),
             domain: Arc::new(ServerName::try_from(domain)?.to_owned()),
@@ -90,7 +116,11 @@ impl TlsConnector {
         if !(alpn_protocol == Some(ALPN_H2) || self.assume_http2) {
             return Err(TlsError::H2NotNegotiated.into());
         }
-        Ok(BoxedIo::new(TokioIo::new(io)))
+
+        let tls_session = TlsSessionInfo {
+            resumed: session.handshake_kind() == Some(HandshakeKind::Resumed),
+        };
+        Ok(BoxedIo::new(TokioIo::new(io)).with_tls_session(tls_session))
     }
 }
 
@@ -99,3 +129,76 @@ impl fmt::Debug for TlsConnector {
         f.debug_struct("TlsConnector").finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper_util::client::legacy::connect::Connection as _;
+    use tokio_rustls::{
+        rustls::{version::TLS12, ServerConfig},
+        TlsAcceptor,
+    };
+
+    fn acceptor() -> TlsAcceptor {
+        let (certs, key) = load_identity(Identity::from_pem(
+            include_str!("../../../../../examples/data/tls/server.pem"),
+            include_str!("../../../../../examples/data/tls/server.key"),
+        ))
+        .unwrap();
+
+        // TLS 1.2 stores the session during the handshake, TLS 1.3 tickets would only be read
+        // along with application data.
+        let mut config = ServerConfig::builder_with_protocol_versions(&[&TLS12])
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .unwrap();
+        config.alpn_protocols = vec![ALPN_H2.into()];
+
+        TlsAcceptor::from(Arc::new(config))
+    }
+
+    fn connector(session_resumption: Option<bool>) -> TlsConnector {
+        TlsConnector::new(
+            vec![Certificate::from_pem(include_str!(
+                "../../../../../examples/data/tls/ca.pem"
+            ))],
+            vec![],
+            None,
+            "example.com",
+            false,
+            None,
+            session_resumption,
+            #[cfg(feature = "tls-native-roots")]
+            false,
+            #[cfg(feature = "tls-webpki-roots")]
+            false,
+        )
+        .unwrap()
+    }
+
+    async fn resumed(connector: &TlsConnector, acceptor: &TlsAcceptor) -> bool {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let acceptor = acceptor.clone();
+        let accept = tokio::spawn(async move { acceptor.accept(server).await.unwrap() });
+
+        let io = connector.connect(client).await.unwrap();
+        let _server = accept.await.unwrap();
+
+        let mut extras = http::Extensions::new();
+        io.connected().get_extras(&mut extras);
+        extras.get::<TlsSessionInfo>().unwrap().resumed()
+    }
+
+    #[tokio::test]
+    async fn reports_resumed_sessions() {
+        let acceptor = acceptor();
+
+        let default = connector(None);
+        assert!(!resumed(&default, &acceptor).await);
+        assert!(resumed(&default, &acceptor).await);
+
+        let disabled = connector(Some(false));
+        assert!(!resumed(&disabled, &acceptor).await);
+        assert!(!resumed(&disabled, &acceptor).await);
+    }
+}
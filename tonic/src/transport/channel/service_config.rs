@@ -0,0 +1,391 @@
+use crate::{util::rng::SplitMix64, Code};
+use std::time::Duration;
+
+/// Per-method call policies applied by a [`Channel`], modeled after the [gRPC service config].
+///
+/// The configuration is provided up front with [`Endpoint::service_config`] instead of being
+/// fetched through name resolution.
+///
+/// ```
+/// # use tonic::transport::{Endpoint, channel::{MethodConfig, RetryPolicy, ServiceConfig}};
+/// # use std::time::Duration;
+/// let config = ServiceConfig::new().method_config(
+///     MethodConfig::new()
+///         .service("helloworld.Greeter")
+///         .timeout(Duration::from_secs(5))
+///         .retry_policy(RetryPolicy::new(3)),
+/// );
+///
+/// let endpoint = Endpoint::from_static("http://[::1]:50051").service_config(config);
+/// ```
+///
+/// [`Channel`]: super::Channel
+/// [`Endpoint::service_config`]: super::Endpoint::service_config
+/// [gRPC service config]: https://github.com/grpc/grpc/blob/master/doc/service_config.md
+#[derive(Debug, Clone, Default)]
+pub struct ServiceConfig {
+    method_configs: Vec<MethodConfig>,
+}
+
+impl ServiceConfig {
+    /// Create an empty service config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a [`MethodConfig`].
+    ///
+    /// When several configs match a call, the one naming its exact method wins over one naming
+    /// its service, which wins over one without names.
+    pub fn method_config(mut self, config: MethodConfig) -> Self {
+        self.method_configs.push(config);
+        self
+    }
+
+    /// Find the config that applies to the request `path`, which is `/<service>/<method>`.
+    pub(crate) fn lookup(&self, path: &str) -> Option<&MethodConfig> {
+        let (service, method) = path.strip_prefix('/')?.split_once('/')?;
+
+        let find = |matches: &dyn Fn(&MethodName) -> bool| {
+            self.method_configs
+                .iter()
+                .find(|config| config.names.iter().any(matches))
+        };
+
+        find(&|name| name.service == service && name.method.as_deref() == Some(method))
+            .or_else(|| find(&|name| name.service == service && name.method.is_none()))
+            .or_else(|| {
+                self.method_configs
+                    .iter()
+                    .find(|config| config.names.is_empty())
+            })
+    }
+}
+
+/// Call policies for a set of methods, part of a [`ServiceConfig`].
+///
+/// A config without any names applies to every method that no other config names.
+#[derive(Debug, Clone, Default)]
+pub struct MethodConfig {
+    names: Vec<MethodName>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+}
+
+#[derive(Debug, Clone)]
+struct MethodName {
+    service: String,
+    method: Option<String>,
+}
+
+impl MethodConfig {
+    /// Create a method config that doesn't apply any policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply this config to every method of `service`, for example `helloworld.Greeter`.
+    pub fn service(mut self, service: impl Into<String>) -> Self {
+        self.names.push(MethodName {
+            service: service.into(),
+            method: None,
+        });
+        self
+    }
+
+    /// Apply this config to `method` of `service`, for example `SayHello` of
+    /// `helloworld.Greeter`.
+    pub fn method(mut self, service: impl Into<String>, method: impl Into<String>) -> Self {
+        self.names.push(MethodName {
+            service: service.into(),
+            method: Some(method.into()),
+        });
+        self
+    }
+
+    /// Set the maximum duration of a call, including all of its retries.
+    ///
+    /// If the request already carries a shorter `grpc-timeout`, that one is kept. The remaining
+    /// time is sent to the server in the `grpc-timeout` header of every attempt, and the call
+    /// fails with `DeadlineExceeded` when a retry could not start before the deadline.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry failed calls according to `policy`.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+}
+
+/// How failed calls are retried, part of a [`MethodConfig`].
+///
+/// A call is retried when the server answers with one of the retryable status codes before
+/// sending any response message, or when the transport fails with an error that maps to one of
+/// them, such as `Unavailable` for connection errors. Attempts are spaced out with an exponential
+/// backoff randomized according to the [`Jitter`] strategy, unless the status asks for another
+/// delay, see [`Status::parse_retry_after`]. That delay is capped to the maximum backoff.
+///
+/// To be able to send it again, the request stream of the first attempt is kept as it is sent,
+/// up to 256KiB. A call is only retried if its request stream had ended without exceeding this
+/// size when the attempt failed, so calls of client and bidirectional streaming methods are
+/// usually sent once. The request stream is never waited for.
+///
+/// [`Status::parse_retry_after`]: crate::Status::parse_retry_after
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) backoff_multiplier: f64,
+    pub(crate) retryable_status_codes: Vec<Code>,
+    pub(crate) jitter: Jitter,
+    pub(crate) jitter_seed: Option<u64>,
+}
+
+impl RetryPolicy {
+    /// The most attempts a call can make, higher values are capped to it.
+    const MAX_ATTEMPTS: u32 = 5;
+
+    /// Create a retry policy making at most `max_attempts` attempts, including the original one.
+    ///
+    /// `max_attempts` is clamped between 2 and 5. The backoff starts at 100 milliseconds and is
+    /// doubled after every attempt up to 1 second, with [full jitter](Jitter::Full), and only
+    /// `Unavailable` is retried.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.clamp(2, Self::MAX_ATTEMPTS),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            retryable_status_codes: vec![Code::Unavailable],
+            jitter: Jitter::default(),
+            jitter_seed: None,
+        }
+    }
+
+    /// Set the delay before the first retry.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the upper bound of the delay between attempts, including the delays asked for by the
+    /// server.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Set the factor the delay is multiplied by after every retry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `multiplier` isn't a finite number greater than zero.
+    pub fn backoff_multiplier(mut self, multiplier: f64) -> Self {
+        assert!(
+            multiplier.is_finite() && multiplier > 0.0,
+            "backoff multiplier must be greater than zero"
+        );
+        self.backoff_multiplier = multiplier;
+        self
+    }
+
+    /// Set the status codes that cause a call to be retried.
+    pub fn retryable_status_codes(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.retryable_status_codes = codes.into_iter().collect();
+        self
+    }
+
+    /// Set how the delay between attempts is randomized.
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Seed the generator of the jitter, so that every call waits for the same delays.
+    ///
+    /// By default, each call is seeded at random. A fixed seed makes delays reproducible, which
+    /// is mostly useful in tests, but it also makes the calls of every client using the policy
+    /// retry in lockstep.
+    pub fn jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = Some(seed);
+        self
+    }
+
+    /// The delays between the attempts of a call.
+    pub(crate) fn backoff(&self) -> Backoff<'_> {
+        let ceiling = self.initial_backoff.min(self.max_backoff);
+        Backoff {
+            policy: self,
+            ceiling,
+            previous: ceiling,
+            rng: self
+                .jitter_seed
+                .map_or_else(SplitMix64::from_entropy, SplitMix64::new),
+        }
+    }
+}
+
+/// How the delay between attempts is randomized, part of a [`RetryPolicy`].
+///
+/// Without jitter, clients that failed at the same time, for example because a server went
+/// down, all retry at the same time too. The strategies follow the [AWS guidance] on backoff
+/// and jitter. The exponential backoff of the `n`th retry, `min(max_backoff, initial_backoff *
+/// backoff_multiplier^(n - 1))`, is the upper bound of its delay, except with
+/// [`Jitter::Decorrelated`] which doesn't use it.
+///
+/// [AWS guidance]: https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Jitter {
+    /// Wait for exactly the exponential backoff.
+    None,
+    /// Wait for a random delay between zero and the exponential backoff.
+    #[default]
+    Full,
+    /// Wait for half of the exponential backoff plus a random delay up to the other half.
+    Equal,
+    /// Wait for a random delay between the initial backoff and three times the previous delay,
+    /// at most `max_backoff`.
+    Decorrelated,
+}
+
+/// The delays between the attempts of a call, created by [`RetryPolicy::backoff`].
+pub(crate) struct Backoff<'a> {
+    policy: &'a RetryPolicy,
+    /// The exponential backoff of the next retry.
+    ceiling: Duration,
+    previous: Duration,
+    rng: SplitMix64,
+}
+
+impl Backoff<'_> {
+    /// The delay before the next retry.
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        let policy = self.policy;
+        let delay = match policy.jitter {
+            Jitter::None => self.ceiling,
+            Jitter::Full => self.ceiling.mul_f64(self.rng.next_f64()),
+            Jitter::Equal => {
+                let half = self.ceiling / 2;
+                half + half.mul_f64(self.rng.next_f64())
+            }
+            Jitter::Decorrelated => {
+                let low = policy.initial_backoff.min(policy.max_backoff);
+                let high = self.previous.saturating_mul(3).max(low);
+                (low + (high - low).mul_f64(self.rng.next_f64())).min(policy.max_backoff)
+            }
+        };
+
+        self.previous = delay;
+        self.ceiling = self
+            .ceiling
+            .mul_f64(policy.backoff_multiplier)
+            .min(policy.max_backoff);
+        delay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_most_specific_config() {
+        let config = ServiceConfig::new()
+            .method_config(MethodConfig::new().timeout(Duration::from_secs(1)))
+            .method_config(
+                MethodConfig::new()
+                    .service("test.Test")
+                    .timeout(Duration::from_secs(2)),
+            )
+            .method_config(
+                MethodConfig::new()
+                    .method("test.Test", "Unary")
+                    .timeout(Duration::from_secs(3)),
+            );
+
+        let timeout = |path| config.lookup(path).and_then(|config| config.timeout);
+
+        assert_eq!(timeout("/test.Test/Unary"), Some(Duration::from_secs(3)));
+        assert_eq!(timeout("/test.Test/Stream"), Some(Duration::from_secs(2)));
+        assert_eq!(timeout("/other.Other/Unary"), Some(Duration::from_secs(1)));
+        assert_eq!(timeout("invalid"), None);
+    }
+
+    #[test]
+    fn lookup_without_default() {
+        let config = ServiceConfig::new().method_config(MethodConfig::new().service("test.Test"));
+
+        assert!(config.lookup("/test.Test/Unary").is_some());
+        assert!(config.lookup("/other.Other/Unary").is_none());
+    }
+
+    #[test]
+    fn retry_policy_limits() {
+        assert_eq!(RetryPolicy::new(1).max_attempts, 2);
+        assert_eq!(RetryPolicy::new(10).max_attempts, 5);
+    }
+
+    fn delays(policy: &RetryPolicy, retries: usize) -> Vec<Duration> {
+        let mut backoff = policy.backoff();
+        (0..retries).map(|_| backoff.next_delay()).collect()
+    }
+
+    #[test]
+    fn backoff_without_jitter() {
+        let policy = RetryPolicy::new(3)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(250))
+            .jitter(Jitter::None);
+        assert_eq!(
+            delays(&policy, 3),
+            [100, 200, 250].map(Duration::from_millis)
+        );
+    }
+
+    #[test]
+    fn jittered_delays_stay_within_bounds() {
+        let initial = Duration::from_millis(10);
+        let max = Duration::from_millis(500);
+        let ceilings: Vec<_> = std::iter::successors(Some(initial), |c| Some((*c * 2).min(max)))
+            .take(100)
+            .collect();
+
+        for seed in 0..20 {
+            let policy = |jitter| {
+                RetryPolicy::new(3)
+                    .initial_backoff(initial)
+                    .max_backoff(max)
+                    .jitter(jitter)
+                    .jitter_seed(seed)
+            };
+
+            let full = delays(&policy(Jitter::Full), ceilings.len());
+            assert!(full.iter().zip(&ceilings).all(|(d, c)| d <= c));
+
+            let equal = delays(&policy(Jitter::Equal), ceilings.len());
+            assert!(equal
+                .iter()
+                .zip(&ceilings)
+                .all(|(d, c)| *d >= *c / 2 && d <= c));
+
+            let decorrelated = delays(&policy(Jitter::Decorrelated), ceilings.len());
+            let mut previous = initial;
+            for delay in decorrelated {
+                assert!(delay >= initial && delay <= max);
+                assert!(delay <= previous * 3);
+                previous = delay;
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_jitter_is_reproducible() {
+        let policy = RetryPolicy::new(3).jitter_seed(7);
+        assert_eq!(delays(&policy, 10), delays(&policy, 10));
+        assert_ne!(delays(&policy, 10), delays(&policy.jitter_seed(8), 10));
+    }
+}
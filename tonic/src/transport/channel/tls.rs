@@ -14,6 +14,8 @@ pub struct ClientTlsConfig {
     trust_anchors: Vec<TrustAnchor<'static>>,
     identity: Option<Identity>,
     assume_http2: bool,
+    alpn_protocols: Option<Vec<Vec<u8>>>,
+    session_resumption: Option<bool>,
     #[cfg(feature = "tls-native-roots")]
     with_native_roots: bool,
     #[cfg(feature = "tls-webpki-roots")]
@@ -27,6 +29,19 @@ impl ClientTlsConfig {
     }
 
     /// Sets the domain name against which to verify the server's TLS certificate.
+    ///
+    /// The name is also sent as the server name indication (SNI) of the TLS handshake. It defaults
+    /// to the host of the endpoint URI, so set it when connecting to an IP address of a server
+    /// that selects its certificate by name.
+    ///
+    /// ```
+    /// # use tonic::transport::{ClientTlsConfig, Endpoint};
+    /// # fn ex() -> Result<(), tonic::transport::Error> {
+    /// let endpoint = Endpoint::from_static("https://10.0.0.1:443")
+    ///     .tls_config(ClientTlsConfig::new().domain_name("api.example.com"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn domain_name(self, domain_name: impl Into<String>) -> Self {
         ClientTlsConfig {
             domain: Some(domain_name.into()),
@@ -84,6 +99,35 @@ impl ClientTlsConfig {
         }
     }
 
+    /// Sets the protocols to offer during ALPN negotiation, in order of preference.
+    ///
+    /// Defaults to `h2` only. The list must contain `h2`, otherwise creating the connector fails.
+    /// A warning is logged for every other protocol in the list.
+    pub fn alpn_protocols(self, alpn_protocols: Vec<Vec<u8>>) -> Self {
+        ClientTlsConfig {
+            alpn_protocols: Some(alpn_protocols),
+            ..self
+        }
+    }
+
+    /// Enables or disables TLS session resumption.
+    ///
+    /// When enabled, sessions are cached in memory and resumed with session tickets (or session
+    /// IDs for TLS 1.2) when reconnecting to the same server, which saves a round trip and the
+    /// certificate verification of a full handshake. The cache is shared by every connection of
+    /// the [`Endpoint`]. Whether a connection was resumed is reported by the [`TlsSessionInfo`]
+    /// extension of its responses.
+    ///
+    /// Defaults to the behavior of rustls, which currently enables resumption.
+    ///
+    /// [`Endpoint`]: super::Endpoint
+    pub fn session_resumption(self, enabled: bool) -> Self {
+        ClientTlsConfig {
+            session_resumption: Some(enabled),
+            ..self
+        }
+    }
+
     /// Enables the platform's trusted certs.
     #[cfg(feature = "tls-native-roots")]
     pub fn with_native_roots(self) -> Self {
@@ -123,6 +167,8 @@ impl ClientTlsConfig {
             self.identity,
             domain,
             self.assume_http2,
+            self.alpn_protocols,
+            self.session_resumption,
             #[cfg(feature = "tls-native-roots")]
             self.with_native_roots,
             #[cfg(feature = "tls-webpki-roots")]
@@ -130,3 +176,52 @@ impl ClientTlsConfig {
         )
     }
 }
+
+/// The outcome of the TLS handshake of the connection a response was received on.
+///
+/// Inserted into the extensions of responses received over TLS by a [`Channel`], which allows
+/// telling apart calls that paid for a full handshake when analysing latency.
+///
+/// ```rust
+/// # use tonic::{transport::channel::TlsSessionInfo, Response};
+/// # fn ex(response: Response<()>) {
+/// if let Some(tls) = response.extensions().get::<TlsSessionInfo>() {
+///     println!("resumed: {}", tls.resumed());
+/// }
+/// # }
+/// ```
+///
+/// [`Channel`]: super::Channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsSessionInfo {
+    pub(crate) resumed: bool,
+}
+
+impl TlsSessionInfo {
+    /// Whether the connection resumed a previous TLS session instead of performing a full
+    /// handshake, see [`ClientTlsConfig::session_resumption`].
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alpn_protocols_must_include_h2() {
+        let uri = Uri::from_static("https://example.com");
+
+        let err = ClientTlsConfig::new()
+            .alpn_protocols(vec![b"http/1.1".to_vec()])
+            .into_tls_connector(&uri)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "ALPN protocols must include `h2`.");
+
+        ClientTlsConfig::new()
+            .alpn_protocols(vec![b"h2".to_vec(), b"http/1.1".to_vec()])
+            .into_tls_connector(&uri)
+            .unwrap();
+    }
+}
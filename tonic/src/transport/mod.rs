@@ -101,7 +101,7 @@ mod tls;
 
 #[doc(inline)]
 #[cfg(feature = "channel")]
-pub use self::channel::{Channel, Endpoint};
+pub use self::channel::{Channel, Endpoint, NameResolver};
 pub use self::error::Error;
 #[doc(inline)]
 #[cfg(feature = "server")]
@@ -116,7 +116,7 @@ pub use hyper::{body::Body, Uri};
 pub use tokio_rustls::rustls::pki_types::CertificateDer;
 
 #[cfg(all(feature = "channel", feature = "tls"))]
-pub use self::channel::ClientTlsConfig;
+pub use self::channel::{ClientTlsConfig, TlsSessionInfo};
 #[cfg(all(feature = "server", feature = "tls"))]
 pub use self::server::ServerTlsConfig;
 #[cfg(feature = "tls")]
@@ -196,7 +196,21 @@ impl TcpIncoming {
         nodelay: bool,
         keepalive: Option<Duration>,
     ) -> Result<Self, crate::Error> {
-        let std_listener = StdTcpListener::bind(addr)?;
+        Self::bind(addr, nodelay, keepalive, false)
+    }
+
+    /// Like [`TcpIncoming::new`], optionally setting `SO_REUSEPORT` on the listener.
+    pub(crate) fn bind(
+        addr: SocketAddr,
+        nodelay: bool,
+        keepalive: Option<Duration>,
+        reuse_port: bool,
+    ) -> Result<Self, crate::Error> {
+        let std_listener = if reuse_port {
+            bind_reuse_port(addr)?
+        } else {
+            StdTcpListener::bind(addr)?
+        };
         std_listener.set_nonblocking(true)?;
 
         let inner = TcpListenerStream::new(TcpListener::from_std(std_listener)?);
@@ -235,6 +249,26 @@ impl Stream for TcpIncoming {
     }
 }
 
+#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+fn bind_reuse_port(addr: SocketAddr) -> io::Result<StdTcpListener> {
+    use socket2::{Domain, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, None)?;
+    socket.set_reuse_address(true)?;
+    socket.set_reuse_port(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    Ok(socket.into())
+}
+
+#[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+fn bind_reuse_port(_addr: SocketAddr) -> io::Result<StdTcpListener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "SO_REUSEPORT is not supported on this platform",
+    ))
+}
+
 // Consistent with hyper-0.14, this function does not return an error.
 fn set_accepted_socket_options(stream: &TcpStream, nodelay: bool, keepalive: Option<Duration>) {
     if nodelay {
@@ -265,4 +299,13 @@ mod tests {
         }
         let _t3 = TcpIncoming::new(addr, true, None).unwrap();
     }
+
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    #[tokio::test]
+    async fn reuse_port_allows_shared_address() {
+        let addr = "127.0.0.1:1323".parse().unwrap();
+        let _t1 = TcpIncoming::bind(addr, true, None, true).unwrap();
+        let _t2 = TcpIncoming::bind(addr, true, None, true).unwrap();
+        let _t3 = TcpIncoming::bind(addr, true, None, false).unwrap_err();
+    }
 }
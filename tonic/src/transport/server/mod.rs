@@ -36,10 +36,11 @@ pub use incoming::TcpIncoming;
 #[cfg(feature = "tls")]
 use crate::transport::Error;
 
-use self::service::{RecoverError, ServerIo};
-use super::service::GrpcTimeout;
+use self::service::{RateLimit, RateLimiter, RecoverError, ServerIo};
+use super::service::{GrpcTimeout, IdleTracker, TrackedBody};
 use crate::body::{boxed, BoxBody};
 use crate::server::NamedService;
+use crate::Status;
 use bytes::Bytes;
 use http::{Request, Response};
 use http_body_util::BodyExt;
@@ -53,7 +54,10 @@ use std::{
     marker::PhantomData,
     net::SocketAddr,
     pin::{pin, Pin},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{ready, Context, Poll},
     time::Duration,
 };
@@ -85,6 +89,7 @@ const DEFAULT_HTTP2_KEEPALIVE_TIMEOUT_SECS: u64 = 20;
 pub struct Server<L = Identity> {
     trace_interceptor: Option<TraceInterceptor>,
     concurrency_limit: Option<usize>,
+    rate_limit: Option<f64>,
     timeout: Option<Duration>,
     #[cfg(feature = "tls")]
     tls: Option<TlsAcceptor>,
@@ -93,6 +98,7 @@ pub struct Server<L = Identity> {
     max_concurrent_streams: Option<u32>,
     tcp_keepalive: Option<Duration>,
     tcp_nodelay: bool,
+    reuse_port: bool,
     http2_keepalive_interval: Option<Duration>,
     http2_keepalive_timeout: Option<Duration>,
     http2_adaptive_window: Option<bool>,
@@ -102,6 +108,8 @@ pub struct Server<L = Identity> {
     accept_http1: bool,
     service_builder: ServiceBuilder<L>,
     max_connection_age: Option<Duration>,
+    max_connection_idle: Option<Duration>,
+    max_connections: Option<usize>,
 }
 
 impl Default for Server<Identity> {
@@ -109,6 +117,7 @@ impl Default for Server<Identity> {
         Self {
             trace_interceptor: None,
             concurrency_limit: None,
+            rate_limit: None,
             timeout: None,
             #[cfg(feature = "tls")]
             tls: None,
@@ -117,6 +126,7 @@ impl Default for Server<Identity> {
             max_concurrent_streams: None,
             tcp_keepalive: None,
             tcp_nodelay: false,
+            reuse_port: false,
             http2_keepalive_interval: None,
             http2_keepalive_timeout: None,
             http2_adaptive_window: None,
@@ -126,6 +136,8 @@ impl Default for Server<Identity> {
             accept_http1: false,
             service_builder: Default::default(),
             max_connection_age: None,
+            max_connection_idle: None,
+            max_connections: None,
         }
     }
 }
@@ -180,6 +192,41 @@ impl<L> Server<L> {
         }
     }
 
+    /// Limit the rate of requests accepted by this server.
+    ///
+    /// The limit is enforced with a token bucket shared by all connections, which allows
+    /// bursts of up to one second worth of requests. Requests over the limit are not queued,
+    /// they are rejected with [`Code::ResourceExhausted`] and a `grpc-retry-after` metadata
+    /// entry holding the number of seconds until a new request will be admitted, which
+    /// [`Status::parse_retry_after`] reads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not a positive, finite number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.with_rate_limit(100.0);
+    /// ```
+    ///
+    /// [`Code::ResourceExhausted`]: crate::Code::ResourceExhausted
+    #[must_use]
+    pub fn with_rate_limit(self, requests_per_second: f64) -> Self {
+        assert!(
+            requests_per_second.is_finite() && requests_per_second > 0.0,
+            "rate limit must be a positive, finite number of requests per second"
+        );
+
+        Server {
+            rate_limit: Some(requests_per_second),
+            ..self
+        }
+    }
+
     /// Set a timeout on for all request handlers.
     ///
     /// # Example
@@ -202,13 +249,23 @@ impl<L> Server<L> {
     /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
     /// stream-level flow control.
     ///
+    /// Servers pushing large response streams can raise it to avoid being throttled by
+    /// the flow control of the client.
+    ///
     /// Default is 65,535
     ///
+    /// # Panics
+    ///
+    /// Panics if the size is less than 65,535 or greater than 2^31-1.
+    ///
     /// [spec]: https://httpwg.org/specs/rfc9113.html#InitialWindowSize
     #[must_use]
     pub fn initial_stream_window_size(self, sz: impl Into<Option<u32>>) -> Self {
+        let sz = sz.into();
+        check_window_size("stream", sz);
+
         Server {
-            init_stream_window_size: sz.into(),
+            init_stream_window_size: sz,
             ..self
         }
     }
@@ -216,10 +273,17 @@ impl<L> Server<L> {
     /// Sets the max connection-level flow control for HTTP2
     ///
     /// Default is 65,535
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size is less than 65,535 or greater than 2^31-1.
     #[must_use]
     pub fn initial_connection_window_size(self, sz: impl Into<Option<u32>>) -> Self {
+        let sz = sz.into();
+        check_window_size("connection", sz);
+
         Server {
-            init_connection_window_size: sz.into(),
+            init_connection_window_size: sz,
             ..self
         }
     }
@@ -259,6 +323,59 @@ impl<L> Server<L> {
         }
     }
 
+    /// Close connections that had no request in flight for `max_connection_idle`.
+    ///
+    /// The timer of a connection is reset whenever a request completes, a request counting as
+    /// in flight until its response body ends. Once it expires, the connection is shut down
+    /// gracefully with a GOAWAY frame, so that clients reconnect, possibly to another instance
+    /// of the server. This helps spreading the load again after new instances are added, for
+    /// example during a rolling update. Unlike [`Server::tcp_keepalive`] and
+    /// [`Server::http2_keepalive_interval`], which only check that the peer is still there,
+    /// this closes connections that are alive but unused.
+    ///
+    /// Default is no limit (`None`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.max_connection_idle(Duration::from_secs(300));
+    /// ```
+    #[must_use]
+    pub fn max_connection_idle(self, max_connection_idle: Duration) -> Self {
+        Server {
+            max_connection_idle: Some(max_connection_idle),
+            ..self
+        }
+    }
+
+    /// Limit the number of connections served at the same time.
+    ///
+    /// Connections accepted beyond the limit are closed right away: HTTP/2 connections receive
+    /// a GOAWAY frame before any request is processed. A connection stops counting towards the
+    /// limit once it is closed.
+    ///
+    /// Default is no limit (`None`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use tower_service::Service;
+    /// # let builder = Server::builder();
+    /// builder.max_connections(1024);
+    /// ```
+    #[must_use]
+    pub fn max_connections(self, max: usize) -> Self {
+        Server {
+            max_connections: Some(max),
+            ..self
+        }
+    }
+
     /// Set whether HTTP2 Ping frames are enabled on accepted connections.
     ///
     /// If `None` is specified, HTTP2 keepalive is disabled, otherwise the duration
@@ -340,8 +457,29 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set the value of `SO_REUSEPORT` option on the listening socket created by
+    /// [`Router::serve`] and [`Router::serve_with_shutdown`].
+    ///
+    /// This allows several processes (or several servers in one process) to bind
+    /// the same address, with the kernel spreading incoming connections between them.
+    /// Only supported on Unix platforms; binding fails elsewhere when enabled.
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn reuse_port(self, enabled: bool) -> Self {
+        Server {
+            reuse_port: enabled,
+            ..self
+        }
+    }
+
     /// Sets the max size of received header frames.
     ///
+    /// The limit is advertised to clients as `SETTINGS_MAX_HEADER_LIST_SIZE` and requests whose
+    /// headers exceed it are rejected before reaching any service, which bounds the memory a
+    /// single request can use for its headers. Lowering it is worthwhile for publicly reachable
+    /// servers.
+    ///
     /// This will default to whatever the default in hyper is. As of v1.4.1, it is 16 KiB.
     #[must_use]
     pub fn http2_max_header_list_size(self, max: impl Into<Option<u32>>) -> Self {
@@ -371,6 +509,10 @@ impl<L> Server<L> {
     /// not correctly configured to handle grpc-web requests, your server may
     /// return confusing (but correct) protocol errors.
     ///
+    /// Without TLS, HTTP/2 connections must start with the HTTP/2 preface ("prior knowledge"),
+    /// as gRPC clients do. Upgrading an HTTP/1.1 connection with `Upgrade: h2c` isn't supported,
+    /// such requests are served as HTTP/1.1 when this is enabled.
+    ///
     /// Default is `false`.
     #[must_use]
     pub fn accept_http1(self, accept_http1: bool) -> Self {
@@ -508,6 +650,7 @@ impl<L> Server<L> {
             service_builder: self.service_builder.layer(new_layer),
             trace_interceptor: self.trace_interceptor,
             concurrency_limit: self.concurrency_limit,
+            rate_limit: self.rate_limit,
             timeout: self.timeout,
             #[cfg(feature = "tls")]
             tls: self.tls,
@@ -516,6 +659,7 @@ impl<L> Server<L> {
             max_concurrent_streams: self.max_concurrent_streams,
             tcp_keepalive: self.tcp_keepalive,
             tcp_nodelay: self.tcp_nodelay,
+            reuse_port: self.reuse_port,
             http2_keepalive_interval: self.http2_keepalive_interval,
             http2_keepalive_timeout: self.http2_keepalive_timeout,
             http2_adaptive_window: self.http2_adaptive_window,
@@ -524,6 +668,8 @@ impl<L> Server<L> {
             max_frame_size: self.max_frame_size,
             accept_http1: self.accept_http1,
             max_connection_age: self.max_connection_age,
+            max_connection_idle: self.max_connection_idle,
+            max_connections: self.max_connections,
         }
     }
 
@@ -550,6 +696,7 @@ impl<L> Server<L> {
     {
         let trace_interceptor = self.trace_interceptor.clone();
         let concurrency_limit = self.concurrency_limit;
+        let rate_limit = self.rate_limit.map(|rps| Arc::new(RateLimiter::new(rps)));
         let init_connection_window_size = self.init_connection_window_size;
         let init_stream_window_size = self.init_stream_window_size;
         let max_concurrent_streams = self.max_concurrent_streams;
@@ -565,6 +712,8 @@ impl<L> Server<L> {
         let http2_adaptive_window = self.http2_adaptive_window;
         let http2_max_pending_accept_reset_streams = self.http2_max_pending_accept_reset_streams;
         let max_connection_age = self.max_connection_age;
+        let max_connection_idle = self.max_connection_idle;
+        let connections = self.max_connections.map(ConnectionCounter::new);
 
         let svc = self.service_builder.service(svc);
 
@@ -576,6 +725,7 @@ impl<L> Server<L> {
         let mut svc = MakeSvc {
             inner: svc,
             concurrency_limit,
+            rate_limit,
             timeout,
             trace_interceptor,
             _io: PhantomData,
@@ -632,7 +782,13 @@ impl<L> Server<L> {
                         },
                     };
 
-                    trace!("connection accepted");
+                    let permit = connections.as_ref().map(ConnectionCounter::try_acquire);
+                    let over_limit = matches!(permit, Some(None));
+                    if over_limit {
+                        debug!("connection limit reached, closing connection");
+                    } else {
+                        trace!("connection accepted");
+                    }
 
                     poll_fn(|cx| svc.poll_ready(cx))
                         .await
@@ -643,10 +799,19 @@ impl<L> Server<L> {
                         .await
                         .map_err(super::Error::from_source)?;
 
+                    let idle = max_connection_idle.map(|timeout| (IdleTracker::new(), timeout));
+                    let req_svc = track_requests(req_svc, idle.as_ref().map(|(tracker, _)| tracker.clone()));
+
                     let hyper_io = TokioIo::new(io);
                     let hyper_svc = TowerToHyperService::new(req_svc.map_request(|req: Request<Incoming>| req.map(boxed)));
 
-                    serve_connection(hyper_io, hyper_svc, server.clone(), graceful.then(|| signal_rx.clone()), max_connection_age);
+                    let lifetime = ConnectionLifetime {
+                        max_age: max_connection_age,
+                        idle,
+                        permit: permit.flatten(),
+                        over_limit,
+                    };
+                    serve_connection(hyper_io, hyper_svc, server.clone(), graceful.then(|| signal_rx.clone()), lifetime);
                 }
             }
         }
@@ -667,14 +832,44 @@ impl<L> Server<L> {
     }
 }
 
+/// Checks an HTTP/2 initial window size, which must be between the default size and the maximum
+/// flow-control window.
+fn check_window_size(kind: &str, size: Option<u32>) {
+    const MIN: u32 = 65_535;
+    const MAX: u32 = (1 << 31) - 1;
+
+    if let Some(size) = size {
+        assert!(
+            (MIN..=MAX).contains(&size),
+            "initial {} window size must be between {} and {}, got {}",
+            kind,
+            MIN,
+            MAX,
+            size
+        );
+    }
+}
+
 // This is moved to its own function as a way to get around
 // https://github.com/rust-lang/rust/issues/102211
+/// What ends a connection besides the client and the shutdown signal, see [`serve_connection`].
+struct ConnectionLifetime {
+    /// See [`Server::max_connection_age`].
+    max_age: Option<Duration>,
+    /// Tracks the requests in flight, see [`Server::max_connection_idle`].
+    idle: Option<(IdleTracker, Duration)>,
+    /// Keeps the connection counted while it is served, see [`Server::max_connections`].
+    permit: Option<ConnectionPermit>,
+    /// Whether the connection is over [`Server::max_connections`], it is closed right away.
+    over_limit: bool,
+}
+
 fn serve_connection<B, IO, S, E>(
     hyper_io: IO,
     hyper_svc: S,
     builder: ConnectionBuilder<E>,
     mut watcher: Option<tokio::sync::watch::Receiver<()>>,
-    max_connection_age: Option<Duration>,
+    lifetime: ConnectionLifetime,
 ) where
     B: http_body::Body + Send + 'static,
     B::Data: Send,
@@ -692,10 +887,20 @@ fn serve_connection<B, IO, S, E>(
             });
 
             let mut conn = pin!(builder.serve_connection(hyper_io, hyper_svc));
+            if lifetime.over_limit {
+                conn.as_mut().graceful_shutdown();
+            }
 
-            let sleep = sleep_or_pending(max_connection_age);
+            let sleep = sleep_or_pending(lifetime.max_age);
             tokio::pin!(sleep);
 
+            let mut idle_expired = pin!(Fuse {
+                inner: lifetime
+                    .idle
+                    .as_ref()
+                    .map(|(tracker, timeout)| tracker.expired(*timeout)),
+            });
+
             loop {
                 tokio::select! {
                     rv = &mut conn => {
@@ -708,6 +913,10 @@ fn serve_connection<B, IO, S, E>(
                         conn.as_mut().graceful_shutdown();
                         sleep.set(sleep_or_pending(None));
                     },
+                    _ = &mut idle_expired => {
+                        debug!("closing idle connection");
+                        conn.as_mut().graceful_shutdown();
+                    },
                     _ = &mut sig => {
                         conn.as_mut().graceful_shutdown();
                     }
@@ -716,10 +925,65 @@ fn serve_connection<B, IO, S, E>(
         }
 
         drop(watcher);
+        drop(lifetime.permit);
         trace!("connection closed");
     });
 }
 
+/// Count the requests of a connection as in flight in `idle` until their response body ends,
+/// see [`Server::max_connection_idle`].
+fn track_requests(svc: BoxService, idle: Option<IdleTracker>) -> BoxService {
+    let Some(idle) = idle else {
+        return svc;
+    };
+
+    BoxCloneService::new(svc.map_future(move |future| {
+        let stream = idle.stream();
+        async move {
+            let response = future.await?;
+            Ok(response.map(|body| boxed(TrackedBody::new(body, stream))))
+        }
+    }))
+}
+
+/// Counts the connections being served, see [`Server::max_connections`].
+struct ConnectionCounter {
+    active: Arc<AtomicUsize>,
+    max: usize,
+}
+
+impl ConnectionCounter {
+    fn new(max: usize) -> Self {
+        Self {
+            active: Arc::default(),
+            max,
+        }
+    }
+
+    /// Count a new connection, unless the limit has been reached.
+    fn try_acquire(&self) -> Option<ConnectionPermit> {
+        self.active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| {
+                (active < self.max).then_some(active + 1)
+            })
+            .ok()
+            .map(|_| ConnectionPermit {
+                active: self.active.clone(),
+            })
+    }
+}
+
+/// Keeps a connection counted until dropped.
+struct ConnectionPermit {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
 async fn sleep_or_pending(wait_for: Option<Duration>) {
     match wait_for {
         Some(wait) => sleep(wait).await,
@@ -769,6 +1033,71 @@ impl<L> Router<L> {
         self
     }
 
+    /// Run `f` on each request before it is dispatched to a service.
+    ///
+    /// The interceptor gets the raw HTTP request, before the body is decoded into a gRPC message,
+    /// which is useful for access logging, rate limiting, or routing decisions based on headers
+    /// that don't fit into a codec. Returning an error rejects the request with that status,
+    /// without reaching the service. The interceptor runs inside the layers added with
+    /// [`Server::layer`], and only one interceptor can be set.
+    ///
+    /// Each connection gets its own clone of `f`, share state through an `Arc` if needed.
+    ///
+    /// ```rust
+    /// # use tonic::{transport::Server, Status};
+    /// # use tonic::server::NamedService;
+    /// # use std::convert::Infallible;
+    /// # fn ex<S>(svc: S)
+    /// # where S: tower_service::Service<http::Request<tonic::body::BoxBody>, Response = http::Response<tonic::body::BoxBody>, Error = Infallible>
+    /// #     + NamedService + Clone + Send + 'static, S::Future: Send + 'static {
+    /// let router = Server::builder()
+    ///     .add_service(svc)
+    ///     .with_raw_interceptor(|request: &http::Request<_>| {
+    ///         if request.headers().contains_key("x-blocked") {
+    ///             return Err(Status::permission_denied("blocked"));
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// # }
+    /// ```
+    pub fn with_raw_interceptor<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&Request<BoxBody>) -> Result<(), Status> + Clone + Send + Sync + 'static,
+    {
+        self.routes = self.routes.with_raw_interceptor(f);
+        self
+    }
+
+    /// Turn panics of the services into a status built by `f`.
+    ///
+    /// By default, a panic in a service doesn't produce a response, and the client only sees its
+    /// stream being closed. With a handler, the panic is caught and the request fails with the
+    /// status returned by `f`, which gets the panic payload. The panic hook still runs, so the
+    /// panic is reported as usual.
+    ///
+    /// Only panics raised while producing the response headers are caught, not the ones raised
+    /// while streaming the response body.
+    ///
+    /// ```rust
+    /// # use tonic::{transport::Server, Status};
+    /// # use tonic::server::NamedService;
+    /// # use std::convert::Infallible;
+    /// # fn ex<S>(svc: S)
+    /// # where S: tower_service::Service<http::Request<tonic::body::BoxBody>, Response = http::Response<tonic::body::BoxBody>, Error = Infallible>
+    /// #     + NamedService + Clone + Send + 'static, S::Future: Send + 'static {
+    /// let router = Server::builder()
+    ///     .add_service(svc)
+    ///     .handle_error(|_panic| Status::internal("internal error"));
+    /// # }
+    /// ```
+    pub fn handle_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Box<dyn std::any::Any + Send>) -> Status + Send + Sync + 'static,
+    {
+        self.routes = self.routes.handle_error(f);
+        self
+    }
+
     /// Convert this tonic `Router` into an axum `Router` consuming the tonic one.
     #[deprecated(since = "0.12.2", note = "Use `Routes::into_axum_router` instead.")]
     pub fn into_router(self) -> axum::Router {
@@ -791,8 +1120,13 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::Error>,
     {
-        let incoming = TcpIncoming::new(addr, self.server.tcp_nodelay, self.server.tcp_keepalive)
-            .map_err(super::Error::from_source)?;
+        let incoming = TcpIncoming::bind(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.reuse_port,
+        )
+        .map_err(super::Error::from_source)?;
         self.server
             .serve_with_shutdown::<_, _, future::Ready<()>, _, _, ResBody>(
                 self.routes.prepare(),
@@ -823,13 +1157,108 @@ impl<L> Router<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::Error>,
     {
-        let incoming = TcpIncoming::new(addr, self.server.tcp_nodelay, self.server.tcp_keepalive)
-            .map_err(super::Error::from_source)?;
+        let incoming = TcpIncoming::bind(
+            addr,
+            self.server.tcp_nodelay,
+            self.server.tcp_keepalive,
+            self.server.reuse_port,
+        )
+        .map_err(super::Error::from_source)?;
         self.server
             .serve_with_shutdown(self.routes.prepare(), incoming, Some(signal))
             .await
     }
 
+    /// Consume this [`Server`] creating a future that will execute the server
+    /// on a Unix domain socket bound at `path`.
+    ///
+    /// Connections are served through the same pipeline as TCP connections, with
+    /// [`UdsConnectInfo`] available in the request extensions. The socket file is removed
+    /// when the server stops. Binding fails if a file already exists at `path`.
+    ///
+    /// This method discards any provided [`Server`] TCP configuration.
+    ///
+    /// [`Server`]: struct.Server.html
+    #[cfg(unix)]
+    pub async fn serve_uds<P, ResBody>(self, path: P) -> Result<(), super::Error>
+    where
+        P: AsRef<std::path::Path>,
+        L: Layer<Routes>,
+        L::Service:
+            Service<Request<BoxBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<BoxBody>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<BoxBody>>>::Error:
+            Into<crate::Error> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::Error>,
+    {
+        self.serve_uds_inner::<_, future::Ready<()>, ResBody>(path, None)
+            .await
+    }
+
+    /// Consume this [`Server`] creating a future that will execute the server
+    /// on a Unix domain socket bound at `path`. And shutdown when the provided
+    /// signal is received.
+    ///
+    /// The socket file is removed once the server has shut down. See
+    /// [`Router::serve_uds`] for more details.
+    ///
+    /// [`Server`]: struct.Server.html
+    #[cfg(unix)]
+    pub async fn serve_uds_with_shutdown<P, F, ResBody>(
+        self,
+        path: P,
+        signal: F,
+    ) -> Result<(), super::Error>
+    where
+        P: AsRef<std::path::Path>,
+        F: Future<Output = ()>,
+        L: Layer<Routes>,
+        L::Service:
+            Service<Request<BoxBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<BoxBody>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<BoxBody>>>::Error:
+            Into<crate::Error> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::Error>,
+    {
+        self.serve_uds_inner(path, Some(signal)).await
+    }
+
+    #[cfg(unix)]
+    async fn serve_uds_inner<P, F, ResBody>(
+        self,
+        path: P,
+        signal: Option<F>,
+    ) -> Result<(), super::Error>
+    where
+        P: AsRef<std::path::Path>,
+        F: Future<Output = ()>,
+        L: Layer<Routes>,
+        L::Service:
+            Service<Request<BoxBody>, Response = Response<ResBody>> + Clone + Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<BoxBody>>>::Future: Send + 'static,
+        <<L as Layer<Routes>>::Service as Service<Request<BoxBody>>>::Error:
+            Into<crate::Error> + Send,
+        ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+        ResBody::Error: Into<crate::Error>,
+    {
+        let path = path.as_ref();
+        let listener = tokio::net::UnixListener::bind(path).map_err(super::Error::from_source)?;
+        let incoming = tokio_stream::wrappers::UnixListenerStream::new(listener);
+
+        let result = self
+            .server
+            .serve_with_shutdown(self.routes.prepare(), incoming, signal)
+            .await;
+
+        if let Err(err) = std::fs::remove_file(path) {
+            debug!("failed to remove unix socket {}: {}", path.display(), err);
+        }
+
+        result
+    }
+
     /// Consume this [`Server`] creating a future that will execute the server
     /// on the provided incoming stream of `AsyncRead + AsyncWrite`.
     ///
@@ -996,6 +1425,7 @@ impl<S> fmt::Debug for Svc<S> {
 #[derive(Clone)]
 struct MakeSvc<S, IO> {
     concurrency_limit: Option<usize>,
+    rate_limit: Option<Arc<RateLimiter>>,
     timeout: Option<Duration>,
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
@@ -1024,11 +1454,13 @@ where
 
         let svc = self.inner.clone();
         let concurrency_limit = self.concurrency_limit;
+        let rate_limit = self.rate_limit.clone();
         let timeout = self.timeout;
         let trace_interceptor = self.trace_interceptor.clone();
 
         let svc = ServiceBuilder::new()
             .layer_fn(RecoverError::new)
+            .layer_fn(|s| RateLimit::new(s, rate_limit.clone()))
             .option_layer(concurrency_limit.map(ConcurrencyLimitLayer::new))
             .layer_fn(|s| GrpcTimeout::new(s, timeout))
             .service(svc);
@@ -1092,3 +1524,114 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_window_sizes() {
+        let _ = Server::builder()
+            .initial_stream_window_size(65_535)
+            .initial_connection_window_size((1 << 31) - 1)
+            .initial_stream_window_size(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "initial stream window size must be between")]
+    fn rejects_small_stream_window_size() {
+        let _ = Server::builder().initial_stream_window_size(1024);
+    }
+
+    #[test]
+    #[should_panic(expected = "initial connection window size must be between")]
+    fn rejects_large_connection_window_size() {
+        let _ = Server::builder().initial_connection_window_size(1 << 31);
+    }
+
+    #[test]
+    fn connection_counter_releases_permits() {
+        let counter = ConnectionCounter::new(2);
+
+        let first = counter.try_acquire().unwrap();
+        let _second = counter.try_acquire().unwrap();
+        assert!(counter.try_acquire().is_none());
+
+        drop(first);
+        assert!(counter.try_acquire().is_some());
+    }
+
+    #[cfg(all(feature = "prost", feature = "channel"))]
+    #[tokio::test]
+    async fn closes_idle_connections() {
+        // Echoes the request message back after 200ms.
+        #[derive(Clone)]
+        struct SlowEcho;
+
+        impl Service<Request<BoxBody>> for SlowEcho {
+            type Response = Response<BoxBody>;
+            type Error = Infallible;
+            type Future =
+                Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+            fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, request: Request<BoxBody>) -> Self::Future {
+                Box::pin(async move {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    let mut response = Response::new(request.into_body());
+                    response.headers_mut().insert(
+                        http::header::CONTENT_TYPE,
+                        crate::metadata::GRPC_CONTENT_TYPE,
+                    );
+                    Ok(response)
+                })
+            }
+        }
+
+        impl NamedService for SlowEcho {
+            const NAME: &'static str = "test.SlowEcho";
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let incoming = {
+            let accepted = accepted.clone();
+            tokio_stream::wrappers::TcpListenerStream::new(listener).map(move |io| {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                io
+            })
+        };
+        tokio::spawn(
+            Server::builder()
+                .max_connection_idle(Duration::from_millis(100))
+                .add_service(SlowEcho)
+                .serve_with_incoming(incoming),
+        );
+
+        let channel = crate::transport::Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut grpc = crate::client::Grpc::new(channel);
+        let request = || crate::Request::new("hello".to_string());
+        let path = || http::uri::PathAndQuery::from_static("/test.SlowEcho/Echo");
+        let codec = crate::codec::ProstCodec::<String, String>::default;
+
+        // The call takes longer than the idle timeout, which only starts once it is done.
+        grpc.ready().await.unwrap();
+        grpc.unary(request(), path(), codec()).await.unwrap();
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+        // The server closes the connection, so the client has to reconnect.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        grpc.ready().await.unwrap();
+        grpc.unary(request(), path(), codec()).await.unwrap();
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+}
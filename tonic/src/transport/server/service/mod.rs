@@ -1,6 +1,9 @@
 mod io;
 pub(crate) use self::io::ServerIo;
 
+mod rate_limit;
+pub(crate) use self::rate_limit::{RateLimit, RateLimiter};
+
 mod recover_error;
 pub(crate) use self::recover_error::RecoverError;
 
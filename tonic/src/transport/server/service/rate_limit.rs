@@ -0,0 +1,162 @@
+use crate::Status;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tower::Service;
+
+/// A token bucket shared by every connection accepted by a server.
+///
+/// The bucket holds at most one second worth of tokens (and never less than one), so short
+/// bursts up to the configured rate are allowed.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: f64) -> Self {
+        let capacity = requests_per_second.max(1.0);
+        Self {
+            rate: requests_per_second,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token from the bucket, or returns how long to wait until one is available.
+    fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - state.tokens) / self.rate))
+        }
+    }
+}
+
+/// Middleware that rejects requests exceeding the server-wide rate limit.
+///
+/// Unlike `tower::limit::RateLimit`, which waits for capacity in `poll_ready`, requests over the
+/// limit fail immediately with `RESOURCE_EXHAUSTED` and a `grpc-retry-after` metadata entry.
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimit<S> {
+    inner: S,
+    limiter: Option<Arc<RateLimiter>>,
+}
+
+impl<S> RateLimit<S> {
+    pub(crate) fn new(inner: S, limiter: Option<Arc<RateLimiter>>) -> Self {
+        Self { inner, limiter }
+    }
+}
+
+impl<S, R> Service<R> for RateLimit<S>
+where
+    S: Service<R>,
+    S::Error: Into<crate::Error>,
+{
+    type Response = S::Response;
+    type Error = crate::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        if let Some(limiter) = &self.limiter {
+            if let Err(retry_after) = limiter.try_acquire() {
+                return ResponseFuture::Limited {
+                    status: Some(rate_limited(retry_after)),
+                };
+            }
+        }
+
+        ResponseFuture::Inner {
+            inner: self.inner.call(req),
+        }
+    }
+}
+
+fn rate_limited(retry_after: Duration) -> Status {
+    let mut status = Status::resource_exhausted(format!(
+        "Rate limit exceeded, retry after {:?}",
+        retry_after
+    ));
+    status.set_retry_after(retry_after);
+    status
+}
+
+#[pin_project(project = ResponseFutureProj)]
+pub(crate) enum ResponseFuture<F> {
+    Inner {
+        #[pin]
+        inner: F,
+    },
+    Limited {
+        status: Option<Status>,
+    },
+}
+
+impl<F, Res, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Res, E>>,
+    E: Into<crate::Error>,
+{
+    type Output = Result<Res, crate::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ResponseFutureProj::Inner { inner } => inner.poll(cx).map_err(Into::into),
+            ResponseFutureProj::Limited { status } => {
+                Poll::Ready(Err(status.take().expect("polled after completion").into()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::{poll_fn, ready, Ready};
+    use tower::service_fn;
+
+    #[tokio::test]
+    async fn rejected_requests_carry_a_parsable_retry_after() {
+        let inner = service_fn(|()| -> Ready<Result<(), Status>> { ready(Ok(())) });
+        let mut svc = RateLimit::new(inner, Some(Arc::new(RateLimiter::new(1.0))));
+
+        poll_fn(|cx| svc.poll_ready(cx)).await.unwrap();
+        svc.call(()).await.unwrap();
+        let err = svc.call(()).await.unwrap_err();
+
+        let status = err.downcast::<Status>().unwrap();
+        assert_eq!(status.code(), crate::Code::ResourceExhausted);
+        let retry_after = status.parse_retry_after().unwrap();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::body::BoxBody;
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{sync::Notify, time::Instant};
+
+/// Tracks the streams open on a connection to find out when it has been idle for too long.
+#[derive(Clone)]
+pub(crate) struct IdleTracker {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    active: AtomicUsize,
+    idle_since: Mutex<Instant>,
+    became_idle: Notify,
+}
+
+impl IdleTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                active: AtomicUsize::new(0),
+                idle_since: Mutex::new(Instant::now()),
+                became_idle: Notify::new(),
+            }),
+        }
+    }
+
+    /// Mark a stream as open until the returned guard is dropped.
+    pub(crate) fn stream(&self) -> ActiveStream {
+        self.inner.active.fetch_add(1, Ordering::SeqCst);
+        ActiveStream {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Resolves once no stream has been open for `timeout`.
+    pub(crate) async fn expired(&self, timeout: Duration) {
+        loop {
+            if self.inner.active.load(Ordering::SeqCst) > 0 {
+                self.inner.became_idle.notified().await;
+                continue;
+            }
+
+            let deadline = *self.inner.idle_since.lock().unwrap() + timeout;
+            if Instant::now() >= deadline {
+                return;
+            }
+            tokio::time::sleep_until(deadline).await;
+
+            // A stream may have been opened, and possibly closed, in the meantime.
+            let deadline = *self.inner.idle_since.lock().unwrap() + timeout;
+            if self.inner.active.load(Ordering::SeqCst) == 0 && Instant::now() >= deadline {
+                return;
+            }
+        }
+    }
+}
+
+/// Keeps a stream open in its [`IdleTracker`] while alive.
+pub(crate) struct ActiveStream {
+    inner: Arc<Inner>,
+}
+
+impl Drop for ActiveStream {
+    fn drop(&mut self) {
+        // Update the instant before the count so that `expired` never sees a stale one.
+        *self.inner.idle_since.lock().unwrap() = Instant::now();
+        if self.inner.active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.inner.became_idle.notify_one();
+        }
+    }
+}
+
+/// A response body that keeps its stream open until it ends or is dropped.
+pub(crate) struct TrackedBody {
+    inner: BoxBody,
+    stream: Option<ActiveStream>,
+}
+
+impl TrackedBody {
+    pub(crate) fn new(inner: BoxBody, stream: ActiveStream) -> Self {
+        Self {
+            inner,
+            stream: Some(stream),
+        }
+    }
+}
+
+impl Body for TrackedBody {
+    type Data = Bytes;
+    type Error = crate::Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = Pin::new(&mut self.inner).poll_frame(cx);
+        if let Poll::Ready(None | Some(Err(_))) = &frame {
+            self.stream = None;
+        }
+        frame
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+
+    fn is_expired(tracker: &IdleTracker, timeout: Duration) -> bool {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        std::pin::pin!(tracker.expired(timeout))
+            .poll(&mut cx)
+            .is_ready()
+    }
+
+    fn noop_waker() -> std::task::Waker {
+        struct Noop;
+        impl std::task::Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+        Arc::new(Noop).into()
+    }
+
+    #[tokio::test]
+    async fn expires_after_streams_are_closed() {
+        let timeout = Duration::from_millis(50);
+        let tracker = IdleTracker::new();
+
+        let stream = tracker.stream();
+        tokio::time::sleep(timeout * 2).await;
+        assert!(!is_expired(&tracker, timeout));
+
+        drop(stream);
+        assert!(!is_expired(&tracker, timeout));
+
+        tokio::time::timeout(timeout * 4, tracker.expired(timeout))
+            .await
+            .unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 pub(crate) mod grpc_timeout;
+pub(crate) mod idle;
 #[cfg(feature = "tls")]
 pub(crate) mod tls;
 
 pub(crate) use self::grpc_timeout::GrpcTimeout;
+pub(crate) use self::idle::{IdleTracker, TrackedBody};
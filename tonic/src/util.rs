@@ -26,3 +26,45 @@ pub(crate) mod base64 {
             .with_decode_padding_mode(DecodePaddingMode::Indifferent),
     );
 }
+
+pub(crate) mod rng {
+    use std::hash::{BuildHasher, RandomState};
+
+    /// A SplitMix64 generator, fast and good enough to spread out retries or pick faults to
+    /// inject, but not suitable for anything security related.
+    #[derive(Debug, Clone)]
+    pub(crate) struct SplitMix64 {
+        state: u64,
+    }
+
+    impl SplitMix64 {
+        /// The increment of the state between two numbers.
+        pub(crate) const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+        /// A generator yielding the same numbers for the same `seed`.
+        pub(crate) fn new(seed: u64) -> Self {
+            Self { state: seed }
+        }
+
+        /// A generator seeded from the randomly keyed hasher of the standard library.
+        pub(crate) fn from_entropy() -> Self {
+            Self::new(RandomState::new().hash_one(0u8))
+        }
+
+        /// A number in `[0, 1)`.
+        pub(crate) fn next_f64(&mut self) -> f64 {
+            self.state = self.state.wrapping_add(Self::GAMMA);
+            Self::to_f64(self.state)
+        }
+
+        /// The number in `[0, 1)` of the generator when its state is `state`.
+        pub(crate) fn to_f64(state: u64) -> f64 {
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^= z >> 31;
+
+            (z >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+}